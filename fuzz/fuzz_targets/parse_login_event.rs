@@ -0,0 +1,12 @@
+#![no_main]
+
+use hosho::listener::logon::parse_login_event;
+use libfuzzer_sys::fuzz_target;
+
+// Event XML comes from the log, which other processes can write to. Any input must produce
+// `Ok` or `Err`, never a panic.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(xml) = std::str::from_utf8(data) {
+        let _ = parse_login_event(xml);
+    }
+});
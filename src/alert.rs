@@ -0,0 +1,112 @@
+use std::io::{self, Write};
+use std::sync::{Mutex, PoisonError};
+
+use async_trait::async_trait;
+use serde::Serialize;
+
+use crate::enrich::Severity;
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+use crate::report::{ACCOUNT_LOCKED_OUT, PerUserReport, Report};
+
+/// What makes an event worth alerting on rather than routine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlertThreshold {
+    min_severity: Severity,
+    min_risk_score: Option<u8>,
+}
+
+impl AlertThreshold {
+    /// Alerts on events graded `min_severity` or above, which with the default severity policy
+    /// covers brute force bursts and RDP from public addresses, and on account lockouts.
+    pub fn new(min_severity: Severity) -> Self {
+        Self {
+            min_severity,
+            min_risk_score: None,
+        }
+    }
+
+    /// Also alerts on logons scored at least `min_risk_score` by `RiskScorer`.
+    pub fn with_min_risk_score(mut self, min_risk_score: u8) -> Self {
+        self.min_risk_score = Some(min_risk_score);
+        self
+    }
+
+    pub fn is_alert(&self, event: &Event) -> bool {
+        event.severity >= self.min_severity
+            || self
+                .min_risk_score
+                .is_some_and(|min| event.risk_score.is_some_and(|score| score >= min))
+            || matches!(&event.details, EventDetails::Login(login)
+                if login.failure_reason.as_deref() == Some(ACCOUNT_LOCKED_OUT))
+            || matches!(&event.details, EventDetails::Lockout(_))
+    }
+}
+
+impl Default for AlertThreshold {
+    fn default() -> Self {
+        Self::new(Severity::High)
+    }
+}
+
+/// The routine activity an [`AlertGate`] held back since the last summary.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoutineSummary {
+    /// Every event held back, of any kind.
+    pub suppressed: u64,
+    /// The held-back logons, per user.
+    #[serde(flatten)]
+    pub report: Report,
+}
+
+impl RoutineSummary {
+    /// Writes the summary to `writer` as one JSON line.
+    pub fn write_json(&self, mut writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer(&mut writer, self)?;
+        writeln!(writer)?;
+        writer.flush()
+    }
+}
+
+/// Passes on only events that cross its threshold. Routine events are counted into a summary
+/// instead, so a quiet machine only produces output when something matters, plus a periodic
+/// [`RoutineSummary`] covering everything else. Operational events (heartbeats, query stats, and
+/// self-tests) always pass, so sinks can still tell Hosho is alive. Belongs at the end of the
+/// pipeline, after the enrichers that grade events.
+#[derive(Debug, Default)]
+pub struct AlertGate {
+    threshold: AlertThreshold,
+    summarizer: PerUserReport,
+    routine: Mutex<RoutineSummary>,
+}
+
+impl AlertGate {
+    pub fn new(threshold: AlertThreshold) -> Self {
+        Self {
+            threshold,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the routine activity held back since the last call, and starts a new summary.
+    pub fn take_summary(&self) -> RoutineSummary {
+        std::mem::take(&mut *self.routine.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+#[async_trait]
+impl Transform for AlertGate {
+    fn name(&self) -> &str {
+        "alert-gate"
+    }
+
+    async fn transform(&self, event: Event) -> Option<Event> {
+        if event.kind().is_operational() || self.threshold.is_alert(&event) {
+            return Some(event);
+        }
+        let mut routine = self.routine.lock().unwrap_or_else(PoisonError::into_inner);
+        routine.suppressed += 1;
+        self.summarizer.add(&mut routine.report, &event);
+        None
+    }
+}
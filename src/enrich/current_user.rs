@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+#[cfg(windows)]
+use windows_sys::Win32::System::RemoteDesktop::{
+    WTS_CURRENT_SERVER_HANDLE, WTS_INFO_CLASS, WTSDomainName, WTSFreeMemory,
+    WTSGetActiveConsoleSessionId, WTSQuerySessionInformationW, WTSUserName,
+};
+#[cfg(windows)]
+use windows_sys::core::PWSTR;
+
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+
+/// Returned by `WTSGetActiveConsoleSessionId` when no session is attached to the console.
+#[cfg(windows)]
+const NO_CONSOLE_SESSION: u32 = 0xFFFF_FFFF;
+
+enum Source {
+    Console,
+    Fixed(Option<String>),
+}
+
+/// Flags logon events whose user is the one currently signed in at the console, separating
+/// "me logging in" from everyone else on a single-user machine.
+pub struct CurrentUserEnricher {
+    source: Source,
+}
+
+impl CurrentUserEnricher {
+    /// Looks up the console user afresh for every event, so sign-outs and user switches are seen.
+    pub fn new() -> Self {
+        Self {
+            source: Source::Console,
+        }
+    }
+
+    /// Compares against a fixed user (formatted as `user@DOMAIN`), or no user at all.
+    pub fn fixed(user: Option<String>) -> Self {
+        Self {
+            source: Source::Fixed(user),
+        }
+    }
+
+    fn current_user(&self) -> Option<String> {
+        match &self.source {
+            Source::Console => console_user(),
+            Source::Fixed(user) => user.clone(),
+        }
+    }
+
+    /// Sets `is_current_user` on logon events. With nobody at the console (e.g. a headless
+    /// server), no logon is the current user's.
+    pub fn enrich(&self, event: &mut Event) {
+        let EventDetails::Login(login_event) = &mut event.details else {
+            return;
+        };
+
+        login_event.is_current_user = self
+            .current_user()
+            .is_some_and(|user| user.eq_ignore_ascii_case(&login_event.username()));
+    }
+}
+
+#[async_trait]
+impl Transform for CurrentUserEnricher {
+    fn name(&self) -> &str {
+        "current-user"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.enrich(&mut event);
+        Some(event)
+    }
+}
+
+impl Default for CurrentUserEnricher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Nobody is at a Windows console off Windows.
+#[cfg(not(windows))]
+fn console_user() -> Option<String> {
+    None
+}
+
+/// The user signed in at the physical console, formatted like `LogonEvent::username()`.
+#[cfg(windows)]
+fn console_user() -> Option<String> {
+    // SAFETY: takes no arguments and has no preconditions.
+    let session_id = unsafe { WTSGetActiveConsoleSessionId() };
+    if session_id == NO_CONSOLE_SESSION {
+        return None;
+    }
+
+    let user = query_session_string(session_id, WTSUserName)?;
+    Some(match query_session_string(session_id, WTSDomainName) {
+        Some(domain) => format!("{}@{}", user, domain),
+        None => user,
+    })
+}
+
+#[cfg(windows)]
+fn query_session_string(session_id: u32, class: WTS_INFO_CLASS) -> Option<String> {
+    let mut buffer: PWSTR = std::ptr::null_mut();
+    let mut bytes = 0u32;
+
+    // SAFETY: both out-pointers are valid for writes; on success the buffer is owned by us and
+    // released with WTSFreeMemory below.
+    let ok = unsafe {
+        WTSQuerySessionInformationW(
+            WTS_CURRENT_SERVER_HANDLE,
+            session_id,
+            class,
+            &mut buffer,
+            &mut bytes,
+        )
+    };
+    if ok == 0 || buffer.is_null() {
+        return None;
+    }
+
+    // The byte count includes the trailing NUL.
+    let len = (bytes as usize / size_of::<u16>()).saturating_sub(1);
+    // SAFETY: the buffer holds `bytes` bytes of UTF-16 as reported by the call above.
+    let value = String::from_utf16_lossy(unsafe { std::slice::from_raw_parts(buffer, len) });
+    // SAFETY: the buffer was allocated by WTSQuerySessionInformationW and is freed exactly once.
+    unsafe { WTSFreeMemory(buffer.cast()) };
+
+    Some(value).filter(|value| !value.is_empty())
+}
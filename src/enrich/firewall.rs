@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+
+/// Where Windows writes the firewall log unless Group Policy moves it.
+pub const DEFAULT_FIREWALL_LOG: &str = r"C:\Windows\System32\LogFiles\Firewall\pfirewall.log";
+
+/// How long entries are kept past the correlation window, so events read a little late still
+/// find the connection that preceded them.
+const RETENTION_SLACK_MINUTES: i64 = 15;
+
+/// The columns of the firewall log when it hasn't told us otherwise with a `#Fields:` header.
+const DEFAULT_COLUMNS: [&str; 8] = [
+    "date", "time", "action", "protocol", "src-ip", "dst-ip", "src-port", "dst-port",
+];
+
+/// What the Windows Firewall did with an inbound connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FirewallAction {
+    Allow,
+    Drop,
+}
+
+/// One inbound connection from the firewall log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirewallEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: FirewallAction,
+    pub source: IpAddr,
+}
+
+/// Column positions from a firewall log's `#Fields:` header.
+#[derive(Debug, Clone)]
+struct Columns {
+    date: usize,
+    time: usize,
+    action: usize,
+    source: usize,
+    path: Option<usize>,
+}
+
+impl Columns {
+    fn parse<'a>(names: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let names: Vec<_> = names.into_iter().collect();
+        let position = |name: &str| names.iter().position(|column| *column == name);
+        Some(Self {
+            date: position("date")?,
+            time: position("time")?,
+            action: position("action")?,
+            source: position("src-ip")?,
+            path: position("path"),
+        })
+    }
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self::parse(DEFAULT_COLUMNS).expect("default columns name every required field")
+    }
+}
+
+/// Parses `pfirewall.log` text into its inbound `ALLOW` and `DROP` entries. Header lines (`#`)
+/// are skipped except `#Fields:`, which sets the column order for the lines after it; without
+/// one the stock order is assumed. Timestamps are logged in local time and converted from `tz`.
+/// Outbound (`SEND`) entries and lines that don't parse are skipped.
+pub fn parse_firewall_log<Tz: TimeZone>(text: &str, tz: &Tz) -> Vec<FirewallEntry> {
+    let mut columns = Columns::default();
+    parse_lines(text, &mut columns, tz)
+}
+
+fn parse_lines<Tz: TimeZone>(text: &str, columns: &mut Columns, tz: &Tz) -> Vec<FirewallEntry> {
+    let mut entries = Vec::new();
+    for line in text.lines() {
+        if let Some(fields) = line.strip_prefix("#Fields:") {
+            if let Some(parsed) = Columns::parse(fields.split_whitespace()) {
+                *columns = parsed;
+            }
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some(entry) = parse_line(line, columns, tz) {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+fn parse_line<Tz: TimeZone>(line: &str, columns: &Columns, tz: &Tz) -> Option<FirewallEntry> {
+    let fields: Vec<_> = line.split_whitespace().collect();
+    if let Some(path) = columns.path
+        && fields.get(path).is_some_and(|path| *path == "SEND")
+    {
+        return None;
+    }
+
+    let action = match *fields.get(columns.action)? {
+        "ALLOW" => FirewallAction::Allow,
+        "DROP" => FirewallAction::Drop,
+        _ => return None,
+    };
+    let local = NaiveDateTime::parse_from_str(
+        &format!(
+            "{} {}",
+            fields.get(columns.date)?,
+            fields.get(columns.time)?
+        ),
+        "%Y-%m-%d %H:%M:%S",
+    )
+    .ok()?;
+    // An hour repeated when the clocks go back is ambiguous; take its first occurrence.
+    let timestamp = tz
+        .from_local_datetime(&local)
+        .earliest()?
+        .with_timezone(&Utc);
+
+    Some(FirewallEntry {
+        timestamp,
+        action,
+        source: fields.get(columns.source)?.parse().ok()?,
+    })
+}
+
+/// How much of the log has been read, and what it said.
+#[derive(Debug, Default)]
+struct State {
+    offset: u64,
+    created: Option<SystemTime>,
+    columns: Columns,
+    entries: HashMap<IpAddr, Vec<(DateTime<Utc>, FirewallAction)>>,
+    /// The latest logon looked up, which entries are pruned against.
+    high_water: Option<DateTime<Utc>>,
+    warned_missing: bool,
+}
+
+/// Tags logons with what the Windows Firewall logged for a connection from the same source
+/// address within a window of the logon, closing the gap between "something connected" and
+/// "someone logged on". Needs firewall logging enabled for dropped and/or successful
+/// connections; logons with no matching entry are left untagged.
+///
+/// The log is read incrementally as it grows. When Windows rotates it to `pfirewall.log.old`,
+/// the rest of the old file is read before starting the new one. A missing log is warned about
+/// once and otherwise treated as having no entries. In a pipeline, the log is read on the
+/// blocking pool.
+///
+/// Entries are kept until they fall out of the window (plus some slack) behind the latest logon
+/// looked up, so a logon read out of order still finds its connection.
+#[derive(Debug, Clone)]
+pub struct FirewallCorrelator {
+    path: PathBuf,
+    window: Duration,
+    /// `None` for the local time zone, whose offset is looked up for each entry so timestamps
+    /// stay right across daylight saving changes.
+    utc_offset: Option<FixedOffset>,
+    state: Arc<Mutex<State>>,
+}
+
+impl FirewallCorrelator {
+    pub fn new(path: impl AsRef<Path>, window: Duration) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            window,
+            utc_offset: None,
+            state: Arc::default(),
+        }
+    }
+
+    /// Reads the log's timestamps at this offset from UTC instead of the local time zone.
+    pub fn with_utc_offset(mut self, offset: FixedOffset) -> Self {
+        self.utc_offset = Some(offset);
+        self
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".old");
+        PathBuf::from(path)
+    }
+
+    /// Reads whatever complete lines were appended to the log since the last call.
+    fn refresh(&self, state: &mut State) {
+        let metadata = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                if !state.warned_missing {
+                    eprintln!("Can't read firewall log {}: {}", self.path.display(), e);
+                    state.warned_missing = true;
+                }
+                return;
+            }
+        };
+        state.warned_missing = false;
+
+        // A new file either starts out shorter than what we've read, or, where the filesystem
+        // records it, with a different creation time.
+        let created = metadata.created().ok();
+        let recreated = matches!((state.created, created), (Some(old), Some(new)) if old != new);
+        if metadata.len() < state.offset || recreated {
+            // Rotated: finish the lines written to the old file after our last read.
+            let _ = self.read_from(&self.rotated_path(), state);
+            state.offset = 0;
+        }
+        state.created = created;
+        if let Err(e) = self.read_from(&self.path, state) {
+            eprintln!("Can't read firewall log {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn read_from(&self, path: &Path, state: &mut State) -> std::io::Result<()> {
+        let mut file = std::fs::File::open(path)?;
+        file.seek(SeekFrom::Start(state.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        // A line still being written is picked up on the next read.
+        let Some(end) = buf.iter().rposition(|&b| b == b'\n').map(|i| i + 1) else {
+            return Ok(());
+        };
+        let text = String::from_utf8_lossy(&buf[..end]);
+        let entries = match self.utc_offset {
+            Some(offset) => parse_lines(&text, &mut state.columns, &offset),
+            None => parse_lines(&text, &mut state.columns, &Local),
+        };
+        for entry in entries {
+            state
+                .entries
+                .entry(entry.source)
+                .or_default()
+                .push((entry.timestamp, entry.action));
+        }
+        state.offset += end as u64;
+        Ok(())
+    }
+
+    /// What the firewall did with the connection from `source` nearest `at`, if one was logged
+    /// within the window. Reads the log, so blocks.
+    pub fn lookup(&self, source: IpAddr, at: DateTime<Utc>) -> Option<FirewallAction> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        self.refresh(&mut state);
+
+        let high_water = state.high_water.map_or(at, |high_water| high_water.max(at));
+        state.high_water = Some(high_water);
+        let horizon = high_water - self.window - Duration::minutes(RETENTION_SLACK_MINUTES);
+        state.entries.retain(|_, seen| {
+            seen.retain(|(timestamp, _)| *timestamp >= horizon);
+            !seen.is_empty()
+        });
+
+        state
+            .entries
+            .get(&source)?
+            .iter()
+            .filter(|(timestamp, _)| (*timestamp - at).abs() <= self.window)
+            .min_by_key(|(timestamp, _)| (*timestamp - at).abs())
+            .map(|(_, action)| *action)
+    }
+
+    /// Sets `firewall_action` on logon events with a source address. Reads the log, so blocks.
+    pub fn enrich(&self, event: &mut Event) {
+        let at = event.timestamp;
+        if let EventDetails::Login(login_event) = &mut event.details
+            && let Some(source) = login_event.source_addr()
+        {
+            login_event.firewall_action = self.lookup(source, at);
+        }
+    }
+}
+
+#[async_trait]
+impl Transform for FirewallCorrelator {
+    fn name(&self) -> &str {
+        "firewall"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        let correlator = self.clone();
+        let enriched = tokio::task::spawn_blocking(move || {
+            correlator.enrich(&mut event);
+            event
+        });
+        match enriched.await {
+            Ok(event) => Some(event),
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => {
+                eprintln!("Firewall lookup failed: {}", e);
+                None
+            }
+        }
+    }
+}
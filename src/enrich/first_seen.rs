@@ -0,0 +1,150 @@
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SentinelError;
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+
+#[derive(Debug, Default)]
+struct Seen {
+    ips: BTreeSet<String>,
+    users: BTreeSet<String>,
+}
+
+/// One line of the state file: an address or a username seen for the first time.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Entry {
+    Ip(String),
+    User(String),
+}
+
+/// Flags logons from a source address or for a username never seen before. Each newly seen
+/// address or username is appended to a JSON Lines file, so "first seen" holds across restarts
+/// and recording one costs a single short write however many are known.
+pub struct FirstSeenEnricher {
+    path: Option<PathBuf>,
+    seen: Seen,
+}
+
+impl FirstSeenEnricher {
+    /// Loads the seen sets from `path`, starting empty if it doesn't exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        let path = path.as_ref().to_path_buf();
+        let error = |e: &dyn std::fmt::Display| {
+            SentinelError::StateError(format!("{}: {}", path.display(), e))
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(error(&e)),
+        };
+
+        let mut seen = Seen::default();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str(line).map_err(|e| error(&e))? {
+                Entry::Ip(ip) => seen.ips.insert(ip),
+                Entry::User(user) => seen.users.insert(user),
+            };
+        }
+
+        Ok(Self {
+            path: Some(path),
+            seen,
+        })
+    }
+
+    /// Keeps the seen sets only for the life of the process.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            seen: Seen::default(),
+        }
+    }
+
+    /// Sets `first_seen_ip` and `first_seen_user` on logon events. Placeholder addresses are
+    /// never first seen, and usernames compare case-insensitively.
+    pub fn enrich(&mut self, event: &mut Event) -> Result<(), SentinelError> {
+        let entries = self.record(event);
+        append(self.path.as_deref(), &entries)
+    }
+
+    /// Updates the seen sets and flags `event`, returning what's new to append to the file.
+    fn record(&mut self, event: &mut Event) -> Vec<Entry> {
+        let EventDetails::Login(login_event) = &mut event.details else {
+            return Vec::new();
+        };
+
+        let mut entries = Vec::new();
+        login_event.first_seen_ip = match login_event.source_ip.as_str() {
+            "" | "-" | "N/A" => false,
+            ip => self.seen.ips.insert(ip.to_string()),
+        };
+        if login_event.first_seen_ip {
+            entries.push(Entry::Ip(login_event.source_ip.clone()));
+        }
+        let user = login_event.username().to_lowercase();
+        login_event.first_seen_user = self.seen.users.insert(user.clone());
+        if login_event.first_seen_user {
+            entries.push(Entry::User(user));
+        }
+        entries
+    }
+}
+
+/// Appends `entries` to the state file at `path`, if there is one, in a single write.
+fn append(path: Option<&Path>, entries: &[Entry]) -> Result<(), SentinelError> {
+    let Some(path) = path else {
+        return Ok(());
+    };
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let error =
+        |e: &dyn std::fmt::Display| SentinelError::StateError(format!("{}: {}", path.display(), e));
+
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry).map_err(|e| error(&e))?);
+        lines.push('\n');
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| error(&e))
+}
+
+/// Recording an event updates the seen sets, so the enricher runs behind a lock in a pipeline.
+/// The lock is only held to update the sets; the file is written on the blocking pool. Failing
+/// to save an entry is logged but doesn't hold the event back.
+#[async_trait]
+impl Transform for Mutex<FirstSeenEnricher> {
+    fn name(&self) -> &str {
+        "first-seen"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        let (path, entries) = {
+            let mut enricher = self.lock().unwrap_or_else(PoisonError::into_inner);
+            (enricher.path.clone(), enricher.record(&mut event))
+        };
+        if entries.is_empty() {
+            return Some(event);
+        }
+
+        let result = tokio::task::spawn_blocking(move || append(path.as_deref(), &entries)).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("Failed to record first-seen state: {}", e),
+            Err(e) => eprintln!("Failed to record first-seen state: {}", e),
+        }
+        Some(event)
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+
+use crate::sink::DisplayTz;
+
+/// The hours of the day, `[start_hour, end_hour)`, that count as business hours, judged in a
+/// time zone. A `start_hour` after `end_hour` wraps past midnight, so `22` to `6` is a night
+/// shift.
+///
+/// Shared by the severity policy, risk scoring, and `--follow` coloring so they agree on what
+/// counts as off-hours, and all follow `--display-tz`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusinessHours {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    display_tz: DisplayTz,
+}
+
+impl BusinessHours {
+    /// Business hours in the local time zone.
+    pub fn new(start_hour: u32, end_hour: u32) -> Self {
+        Self {
+            start_hour,
+            end_hour,
+            display_tz: DisplayTz::default(),
+        }
+    }
+
+    /// Judges hours in `display_tz` instead of the local time zone.
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.display_tz = display_tz;
+        self
+    }
+
+    /// Whether `timestamp` falls within business hours. The zone's offset is looked up for
+    /// each timestamp, so hours stay right across daylight saving changes.
+    pub fn contains(&self, timestamp: DateTime<Utc>) -> bool {
+        let hour = self.display_tz.hour(timestamp);
+        if self.start_hour <= self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+impl Default for BusinessHours {
+    /// 08:00-18:00 local time.
+    fn default() -> Self {
+        Self::new(8, 18)
+    }
+}
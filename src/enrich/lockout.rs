@@ -0,0 +1,92 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::listener::{Event, EventDetails, LogonEvent};
+use crate::pipeline::Transform;
+
+/// How many failed logons are kept per user by default. A lockout threshold is rarely above
+/// 50, so this keeps every failure that could have counted towards one.
+pub const DEFAULT_MAX_PER_USER: usize = 100;
+
+/// Attaches the failed logons that led to an account lockout (4740) to the lockout, as its
+/// `causes`. Failed logons are buffered per user for `window` before they're forgotten, up to
+/// a number per user; each lockout takes the failures buffered for its account, so a later
+/// lockout of the same account only gets the failures since.
+///
+/// 4740 logs the account without its domain, so failures are matched by user name alone,
+/// ignoring case. Only failures that reach the pipeline before the lockout are attached.
+#[derive(Debug)]
+pub struct LockoutCorrelator {
+    window: Duration,
+    max_per_user: usize,
+    failures: Mutex<HashMap<String, VecDeque<(DateTime<Utc>, LogonEvent)>>>,
+}
+
+impl LockoutCorrelator {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            max_per_user: DEFAULT_MAX_PER_USER,
+            failures: Mutex::default(),
+        }
+    }
+
+    /// Keeps at most this many failed logons per user, dropping the oldest.
+    pub fn with_max_per_user(mut self, max_per_user: usize) -> Self {
+        self.max_per_user = max_per_user;
+        self
+    }
+
+    /// Buffers failed logons and sets `causes` on lockouts.
+    pub fn correlate(&self, event: &mut Event) {
+        let at = event.timestamp;
+        let mut failures = self.failures.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let horizon = at - self.window;
+        failures.retain(|_, buffered| {
+            while buffered.front().is_some_and(|(seen, _)| *seen < horizon) {
+                buffered.pop_front();
+            }
+            !buffered.is_empty()
+        });
+
+        match &mut event.details {
+            EventDetails::Login(login) if !login.success => {
+                let buffered = failures.entry(key(&login.target.user)).or_default();
+                if buffered.len() >= self.max_per_user {
+                    buffered.pop_front();
+                }
+                buffered.push_back((at, login.clone()));
+            }
+            EventDetails::Lockout(lockout) => {
+                if let Some(buffered) = failures.remove(&key(&lockout.account.user)) {
+                    lockout.causes = buffered
+                        .into_iter()
+                        .filter(|(seen, _)| *seen <= at)
+                        .map(|(_, login)| login)
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn key(user: &str) -> String {
+    user.to_lowercase()
+}
+
+#[async_trait]
+impl Transform for LockoutCorrelator {
+    fn name(&self) -> &str {
+        "lockout"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.correlate(&mut event);
+        Some(event)
+    }
+}
@@ -0,0 +1,38 @@
+pub mod current_user;
+pub mod firewall;
+pub mod first_seen;
+pub mod hours;
+pub mod lockout;
+pub mod reverse_dns;
+pub mod risk;
+pub mod severity;
+
+use std::net::IpAddr;
+
+pub use current_user::CurrentUserEnricher;
+pub use firewall::FirewallCorrelator;
+pub use first_seen::FirstSeenEnricher;
+pub use hours::BusinessHours;
+pub use lockout::LockoutCorrelator;
+pub use reverse_dns::ReverseDnsEnricher;
+pub use risk::RiskScorer;
+pub use severity::{Severity, SeverityPolicy};
+
+/// Whether `ip` is routable on the internet, i.e. not private, loopback, link-local, or similar.
+pub(crate) fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            let unique_local = segments[0] & 0xfe00 == 0xfc00;
+            let link_local = segments[0] & 0xffc0 == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+
+use super::is_public;
+
+/// Looks up the PTR name for an address, blocking the calling thread.
+pub type Resolver = Arc<dyn Fn(IpAddr) -> Option<String> + Send + Sync>;
+
+/// Entries kept before the cache is cleared, bounding memory during scans from many addresses.
+const CACHE_CAPACITY: usize = 4096;
+
+/// How long a failed or timed-out lookup is remembered before the address is tried again,
+/// unless set otherwise.
+pub const MISS_TTL: Duration = Duration::from_secs(300);
+
+/// Lookups allowed in flight at once. Past this, addresses go unresolved until one finishes.
+pub const MAX_PENDING_LOOKUPS: usize = 8;
+
+/// A cached lookup. Misses expire, so a resolver that was briefly down gets another chance.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    hostname: Option<String>,
+    expires: Option<Instant>,
+}
+
+type Cache = Arc<Mutex<HashMap<IpAddr, CacheEntry>>>;
+
+/// Resolves public logon source addresses to hostnames. Names are cached for good and misses for
+/// [`MISS_TTL`] by default, so an address under sustained attack costs one query.
+///
+/// An event waits at most `timeout` for its lookup, which goes on in the background after that
+/// and caches its answer for later events. At most [`MAX_PENDING_LOOKUPS`] run at once, so a
+/// scan from many addresses stalls the pipeline for a few timeouts rather than one per address.
+pub struct ReverseDnsEnricher {
+    resolver: Resolver,
+    timeout: Duration,
+    miss_ttl: Duration,
+    cache: Cache,
+    lookups: Arc<Semaphore>,
+}
+
+impl ReverseDnsEnricher {
+    /// Resolves with the system resolver, giving up on each lookup after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self::with_resolver(
+            timeout,
+            Arc::new(|ip| {
+                dns_lookup::lookup_addr(&ip)
+                    .ok()
+                    .filter(|name| name.parse::<IpAddr>().is_err())
+            }),
+        )
+    }
+
+    pub fn with_resolver(timeout: Duration, resolver: Resolver) -> Self {
+        Self {
+            resolver,
+            timeout,
+            miss_ttl: MISS_TTL,
+            cache: Cache::default(),
+            lookups: Arc::new(Semaphore::new(MAX_PENDING_LOOKUPS)),
+        }
+    }
+
+    /// Remembers failed lookups for `miss_ttl` instead of [`MISS_TTL`].
+    pub fn with_miss_ttl(mut self, miss_ttl: Duration) -> Self {
+        self.miss_ttl = miss_ttl;
+        self
+    }
+
+    /// Sets `source_hostname` on logon events from a public address that resolves in time.
+    pub async fn enrich(&self, event: &mut Event) {
+        let EventDetails::Login(login_event) = &mut event.details else {
+            return;
+        };
+        let Some(ip) = login_event.source_addr() else {
+            return;
+        };
+        if !is_public(ip) {
+            return;
+        }
+
+        login_event.source_hostname = self.resolve(ip).await;
+    }
+
+    async fn resolve(&self, ip: IpAddr) -> Option<String> {
+        if let Some(entry) = lock(&self.cache).get(&ip)
+            && entry.expires.is_none_or(|expires| Instant::now() < expires)
+        {
+            return entry.hostname.clone();
+        }
+        let Ok(permit) = Arc::clone(&self.lookups).try_acquire_owned() else {
+            return None;
+        };
+
+        let resolver = Arc::clone(&self.resolver);
+        let cache = Arc::clone(&self.cache);
+        let miss_ttl = self.miss_ttl;
+        let lookup = tokio::spawn(async move {
+            let _permit = permit;
+            let hostname = tokio::task::spawn_blocking(move || resolver(ip))
+                .await
+                .ok()
+                .flatten();
+            let entry = CacheEntry {
+                expires: hostname.is_none().then(|| Instant::now() + miss_ttl),
+                hostname: hostname.clone(),
+            };
+
+            let mut cache = lock(&cache);
+            if cache.len() >= CACHE_CAPACITY {
+                cache.clear();
+            }
+            cache.insert(ip, entry);
+            hostname
+        });
+
+        match tokio::time::timeout(self.timeout, lookup).await {
+            Ok(Ok(hostname)) => hostname,
+            _ => None,
+        }
+    }
+}
+
+fn lock(cache: &Cache) -> MutexGuard<'_, HashMap<IpAddr, CacheEntry>> {
+    cache.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[async_trait]
+impl Transform for ReverseDnsEnricher {
+    fn name(&self) -> &str {
+        "reverse-dns"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.enrich(&mut event).await;
+        Some(event)
+    }
+}
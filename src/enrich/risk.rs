@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+
+use crate::listener::logon::LogonVariant;
+use crate::listener::{Event, EventDetails, LogonEvent};
+use crate::pipeline::Transform;
+use crate::sink::DisplayTz;
+
+use super::{BusinessHours, is_public};
+
+/// The highest score a logon can get, however many factors add up.
+pub const MAX_RISK_SCORE: u8 = 100;
+
+/// How many points each risk factor adds to a logon's score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskWeights {
+    /// RDP, including cached RDP credentials.
+    pub remote_interactive: u8,
+    /// Network logons, including cleartext and `runas /netonly` credentials.
+    pub network: u8,
+    /// Console logons and unlocks.
+    pub interactive: u8,
+    /// Service and batch (scheduled task) logons.
+    pub service: u8,
+    /// Logon types not covered above.
+    pub other_logon_type: u8,
+    /// A source address outside private, loopback, and link-local ranges.
+    pub public_source: u8,
+    /// A logon outside business hours.
+    pub off_hours: u8,
+    /// Authentication with NTLM rather than Kerberos.
+    pub ntlm: u8,
+    /// A failed logon.
+    pub failure: u8,
+}
+
+impl RiskWeights {
+    fn logon_type(&self, variant: &LogonVariant) -> u8 {
+        match variant {
+            LogonVariant::RemoteInteractive | LogonVariant::CachedRemoteInteractive => {
+                self.remote_interactive
+            }
+            LogonVariant::Network
+            | LogonVariant::NetworkCleartext
+            | LogonVariant::NewCredentials => self.network,
+            LogonVariant::Interactive
+            | LogonVariant::CachedInteractive
+            | LogonVariant::Unlock
+            | LogonVariant::CachedUnlock => self.interactive,
+            LogonVariant::Service | LogonVariant::Batch => self.service,
+            LogonVariant::Unknown(_) | LogonVariant::Invalid(_) => self.other_logon_type,
+        }
+    }
+}
+
+impl Default for RiskWeights {
+    /// Weighted so that a failed NTLM RDP logon from the internet at night scores the maximum.
+    fn default() -> Self {
+        Self {
+            remote_interactive: 30,
+            network: 15,
+            interactive: 10,
+            service: 0,
+            other_logon_type: 10,
+            public_source: 30,
+            off_hours: 15,
+            ntlm: 10,
+            failure: 15,
+        }
+    }
+}
+
+/// Scores each logon by adding up the weights of the risk factors it has, capped at
+/// [`MAX_RISK_SCORE`]. Other events are left unscored.
+#[derive(Debug, Clone)]
+pub struct RiskScorer {
+    weights: RiskWeights,
+    business_hours: BusinessHours,
+}
+
+impl RiskScorer {
+    /// Treats 08:00-18:00 local time as business hours.
+    pub fn new(weights: RiskWeights) -> Self {
+        Self {
+            weights,
+            business_hours: BusinessHours::default(),
+        }
+    }
+
+    /// Counts logons outside `[start_hour, end_hour)` as off-hours. A `start_hour` after
+    /// `end_hour` wraps past midnight.
+    pub fn with_business_hours(mut self, start_hour: u32, end_hour: u32) -> Self {
+        self.business_hours.start_hour = start_hour;
+        self.business_hours.end_hour = end_hour;
+        self
+    }
+
+    /// Evaluates business hours in `display_tz` instead of the local time zone.
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.business_hours = self.business_hours.with_display_tz(display_tz);
+        self
+    }
+
+    pub fn score(&self, event: &Event, login_event: &LogonEvent) -> u8 {
+        let weights = &self.weights;
+
+        let factors = [
+            weights.logon_type(&login_event.variant),
+            if login_event.source_addr().is_some_and(is_public) {
+                weights.public_source
+            } else {
+                0
+            },
+            if self.business_hours.contains(event.timestamp) {
+                0
+            } else {
+                weights.off_hours
+            },
+            if login_event.is_ntlm() {
+                weights.ntlm
+            } else {
+                0
+            },
+            if login_event.success {
+                0
+            } else {
+                weights.failure
+            },
+        ];
+        factors
+            .into_iter()
+            .fold(0u8, u8::saturating_add)
+            .min(MAX_RISK_SCORE)
+    }
+
+    /// Sets `risk_score` on logon events.
+    pub fn enrich(&self, event: &mut Event) {
+        if let EventDetails::Login(login_event) = &event.details {
+            event.risk_score = Some(self.score(event, login_event));
+        }
+    }
+}
+
+#[async_trait]
+impl Transform for RiskScorer {
+    fn name(&self) -> &str {
+        "risk"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.enrich(&mut event);
+        Some(event)
+    }
+}
+
+impl Default for RiskScorer {
+    fn default() -> Self {
+        Self::new(RiskWeights::default())
+    }
+}
@@ -0,0 +1,233 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::listener::logon::LogonVariant;
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+use crate::sink::DisplayTz;
+
+use super::{BusinessHours, is_public};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    Display,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+pub enum Severity {
+    #[default]
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// A property of an event that a rule can test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// A logon of the given type.
+    LogonType(LogonVariant),
+    /// A logon from an IP address outside private, loopback, and link-local ranges.
+    PublicSource,
+    /// A failed logon.
+    Failure,
+    /// A logon outside business hours, `[start_hour, end_hour)` in the policy's time zone. A
+    /// `start_hour` after `end_hour` wraps past midnight, so `22` to `6` is a night shift.
+    OffHours { start_hour: u32, end_hour: u32 },
+    /// A collapsed logon failure representing at least this many attempts.
+    AttemptsAtLeast(u32),
+    /// An event a `Tagger` has given this tag.
+    Tagged(String),
+    /// A logon naming a disabled or nonexistent account.
+    SuspiciousAccount,
+    /// A logon as `ANONYMOUS LOGON`.
+    Anonymous,
+    /// A logon whose audit keywords contradict its event ID.
+    AuditKeywordMismatch,
+}
+
+/// Assigns `severity` to events meeting every one of `conditions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeverityRule {
+    pub conditions: Vec<Condition>,
+    pub severity: Severity,
+}
+
+impl SeverityRule {
+    pub fn new(conditions: Vec<Condition>, severity: Severity) -> Self {
+        Self {
+            conditions,
+            severity,
+        }
+    }
+}
+
+/// Decides how severe each event is. An event gets the highest severity among its kind's
+/// baseline and every rule it matches, so rules can only raise severity.
+#[derive(Debug, Clone)]
+pub struct SeverityPolicy {
+    rules: Vec<SeverityRule>,
+    display_tz: DisplayTz,
+}
+
+impl SeverityPolicy {
+    /// A policy with no rules, where every event gets its kind's baseline severity.
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            display_tz: DisplayTz::default(),
+        }
+    }
+
+    pub fn with_rule(mut self, rule: SeverityRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Evaluates `OffHours` conditions in `display_tz` instead of the local time zone.
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.display_tz = display_tz;
+        self
+    }
+
+    pub fn evaluate(&self, event: &Event) -> Severity {
+        self.rules
+            .iter()
+            .filter(|rule| rule.conditions.iter().all(|c| self.matches(c, event)))
+            .map(|rule| rule.severity)
+            .fold(baseline(event), Severity::max)
+    }
+
+    /// Sets the event's severity according to this policy.
+    pub fn enrich(&self, event: &mut Event) {
+        event.severity = self.evaluate(event);
+    }
+
+    fn matches(&self, condition: &Condition, event: &Event) -> bool {
+        let login_event = match &event.details {
+            EventDetails::Login(login_event) => Some(login_event),
+            _ => None,
+        };
+
+        match condition {
+            Condition::LogonType(variant) => login_event.is_some_and(|l| &l.variant == variant),
+            Condition::PublicSource => login_event
+                .and_then(|l| l.source_addr())
+                .is_some_and(is_public),
+            Condition::Failure => login_event.is_some_and(|l| !l.success),
+            Condition::OffHours {
+                start_hour,
+                end_hour,
+            } => {
+                let business_hours =
+                    BusinessHours::new(*start_hour, *end_hour).with_display_tz(self.display_tz);
+                login_event.is_some() && !business_hours.contains(event.timestamp)
+            }
+            Condition::AttemptsAtLeast(count) => {
+                login_event.is_some_and(|l| l.attempt_count >= *count)
+            }
+            Condition::Tagged(tag) => event.tags.contains(tag),
+            Condition::SuspiciousAccount => login_event.is_some_and(|l| l.suspicious_account),
+            Condition::Anonymous => login_event.is_some_and(|l| l.is_anonymous()),
+            Condition::AuditKeywordMismatch => {
+                login_event.is_some_and(|l| l.audit_keyword_mismatch())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transform for SeverityPolicy {
+    fn name(&self) -> &str {
+        "severity"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.enrich(&mut event);
+        Some(event)
+    }
+}
+
+impl Default for SeverityPolicy {
+    /// Raises failed logons from public addresses, outside 08:00-18:00, in bursts, and against
+    /// disabled or nonexistent accounts, failed RDP logons from public addresses further, and
+    /// anonymous network logons whether or not they succeed. Logons whose audit keywords
+    /// contradict their event ID are raised too, as likely forged.
+    fn default() -> Self {
+        use Condition::*;
+
+        Self::empty()
+            .with_rule(SeverityRule::new(
+                vec![Failure, PublicSource],
+                Severity::Medium,
+            ))
+            .with_rule(SeverityRule::new(
+                vec![
+                    Failure,
+                    OffHours {
+                        start_hour: 8,
+                        end_hour: 18,
+                    },
+                ],
+                Severity::Medium,
+            ))
+            .with_rule(SeverityRule::new(vec![AttemptsAtLeast(10)], Severity::High))
+            .with_rule(SeverityRule::new(vec![SuspiciousAccount], Severity::High))
+            .with_rule(SeverityRule::new(
+                vec![AuditKeywordMismatch],
+                Severity::High,
+            ))
+            .with_rule(SeverityRule::new(
+                vec![Anonymous, LogonType(LogonVariant::Network)],
+                Severity::High,
+            ))
+            .with_rule(SeverityRule::new(
+                vec![
+                    Failure,
+                    LogonType(LogonVariant::RemoteInteractive),
+                    PublicSource,
+                ],
+                Severity::High,
+            ))
+            .with_rule(SeverityRule::new(
+                vec![
+                    LogonType(LogonVariant::RemoteInteractive),
+                    PublicSource,
+                    AttemptsAtLeast(10),
+                ],
+                Severity::Critical,
+            ))
+    }
+}
+
+/// The severity of an event before any rules apply.
+fn baseline(event: &Event) -> Severity {
+    match &event.details {
+        EventDetails::Login(_) => Severity::Low,
+        EventDetails::ThreatDetected(_) | EventDetails::Lockout(_) => Severity::High,
+        EventDetails::AppBlocked(_) | EventDetails::RemoteExecution(_) => Severity::Medium,
+        EventDetails::UsbDevice(_)
+        | EventDetails::ScreenLock(_)
+        | EventDetails::Heartbeat { .. }
+        | EventDetails::QueryStats { .. }
+        | EventDetails::SelfTest => Severity::Info,
+        EventDetails::ListenerPanicked { restarting, .. } => {
+            if *restarting {
+                Severity::Medium
+            } else {
+                Severity::High
+            }
+        }
+    }
+}
@@ -10,6 +10,76 @@ pub enum SentinelError {
     #[error("Failed to query events: {0}")]
     EventQueryError(String),
 
+    #[error("Access denied to the {0} log. Reading it requires Administrator privileges")]
+    AccessDenied(String),
+
+    #[error("The {0} log does not exist on this machine")]
+    ChannelNotFound(String),
+
+    #[error("The query for the {0} log is malformed")]
+    QueryMalformed(String),
+
+    #[error("The XPath query for the {0} log is invalid: {1}")]
+    InvalidXPath(String, String),
+
+    #[error("Could not reach the event log service: {0}")]
+    Rpc(String),
+
+    #[error("Invalid event ID list: {0}")]
+    InvalidEventIds(String),
+
     #[error("Failed to send event to channel")]
     ChannelSendError,
+
+    #[error("Failed to write to sink: {0}")]
+    SinkError(String),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
+    #[error("Failed to load or save state: {0}")]
+    StateError(String),
+
+    #[error("Failed to read or write archive: {0}")]
+    ArchiveError(String),
+}
+
+// Win32 error codes `EvtQuery` reports for the failures callers handle differently.
+const ERROR_ACCESS_DENIED: i32 = 5;
+const RPC_S_SERVER_UNAVAILABLE: i32 = 1722;
+const RPC_S_CALL_FAILED: i32 = 1726;
+const ERROR_EVT_INVALID_CHANNEL_PATH: i32 = 15000;
+const ERROR_EVT_INVALID_QUERY: i32 = 15001;
+const ERROR_EVT_CHANNEL_NOT_FOUND: i32 = 15007;
+
+impl SentinelError {
+    /// Classifies a failed query against `channel` from the Win32 error code, falling back to the
+    /// message text when no code is available.
+    pub fn from_query_failure(channel: &str, os_error: Option<i32>, message: &str) -> Self {
+        match os_error {
+            Some(ERROR_ACCESS_DENIED) => SentinelError::AccessDenied(channel.to_string()),
+            Some(ERROR_EVT_CHANNEL_NOT_FOUND | ERROR_EVT_INVALID_CHANNEL_PATH) => {
+                SentinelError::ChannelNotFound(channel.to_string())
+            }
+            Some(ERROR_EVT_INVALID_QUERY) => SentinelError::QueryMalformed(channel.to_string()),
+            Some(RPC_S_SERVER_UNAVAILABLE | RPC_S_CALL_FAILED) => {
+                SentinelError::Rpc(message.to_string())
+            }
+            _ if message.contains("Access is denied") => {
+                SentinelError::AccessDenied(channel.to_string())
+            }
+            _ => SentinelError::EventQueryError(format!(
+                "Failed to query {} events: {}",
+                channel, message
+            )),
+        }
+    }
+
+    /// Whether retrying the same operation later could succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            SentinelError::Rpc(_) | SentinelError::EventQueryError(_) | SentinelError::SinkError(_)
+        )
+    }
 }
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+
+use crate::clock::Clock;
+use crate::listener::{Event, EventDetails};
+
+/// Counts failed logons per source address and per user over a rolling window, by when each
+/// failure happened rather than when it arrived. Counts are updated as events arrive and as they
+/// age out, so reading the standings never rescans history.
+pub struct Leaderboard {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    /// Each counted failure, oldest first, so the oldest can be expired from the front.
+    entries: VecDeque<Entry>,
+    by_source: HashMap<String, u32>,
+    by_user: HashMap<String, u32>,
+}
+
+struct Entry {
+    at: DateTime<Utc>,
+    source: Option<String>,
+    user: String,
+    attempts: u32,
+}
+
+impl Leaderboard {
+    pub fn new(window: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            window,
+            clock,
+            entries: VecDeque::new(),
+            by_source: HashMap::new(),
+            by_user: HashMap::new(),
+        }
+    }
+
+    /// Counts `event` if it's a failed logon. Collapsed events count once per attempt. Events can
+    /// arrive out of order, e.g. from several listeners, and one already older than the window
+    /// isn't counted.
+    pub fn record(&mut self, event: &Event) {
+        let EventDetails::Login(login) = &event.details else {
+            return;
+        };
+        if login.success {
+            return;
+        }
+
+        let entry = Entry {
+            at: event.timestamp,
+            source: login.source_addr().map(|ip| ip.to_string()),
+            user: login.username(),
+            attempts: login.attempt_count.max(1),
+        };
+        if let Some(source) = &entry.source {
+            *self.by_source.entry(source.clone()).or_default() += entry.attempts;
+        }
+        *self.by_user.entry(entry.user.clone()).or_default() += entry.attempts;
+        let position = self
+            .entries
+            .partition_point(|earlier| earlier.at <= entry.at);
+        self.entries.insert(position, entry);
+        self.expire();
+    }
+
+    /// The `n` source addresses with the most failures in the window, most first.
+    pub fn top_sources(&mut self, n: usize) -> Vec<(String, u32)> {
+        self.expire();
+        top(&self.by_source, n)
+    }
+
+    /// The `n` users with the most failures in the window, most first.
+    pub fn top_users(&mut self, n: usize) -> Vec<(String, u32)> {
+        self.expire();
+        top(&self.by_user, n)
+    }
+
+    /// The top `n` of each, as `{"window_secs": .., "sources": [{"source_ip": .., "failures":
+    /// ..}], "users": [{"username": .., "failures": ..}]}`.
+    pub fn to_json(&mut self, n: usize) -> Value {
+        let sources: Vec<_> = self
+            .top_sources(n)
+            .into_iter()
+            .map(|(source_ip, failures)| json!({ "source_ip": source_ip, "failures": failures }))
+            .collect();
+        let users: Vec<_> = self
+            .top_users(n)
+            .into_iter()
+            .map(|(username, failures)| json!({ "username": username, "failures": failures }))
+            .collect();
+        json!({
+            "window_secs": self.window.num_seconds(),
+            "sources": sources,
+            "users": users,
+        })
+    }
+
+    fn expire(&mut self) {
+        let cutoff = self.clock.now() - self.window;
+        while let Some(entry) = self.entries.front()
+            && entry.at < cutoff
+        {
+            let entry = self.entries.pop_front().expect("front was just checked");
+            if let Some(source) = &entry.source {
+                decrement(&mut self.by_source, source, entry.attempts);
+            }
+            decrement(&mut self.by_user, &entry.user, entry.attempts);
+        }
+    }
+}
+
+fn decrement(counts: &mut HashMap<String, u32>, key: &str, by: u32) {
+    if let Some(count) = counts.get_mut(key) {
+        *count = count.saturating_sub(by);
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// Highest counts first, ties broken by name so the order is stable.
+fn top(counts: &HashMap<String, u32>, n: usize) -> Vec<(String, u32)> {
+    let mut standings: Vec<_> = counts
+        .iter()
+        .map(|(name, &count)| (name.clone(), count))
+        .collect();
+    standings.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    standings.truncate(n);
+    standings
+}
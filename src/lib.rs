@@ -1,2 +1,15 @@
+pub mod alert;
+pub mod clock;
+pub mod enrich;
 pub mod errors;
+pub mod leaderboard;
 pub mod listener;
+pub mod pipeline;
+pub mod privileges;
+pub mod report;
+pub mod sink;
+pub mod suppress;
+pub mod tag;
+pub mod time_dedup;
+#[cfg(feature = "tui")]
+pub mod tui;
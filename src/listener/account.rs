@@ -0,0 +1,187 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::record::{EventRecord, non_placeholder, parse_hex};
+
+/// A Windows security identifier in its string form, e.g. `S-1-5-18`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Sid(String);
+
+impl Sid {
+    /// Accepts an `S-1-...` string. The null SID (`S-1-0-0`), which Windows reports when no
+    /// account applies, is treated as absent.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let valid = value
+            .strip_prefix("S-1-")
+            .is_some_and(|rest| rest.split('-').all(|part| part.parse::<u64>().is_ok()));
+        (valid && value != "S-1-0-0").then(|| Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The English name of a well-known SID, independent of the account name Windows reports
+    /// (which is localized, e.g. `Système` for SYSTEM on French installs).
+    pub fn well_known_name(&self) -> Option<&'static str> {
+        let name = match self.0.as_str() {
+            "S-1-1-0" => "Everyone",
+            "S-1-5-7" => "ANONYMOUS LOGON",
+            "S-1-5-18" => "SYSTEM",
+            "S-1-5-19" => "LOCAL SERVICE",
+            "S-1-5-20" => "NETWORK SERVICE",
+            "S-1-5-32-544" => "Administrators",
+            "S-1-5-32-545" => "Users",
+            "S-1-5-32-546" => "Guests",
+            sid if sid.starts_with("S-1-5-90-0-") => "Window Manager",
+            sid if sid.starts_with("S-1-5-96-0-") => "Font Driver Host",
+            sid if sid.starts_with("S-1-5-21-") && sid.ends_with("-500") => "Administrator",
+            sid if sid.starts_with("S-1-5-21-") && sid.ends_with("-501") => "Guest",
+            sid if sid.starts_with("S-1-5-21-") && sid.ends_with("-503") => "DefaultAccount",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    pub fn is_well_known(&self) -> bool {
+        self.well_known_name().is_some()
+    }
+
+    /// Whether this is `S-1-5-7`, the identity of unauthenticated (null session) logons.
+    pub fn is_anonymous(&self) -> bool {
+        self.0 == "S-1-5-7"
+    }
+}
+
+impl fmt::Display for Sid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A logon session's ID, logged in hex (`0x3e7`) in `SubjectLogonId`, `TargetLogonId`, and
+/// `TargetLinkedLogonId`. Compare these rather than the strings, which differ in case and
+/// leading zeros between event sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LogonId(pub u64);
+
+impl FromStr for LogonId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_hex(s.trim())
+            .map(Self)
+            .ok_or_else(|| format!("invalid logon ID '{}', expected hex like 0x3e7", s))
+    }
+}
+
+impl fmt::Display for LogonId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// A user or machine account as it appears in an event's `*UserName`, `*DomainName`, and
+/// `*UserSid` fields.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Account {
+    pub user: String,
+    pub domain: Option<String>,
+    pub sid: Option<Sid>,
+}
+
+impl Account {
+    pub fn new(user: &str, domain: Option<&str>) -> Self {
+        Self {
+            user: user.to_string(),
+            domain: domain.map(str::to_string),
+            sid: None,
+        }
+    }
+
+    pub fn with_sid(mut self, sid: Sid) -> Self {
+        self.sid = Some(sid);
+        self
+    }
+
+    /// Reads the account whose fields start with `prefix` (e.g. `Target` or `Subject`), treating
+    /// `-` placeholders as absent. Returns `None` when there's no user name.
+    pub(crate) fn from_record(record: &EventRecord, prefix: &str) -> Option<Self> {
+        let user = non_placeholder(record.get(&format!("{}UserName", prefix)))?;
+        Some(Self {
+            user,
+            domain: non_placeholder(record.get(&format!("{}DomainName", prefix))),
+            sid: record
+                .get(&format!("{}UserSid", prefix))
+                .and_then(|sid| Sid::parse(sid)),
+        })
+    }
+
+    /// `user@DOMAIN`, or the bare user without a domain.
+    pub fn upn(&self) -> String {
+        match &self.domain {
+            Some(domain) => format!("{}@{}", self.user, domain),
+            None => self.user.clone(),
+        }
+    }
+
+    /// `DOMAIN\user`, or the bare user without a domain.
+    pub fn downlevel(&self) -> String {
+        match &self.domain {
+            Some(domain) => format!("{}\\{}", domain, self.user),
+            None => self.user.clone(),
+        }
+    }
+
+    /// Whether both refer to the same account: by SID when both have one (so `ETHER$` acting as
+    /// `S-1-5-18` matches `SYSTEM`), otherwise by user and domain, ignoring case.
+    pub fn same_as(&self, other: &Account) -> bool {
+        if let (Some(sid), Some(other_sid)) = (&self.sid, &other.sid) {
+            return sid == other_sid;
+        }
+        let domain = |account: &Account| account.domain.as_deref().map(str::to_ascii_uppercase);
+        self.user.eq_ignore_ascii_case(&other.user) && domain(self) == domain(other)
+    }
+
+    /// Whether this is a computer account, which Windows names with a trailing `$`.
+    pub fn is_machine(&self) -> bool {
+        self.user.ends_with('$')
+    }
+
+    /// Whether this is the anonymous identity: by SID when there is one, otherwise by the
+    /// English `ANONYMOUS LOGON` name.
+    pub fn is_anonymous(&self) -> bool {
+        match &self.sid {
+            Some(sid) => sid.is_anonymous(),
+            None => self.user.eq_ignore_ascii_case("ANONYMOUS LOGON"),
+        }
+    }
+
+    /// Whether this is a built-in identity: a well-known SID (SYSTEM, the service accounts, the
+    /// built-in Administrator, ...) or, without a SID, one of the built-in service account names.
+    /// Those names are localized (`NETZWERKDIENST` on German installs), so the name fallback only
+    /// recognizes English ones.
+    pub fn is_wellknown(&self) -> bool {
+        if let Some(sid) = &self.sid {
+            return sid.is_well_known();
+        }
+
+        let user = self.user.to_ascii_uppercase();
+        matches!(
+            user.as_str(),
+            "SYSTEM" | "LOCAL SERVICE" | "NETWORK SERVICE" | "ANONYMOUS LOGON"
+        ) || user.starts_with("DWM-")
+            || user.starts_with("UMFD-")
+    }
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.upn())
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_xml_rs::from_str;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::record::inline_cdata;
+use super::winevt::QueryList;
+use super::{Event, EventDetails, EventListener, build_query, forward_events, query_channel};
+
+const EXE_CHANNEL: &str = "Microsoft-Windows-AppLocker/EXE and DLL";
+const SCRIPT_CHANNEL: &str = "Microsoft-Windows-AppLocker/MSI and Script";
+
+const EXE_BLOCKED: u32 = 8004;
+const SCRIPT_BLOCKED: u32 = 8007;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppBlockedEvent {
+    pub file_path: String,
+    /// The SID of the user the file was blocked for.
+    pub user_sid: Option<String>,
+    /// The rule collection that blocked the file (`EXE`, `DLL`, `MSI`, `SCRIPT`).
+    pub policy: Option<String>,
+    pub rule_name: Option<String>,
+    pub event_record_id: Option<u64>,
+}
+
+pub fn parse_app_blocked_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, AppBlockedEvent)> {
+    #[derive(Debug, Deserialize)]
+    struct AppLockerEvent {
+        #[serde(rename = "System")]
+        system: System,
+        #[serde(rename = "UserData")]
+        user_data: UserData,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct System {
+        #[serde(rename = "TimeCreated")]
+        time_created: TimeCreated,
+        #[serde(rename = "EventRecordID", default)]
+        event_record_id: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct TimeCreated {
+        #[serde(rename = "@SystemTime")]
+        system_time: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct UserData {
+        #[serde(rename = "RuleAndFileData")]
+        rule_and_file_data: RuleAndFileData,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct RuleAndFileData {
+        #[serde(rename = "FilePath")]
+        file_path: String,
+        #[serde(rename = "TargetUser")]
+        target_user: Option<String>,
+        #[serde(rename = "PolicyName")]
+        policy_name: Option<String>,
+        #[serde(rename = "RuleName")]
+        rule_name: Option<String>,
+    }
+
+    let event: AppLockerEvent =
+        from_str(&inline_cdata(xml)).map_err(|e| SentinelError::XmlParseError(e.to_string()))?;
+
+    let timestamp: DateTime<Utc> =
+        DateTime::parse_from_rfc3339(&event.system.time_created.system_time)
+            .map_err(|e| SentinelError::TimestampParseError(e.to_string()))?
+            .with_timezone(&Utc);
+
+    let data = event.user_data.rule_and_file_data;
+    let non_empty = |value: Option<String>| value.filter(|value| !value.is_empty() && value != "-");
+
+    Ok((
+        timestamp,
+        AppBlockedEvent {
+            file_path: data.file_path,
+            user_sid: non_empty(data.target_user),
+            policy: non_empty(data.policy_name),
+            rule_name: non_empty(data.rule_name),
+            event_record_id: event.system.event_record_id,
+        },
+    ))
+}
+
+/// Polls one AppLocker channel for block events. Each channel numbers its records
+/// independently, so executables and scripts are watched by separate listeners.
+pub struct AppLockerListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+    channel: &'static str,
+    event_id: u32,
+}
+
+impl Clone for AppLockerListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+            channel: self.channel,
+            event_id: self.event_id,
+        }
+    }
+}
+
+impl AppLockerListener {
+    /// Watches for blocked executables and DLLs.
+    pub fn executables(tx: mpsc::Sender<Event>) -> Self {
+        Self::new(tx, EXE_CHANNEL, EXE_BLOCKED)
+    }
+
+    /// Watches for blocked MSI installers and scripts.
+    pub fn scripts(tx: mpsc::Sender<Event>) -> Self {
+        Self::new(tx, SCRIPT_CHANNEL, SCRIPT_BLOCKED)
+    }
+
+    fn new(tx: mpsc::Sender<Event>, channel: &'static str, event_id: u32) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+            channel,
+            event_id,
+        }
+    }
+
+    fn get_query(channel: &str, event_id: u32) -> QueryList {
+        build_query(channel, &[event_id])
+    }
+
+    fn query_events(channel: &str, event_id: u32) -> anyhow::Result<Vec<Event>> {
+        query_channel(channel, Self::get_query(channel, event_id), |xml| {
+            let (timestamp, blocked_event) = parse_app_blocked_event(xml)?;
+            Ok(Event::new(
+                EventDetails::AppBlocked(blocked_event),
+                timestamp,
+            ))
+        })
+    }
+}
+
+impl EventListener for AppLockerListener {
+    fn invoke(&self) {
+        let (channel, event_id) = (self.channel, self.event_id);
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            move || Self::query_events(channel, event_id),
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query(self.channel, self.event_id))
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(self.channel)
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::{Path, PathBuf};
+
+use crate::errors::SentinelError;
+
+use super::Event;
+use super::dedup::{Watermark, diff_events};
+
+/// The highest `EventRecordID` a listener has delivered, saved to a file so a restarted listener
+/// resumes where it left off: it skips what was delivered before, and the first poll picks up
+/// whatever was written while it was down. The file holds just the record ID.
+#[derive(Debug)]
+pub struct Bookmark {
+    path: PathBuf,
+    record_id: Option<u64>,
+}
+
+impl Bookmark {
+    /// Loads the bookmark from `path`, starting from the beginning of the log if it doesn't exist
+    /// yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        let path = path.as_ref().to_path_buf();
+        let error = |e: &dyn std::fmt::Display| {
+            SentinelError::StateError(format!("{}: {}", path.display(), e))
+        };
+        let record_id = match std::fs::read_to_string(&path) {
+            Ok(contents) => Some(contents.trim().parse().map_err(|e| error(&e))?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(error(&e)),
+        };
+        Ok(Self { path, record_id })
+    }
+
+    pub fn record_id(&self) -> Option<u64> {
+        self.record_id
+    }
+
+    /// Drops the events in `events` delivered before, without moving the bookmark. As with the
+    /// dedup watermark, a batch entirely below the bookmark means the log was cleared, so all of
+    /// it passes.
+    pub fn skip_delivered(&self, events: Vec<Event>) -> Vec<Event> {
+        let watermark = self.record_id.map_or_else(Watermark::new, Watermark::at);
+        diff_events(watermark, events).0
+    }
+
+    /// Moves the bookmark to `record_id`, the highest one just delivered, and saves it. The file
+    /// is replaced rather than rewritten in place, so a crash mid-save leaves the old bookmark.
+    pub fn advance(&mut self, record_id: u64) -> Result<(), SentinelError> {
+        let error = |e: std::io::Error| {
+            SentinelError::StateError(format!("{}: {}", self.path.display(), e))
+        };
+        let partial = self.path.with_extension("partial");
+        std::fs::write(&partial, record_id.to_string()).map_err(error)?;
+        std::fs::rename(&partial, &self.path).map_err(error)?;
+        self.record_id = Some(record_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::record::parse_event_record;
+use super::{XmlSource, extract_text};
+
+/// How large a capture file grows by default before capturing stops.
+pub const DEFAULT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+static CAPTURE: OnceLock<Capture> = OnceLock::new();
+
+/// Identifies a log entry: its channel, computer, and `EventRecordID`.
+type RecordKey = (Option<String>, Option<String>, u64);
+
+struct State {
+    file: File,
+    written: u64,
+    full: bool,
+    /// The entries captured so far, since listeners read most entries again on every poll.
+    captured: HashSet<RecordKey>,
+}
+
+/// Appends the raw XML of every event a listener reads to a file, for reproducing missed or
+/// misparsed events later with [`replay_source`]. Each log entry is captured once, however many
+/// polls read it; entries without an `EventRecordID` are captured every time. Stops once the
+/// file reaches `max_bytes`, so a forgotten capture can't fill the disk.
+pub struct Capture {
+    path: PathBuf,
+    max_bytes: u64,
+    state: Mutex<State>,
+}
+
+impl Capture {
+    /// Opens `path` for appending, creating it if it doesn't exist. What's already in the file
+    /// counts towards `max_bytes`.
+    pub fn open(path: impl AsRef<Path>, max_bytes: u64) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            state: Mutex::new(State {
+                file,
+                written,
+                full: false,
+                captured: HashSet::new(),
+            }),
+        })
+    }
+
+    /// Appends one event's XML, returning whether it was written. An entry already captured
+    /// isn't written again. The first event that would take the file past its limit ends the
+    /// capture.
+    pub fn record(&self, xml: &str) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.full {
+            return false;
+        }
+        let key = record_key(xml);
+        if key.as_ref().is_some_and(|key| state.captured.contains(key)) {
+            return false;
+        }
+        let line = format!("{}\n", xml.trim());
+        if state.written + line.len() as u64 > self.max_bytes {
+            state.full = true;
+            eprintln!(
+                "Capture {} reached {} bytes, no longer capturing",
+                self.path.display(),
+                self.max_bytes
+            );
+            return false;
+        }
+        if let Err(e) = state.file.write_all(line.as_bytes()) {
+            state.full = true;
+            eprintln!(
+                "Capture {} failed ({}), no longer capturing",
+                self.path.display(),
+                e
+            );
+            return false;
+        }
+        state.written += line.len() as u64;
+        state.captured.extend(key);
+        true
+    }
+}
+
+fn record_key(xml: &str) -> Option<RecordKey> {
+    let record_id = extract_text(xml, "EventRecordID")?.parse().ok()?;
+    Some((
+        extract_text(xml, "Channel").map(str::to_string),
+        extract_text(xml, "Computer").map(str::to_string),
+        record_id,
+    ))
+}
+
+/// Captures every event read from now on with `capture`. Off unless configured. Only takes
+/// effect once; returns whether it did.
+pub fn configure(capture: Capture) -> bool {
+    CAPTURE.set(capture).is_ok()
+}
+
+/// Captures one event's XML, if capturing is configured.
+pub(crate) fn record(xml: &str) {
+    if let Some(capture) = CAPTURE.get() {
+        capture.record(xml);
+    }
+}
+
+/// Splits a capture, or any file of concatenated `<Event>` documents such as `wevtutil qe
+/// /f:xml` output or an Event Viewer XML export, into one XML string per event.
+pub fn split_events(text: &str) -> Vec<String> {
+    text.split_inclusive("</Event>")
+        .filter_map(|chunk| {
+            let start = [chunk.find("<Event "), chunk.find("<Event>")]
+                .into_iter()
+                .flatten()
+                .min()?;
+            chunk
+                .ends_with("</Event>")
+                .then(|| chunk[start..].to_string())
+        })
+        .collect()
+}
+
+/// Reads the events in the capture at `path` as an [`XmlSource`], keeping only those with one
+/// of `event_ids`, so a capture of every listener replays into one that expects only its own.
+/// The file is read once, up front.
+pub fn replay_source(path: impl AsRef<Path>, event_ids: &[u32]) -> io::Result<XmlSource> {
+    let xmls: Vec<_> = split_events(&fs::read_to_string(path)?)
+        .into_iter()
+        .filter(|xml| {
+            parse_event_record(xml).is_ok_and(|record| event_ids.contains(&record.event_id))
+        })
+        .collect();
+    Ok(Arc::new(move || Ok(xmls.clone())))
+}
@@ -0,0 +1,145 @@
+#[cfg(windows)]
+use windows_sys::Win32::System::EventLog::{EvtNextChannelPath, EvtOpenChannelEnum};
+
+#[cfg(windows)]
+use super::xpath::EvtHandle;
+use std::collections::HashSet;
+
+use super::EventListener;
+use crate::errors::SentinelError;
+
+#[cfg(windows)]
+const ERROR_ACCESS_DENIED: i32 = 5;
+#[cfg(windows)]
+const ERROR_INSUFFICIENT_BUFFER: i32 = 122;
+#[cfg(windows)]
+const ERROR_NO_MORE_ITEMS: i32 = 259;
+
+/// Lists the names of every event log channel registered on this machine, sorted, e.g.
+/// `Microsoft-Windows-TerminalServices-RemoteConnectionManager/Operational`. A channel being
+/// listed doesn't mean it can be read: the Security log still needs Administrator privileges.
+///
+/// If enumeration is refused partway through, the channels listed so far are returned rather
+/// than an error.
+pub fn list_channels() -> Result<Vec<String>, SentinelError> {
+    enumerate_channels().map(|(names, _)| names)
+}
+
+/// Lists the channels like [`list_channels`], along with whether the list is complete: `false`
+/// when enumeration was refused partway through.
+#[cfg(windows)]
+fn enumerate_channels() -> Result<(Vec<String>, bool), SentinelError> {
+    // SAFETY: a null session opens the local machine's channel list; the handle is closed by
+    // EvtHandle's Drop.
+    let channels = EvtHandle(unsafe { EvtOpenChannelEnum(0, 0) });
+    if channels.0 == 0 {
+        let error = std::io::Error::last_os_error();
+        return Err(SentinelError::EventQueryError(format!(
+            "Failed to list channels: {}",
+            error
+        )));
+    }
+
+    let mut names = Vec::new();
+    let mut complete = true;
+    let mut buffer = vec![0u16; 256];
+    loop {
+        let mut used = 0u32;
+        // SAFETY: `buffer` holds `buffer.len()` UTF-16 units and `used` is valid for writes.
+        let ok = unsafe {
+            EvtNextChannelPath(
+                channels.0,
+                buffer.len() as u32,
+                buffer.as_mut_ptr(),
+                &mut used,
+            )
+        };
+        if ok == 0 {
+            let error = std::io::Error::last_os_error();
+            match error.raw_os_error() {
+                Some(ERROR_NO_MORE_ITEMS) => break,
+                Some(ERROR_INSUFFICIENT_BUFFER) => {
+                    buffer.resize(used as usize, 0);
+                    continue;
+                }
+                Some(ERROR_ACCESS_DENIED) => {
+                    complete = false;
+                    break;
+                }
+                _ => {
+                    return Err(SentinelError::EventQueryError(format!(
+                        "Failed to list channels: {}",
+                        error
+                    )));
+                }
+            }
+        }
+        // `used` counts UTF-16 units, including the trailing NUL.
+        let len = (used as usize).saturating_sub(1);
+        names.push(String::from_utf16_lossy(&buffer[..len]));
+    }
+    names.sort_unstable_by_key(|name| name.to_lowercase());
+    Ok((names, complete))
+}
+
+/// There are no channels to list off Windows.
+#[cfg(not(windows))]
+fn enumerate_channels() -> Result<(Vec<String>, bool), SentinelError> {
+    Err(SentinelError::EventQueryError(format!(
+        "Failed to list channels: {}",
+        super::winevt::UNSUPPORTED
+    )))
+}
+
+/// The channels registered on this machine, listed once at startup so each listener can check
+/// that the channel it reads exists, e.g. an operational channel a Windows edition doesn't have.
+/// Channel names compare ignoring case, as the event log does.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelSet {
+    /// Lowercased names, or `None` if they couldn't be listed.
+    names: Option<HashSet<String>>,
+}
+
+impl ChannelSet {
+    /// Lists this machine's channels. If they can't all be listed, says so and treats every
+    /// channel as present, leaving a missing one to fail when it's queried; a partial list would
+    /// disable listeners whose channels exist but weren't reached.
+    pub fn list() -> Self {
+        match enumerate_channels() {
+            Ok((names, true)) => Self::from_names(names),
+            Ok((_, false)) => {
+                eprintln!(
+                    "Warning: can't check listener channels exist: listing them was refused \
+                     partway through"
+                );
+                Self::default()
+            }
+            Err(e) => {
+                eprintln!("Warning: can't check listener channels exist: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn from_names(names: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        Self {
+            names: Some(
+                names
+                    .into_iter()
+                    .map(|name| name.as_ref().to_lowercase())
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn contains(&self, channel: &str) -> bool {
+        self.names
+            .as_ref()
+            .is_none_or(|names| names.contains(&channel.to_lowercase()))
+    }
+
+    /// The channel `listener` reads, if it doesn't exist here.
+    pub fn missing<'a, L: EventListener>(&self, listener: &'a L) -> Option<&'a str> {
+        listener.channel().filter(|channel| !self.contains(channel))
+    }
+}
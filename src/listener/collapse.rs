@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::clock::Clock;
+use crate::errors::SentinelError;
+
+use super::account::LogonId;
+use super::{Event, EventDetails, LogonEvent};
+
+/// How many leading bits of a source address identify where an attempt came from, so attackers
+/// rotating through a subnet still count as one source. Written `V4,V6`, e.g. `24,64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPrefix {
+    pub v4: u8,
+    pub v6: u8,
+}
+
+impl KeyPrefix {
+    /// Zeroes every bit of `ip` past the prefix for its address family.
+    pub fn truncate(&self, ip: IpAddr) -> IpAddr {
+        match ip {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.v4)).unwrap_or(0);
+                IpAddr::V4((u32::from(ip) & mask).into())
+            }
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.v6)).unwrap_or(0);
+                IpAddr::V6((u128::from(ip) & mask).into())
+            }
+        }
+    }
+}
+
+impl FromStr for KeyPrefix {
+    type Err = SentinelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SentinelError::ConfigError(format!("invalid key prefix {:?}", s));
+        let (v4, v6) = s.split_once(',').ok_or_else(invalid)?;
+        let v4: u8 = v4
+            .trim()
+            .trim_start_matches('/')
+            .parse()
+            .map_err(|_| invalid())?;
+        let v6: u8 = v6
+            .trim()
+            .trim_start_matches('/')
+            .parse()
+            .map_err(|_| invalid())?;
+        if v4 > 32 || v6 > 128 {
+            return Err(invalid());
+        }
+        Ok(Self { v4, v6 })
+    }
+}
+
+/// Collapses consecutive failed logons for the same user, source, logon type, and failure reason
+/// into a single event whose `attempt_count` is the size of the run.
+///
+/// A run stays open until an event with a different key arrives, or until `window` has passed
+/// since its first attempt; only then is its representative (the first attempt) released. This
+/// holds back failed logons by up to `window`, so it's intended for timelines rather than
+/// real-time alerting. Successful logons and non-logon events pass through untouched.
+pub struct AttemptCollapser {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    key_prefix: Option<KeyPrefix>,
+    run: Option<Event>,
+}
+
+impl AttemptCollapser {
+    pub fn new(window: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            window,
+            clock,
+            key_prefix: None,
+            run: None,
+        }
+    }
+
+    /// Treats sources in the same subnet as the same source. The collapsed event keeps the
+    /// address of the run's first attempt.
+    pub fn with_key_prefix(mut self, key_prefix: KeyPrefix) -> Self {
+        self.key_prefix = Some(key_prefix);
+        self
+    }
+
+    fn same_source(key_prefix: Option<KeyPrefix>, a: &LogonEvent, b: &LogonEvent) -> bool {
+        if let Some(prefix) = key_prefix
+            && let (Some(a), Some(b)) = (a.source_addr(), b.source_addr())
+        {
+            return prefix.truncate(a) == prefix.truncate(b);
+        }
+        a.source_ip == b.source_ip
+    }
+
+    fn same_cause(key_prefix: Option<KeyPrefix>, a: &LogonEvent, b: &LogonEvent) -> bool {
+        a.target == b.target
+            && Self::same_source(key_prefix, a, b)
+            && a.variant == b.variant
+            && a.failure_reason == b.failure_reason
+    }
+
+    fn expired(&self, run: &Event, at: DateTime<Utc>) -> bool {
+        at - run.timestamp > self.window
+    }
+
+    /// Feeds a batch of events through the collapser, returning the events ready to be emitted:
+    /// pass-through events, and any runs that ended during (or expired after) the batch.
+    pub fn process(&mut self, events: Vec<Event>) -> Vec<Event> {
+        let mut ready = Vec::new();
+
+        for event in events {
+            let EventDetails::Login(login_event) = &event.details else {
+                ready.push(event);
+                continue;
+            };
+            if login_event.success {
+                ready.push(event);
+                continue;
+            }
+
+            if let Some(run) = &mut self.run
+                && let EventDetails::Login(run_event) = &mut run.details
+                && Self::same_cause(self.key_prefix, run_event, login_event)
+                && event.timestamp - run.timestamp <= self.window
+            {
+                run_event.attempt_count += login_event.attempt_count;
+                continue;
+            }
+
+            ready.extend(self.run.replace(event));
+        }
+
+        if let Some(run) = &self.run
+            && self.expired(run, self.clock.now())
+        {
+            ready.extend(self.run.take());
+        }
+
+        ready
+    }
+
+    /// Releases the open run, if any, regardless of its age.
+    pub fn flush(&mut self) -> Option<Event> {
+        self.run.take()
+    }
+}
+
+/// Merges successful logons for the same session, keyed by computer and `logon_id`, into one
+/// event: the logon with the most interactive type, filled in from the others with
+/// [`LogonEvent::merge`], at the position of the session's first logon. Events without a logon
+/// ID pass through untouched, and order is otherwise kept.
+pub fn collapse_sessions(events: Vec<Event>) -> Vec<Event> {
+    let mut merged: Vec<Event> = Vec::with_capacity(events.len());
+    let mut sessions: HashMap<(Option<String>, LogonId), usize> = HashMap::new();
+
+    for event in events {
+        let logon_id = match &event.details {
+            EventDetails::Login(login) if login.success => login.logon_id,
+            _ => None,
+        };
+        let Some(logon_id) = logon_id else {
+            merged.push(event);
+            continue;
+        };
+
+        match sessions.entry((event.computer.clone(), logon_id)) {
+            Entry::Occupied(index) => {
+                let kept = &mut merged[*index.get()];
+                if interactivity(&event) > interactivity(kept) {
+                    let earlier = std::mem::replace(kept, event);
+                    merge_into(kept, earlier);
+                } else {
+                    merge_into(kept, event);
+                }
+            }
+            Entry::Vacant(index) => {
+                index.insert(merged.len());
+                merged.push(event);
+            }
+        }
+    }
+    merged
+}
+
+/// Merges the logon in `other` into the one in `event`, which takes precedence.
+fn merge_into(event: &mut Event, other: Event) {
+    if let (EventDetails::Login(login), EventDetails::Login(other)) =
+        (&mut event.details, other.details)
+    {
+        *login = std::mem::take(login).merge(other);
+    }
+}
+
+fn interactivity(event: &Event) -> u8 {
+    match &event.details {
+        EventDetails::Login(login) => login.variant.interactivity(),
+        _ => 0,
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::Mutex;
+
+use super::Event;
+
+/// Decides which events in a freshly polled batch haven't been forwarded before.
+pub trait DedupStrategy: Send {
+    /// Returns the events in `events` not seen before, remembering them for later batches.
+    fn filter(&mut self, events: Vec<Event>) -> Vec<Event>;
+
+    /// Like [`filter`](Self::filter), but returns at most `max` events and only remembers those,
+    /// so the rest are returned by a later call. The default checks events one at a time, which
+    /// suits strategies that judge each event on its own.
+    fn filter_at_most(&mut self, events: Vec<Event>, max: usize) -> Vec<Event> {
+        let mut fresh = Vec::new();
+        for event in events {
+            if fresh.len() >= max {
+                break;
+            }
+            fresh.extend(self.filter(vec![event]));
+        }
+        fresh
+    }
+}
+
+/// A listener's dedup state, shared between its clones.
+pub type SharedDedup = Arc<Mutex<Box<dyn DedupStrategy>>>;
+
+/// Which property of an event identifies it for deduplication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DedupKey {
+    /// The `EventRecordID`. Correct for a single machine's log.
+    #[default]
+    RecordId,
+    /// The `EventRecordID` scoped to the source `Computer`, for WEF collectors whose log mixes
+    /// events from several hosts with overlapping record IDs.
+    RecordIdPlusComputer,
+    /// A hash of the event's contents, for sources without reliable record IDs.
+    ContentHash,
+}
+
+impl DedupKey {
+    pub fn strategy(self) -> Box<dyn DedupStrategy> {
+        match self {
+            DedupKey::RecordId => Box::new(Watermark::new()),
+            DedupKey::RecordIdPlusComputer => Box::new(PerComputerWatermark::new()),
+            DedupKey::ContentHash => Box::new(ContentHashDedup::new()),
+        }
+    }
+
+    pub fn shared(self) -> SharedDedup {
+        Arc::new(Mutex::new(self.strategy()))
+    }
+}
+
+impl FromStr for DedupKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "record-id" => Ok(DedupKey::RecordId),
+            "record-id-plus-computer" => Ok(DedupKey::RecordIdPlusComputer),
+            "content-hash" => Ok(DedupKey::ContentHash),
+            other => Err(format!(
+                "unknown dedup key '{}' (expected record-id, record-id-plus-computer, or content-hash)",
+                other
+            )),
+        }
+    }
+}
+
+/// Logged when the log is cleared (1102), or archived and started afresh once full (1104).
+const LOG_CLEARED: u32 = 1102;
+const LOG_FULL: u32 = 1104;
+
+/// Tracks the highest `EventRecordID` forwarded so far, so repeated polls of the same log only
+/// forward events that weren't seen before.
+///
+/// Record IDs only ever increase within a log, until the log is cleared (event 1102) and they
+/// restart from 1. A poll shows the log was cleared since the last one when its batch's highest
+/// record ID is below the watermark, when its lowest is below the last batch's lowest (a query's
+/// window only moves forward), or when it holds a new 1102 or 1104 numbered at or below the
+/// watermark. The last two catch a log refilled past the watermark between polls. Either way the
+/// watermark is reset rather than suppressing the new events.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Watermark {
+    last_record_id: Option<u64>,
+    /// The lowest record ID in the last batch that had one.
+    lowest_record_id: Option<u64>,
+    /// When the latest 1102 or 1104 seen was written.
+    last_cleared: Option<DateTime<Utc>>,
+}
+
+impl Watermark {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A watermark past `record_id`, knowing nothing about the batches before it.
+    pub fn at(record_id: u64) -> Self {
+        Self {
+            last_record_id: Some(record_id),
+            ..Self::default()
+        }
+    }
+
+    pub fn last_record_id(&self) -> Option<u64> {
+        self.last_record_id
+    }
+
+    /// Returns the events in `events` newer than the watermark, and advances it past them. Events
+    /// without a record ID can't be deduplicated and always pass through.
+    pub fn filter(&mut self, events: Vec<Event>) -> Vec<Event> {
+        let (fresh, next) = diff_events(*self, events);
+        *self = next;
+        fresh
+    }
+}
+
+/// Compares a freshly polled batch against the watermark before it, returning the events that
+/// are new and the watermark after it. This is [`Watermark`]'s logic as a pure function: events
+/// without a record ID always count as new, and a batch that shows the log was cleared counts
+/// every event in it as new.
+pub fn diff_events(prev: Watermark, current: Vec<Event>) -> (Vec<Event>, Watermark) {
+    let batch_max = current.iter().filter_map(Event::record_id).max();
+    let batch_min = current.iter().filter_map(Event::record_id).min();
+    let markers: Vec<(u64, u32, DateTime<Utc>)> = current
+        .iter()
+        .filter_map(|event| match event.event_id() {
+            Some(id @ (LOG_CLEARED | LOG_FULL)) => Some((event.record_id()?, id, event.timestamp)),
+            _ => None,
+        })
+        .collect();
+
+    let mut last = prev.last_record_id;
+    if let Some(prev_max) = last {
+        let reason = if let Some(batch_max) = batch_max
+            && batch_max < prev_max
+        {
+            Some(format!(
+                "event record IDs went backwards ({} < {})",
+                batch_max, prev_max
+            ))
+        } else if let (Some(batch_min), Some(prev_min)) = (batch_min, prev.lowest_record_id)
+            && batch_min < prev_min
+        {
+            Some(format!(
+                "the oldest event record ID went backwards ({} < {})",
+                batch_min, prev_min
+            ))
+        } else if prev.lowest_record_id.is_some()
+            && let Some((record_id, event_id, _)) = markers
+                .iter()
+                .find(|(id, _, at)| *id <= prev_max && Some(*at) > prev.last_cleared)
+        {
+            Some(format!("record {} is a new event {}", record_id, event_id))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            eprintln!(
+                "Warning: {}, the log was likely cleared; resetting watermark",
+                reason
+            );
+            last = None;
+        }
+    }
+
+    let fresh = current
+        .into_iter()
+        .filter(|event| match (event.record_id(), last) {
+            (Some(id), Some(last)) => id > last,
+            _ => true,
+        })
+        .collect();
+
+    let next = Watermark {
+        last_record_id: match batch_max {
+            Some(batch_max) => Some(last.map_or(batch_max, |last| last.max(batch_max))),
+            None => last,
+        },
+        lowest_record_id: batch_min.or(prev.lowest_record_id),
+        last_cleared: markers
+            .iter()
+            .map(|(_, _, at)| Some(*at))
+            .fold(prev.last_cleared, |latest, at| latest.max(at)),
+    };
+    (fresh, next)
+}
+
+impl DedupStrategy for Watermark {
+    fn filter(&mut self, events: Vec<Event>) -> Vec<Event> {
+        Watermark::filter(self, events)
+    }
+
+    /// Returns the oldest `max` new events by record ID, advancing the watermark only past them.
+    fn filter_at_most(&mut self, events: Vec<Event>, max: usize) -> Vec<Event> {
+        let (mut fresh, next) = diff_events(*self, events);
+        if fresh.len() <= max {
+            *self = next;
+            return fresh;
+        }
+
+        // Events without a record ID pass every time anyway, so they go last.
+        fresh.sort_by_key(|event| event.record_id().map_or((1, 0), |id| (0, id)));
+        fresh.truncate(max);
+        if let Some(returned_max) = fresh.iter().filter_map(Event::record_id).max() {
+            *self = Watermark {
+                last_record_id: Some(returned_max),
+                ..next
+            };
+        }
+        fresh
+    }
+}
+
+/// Keeps a separate [`Watermark`] per source computer.
+#[derive(Debug, Default)]
+pub struct PerComputerWatermark {
+    watermarks: HashMap<Option<String>, Watermark>,
+}
+
+impl PerComputerWatermark {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PerComputerWatermark {
+    fn by_computer(events: Vec<Event>) -> HashMap<Option<String>, Vec<Event>> {
+        let mut by_computer: HashMap<Option<String>, Vec<Event>> = HashMap::new();
+        for event in events {
+            by_computer
+                .entry(event.computer.clone())
+                .or_default()
+                .push(event);
+        }
+        by_computer
+    }
+}
+
+impl DedupStrategy for PerComputerWatermark {
+    fn filter(&mut self, events: Vec<Event>) -> Vec<Event> {
+        let mut fresh: Vec<Event> = Self::by_computer(events)
+            .into_iter()
+            .flat_map(|(computer, events)| {
+                self.watermarks.entry(computer).or_default().filter(events)
+            })
+            .collect();
+        fresh.sort_by_key(|event| event.timestamp);
+        fresh
+    }
+
+    /// Returns the oldest `max` new events across every computer. Each computer's events are
+    /// taken in record ID order, so its watermark only advances past the ones returned.
+    fn filter_at_most(&mut self, events: Vec<Event>, max: usize) -> Vec<Event> {
+        struct Pending {
+            computer: Option<String>,
+            queue: VecDeque<Event>,
+            next: Watermark,
+            returned_max: Option<u64>,
+        }
+
+        let mut pending: Vec<Pending> = Self::by_computer(events)
+            .into_iter()
+            .map(|(computer, events)| {
+                let watermark = self.watermarks.entry(computer.clone()).or_default();
+                let (mut fresh, next) = diff_events(*watermark, events);
+                // Events without a record ID pass every time anyway, so they go last.
+                fresh.sort_by_key(|event| event.record_id().map_or((1, 0), |id| (0, id)));
+                Pending {
+                    computer,
+                    queue: fresh.into(),
+                    next,
+                    returned_max: None,
+                }
+            })
+            .collect();
+
+        let mut fresh = Vec::new();
+        while fresh.len() < max {
+            let Some(next) = pending
+                .iter_mut()
+                .filter(|pending| !pending.queue.is_empty())
+                .min_by_key(|pending| pending.queue[0].timestamp)
+            else {
+                break;
+            };
+            let event = next.queue.pop_front().expect("queue is not empty");
+            next.returned_max = next.returned_max.max(event.record_id());
+            fresh.push(event);
+        }
+
+        for pending in pending {
+            let watermark = self.watermarks.entry(pending.computer).or_default();
+            if pending.queue.is_empty() {
+                *watermark = pending.next;
+            } else if pending.returned_max.is_some() {
+                *watermark = Watermark {
+                    last_record_id: pending.returned_max,
+                    ..pending.next
+                };
+            }
+        }
+        fresh.sort_by_key(|event| event.timestamp);
+        fresh
+    }
+}
+
+/// Remembers a hash of the contents of each event the query can still return. A hash is
+/// forgotten once its event is older than the oldest event in a later poll: the query window has
+/// moved past it, so it can't be returned again. Memory therefore follows the size of the query
+/// window rather than a fixed capacity, which would forget events still being returned once the
+/// window held more.
+#[derive(Debug, Default)]
+pub struct ContentHashDedup {
+    seen: HashMap<u64, DateTime<Utc>>,
+}
+
+impl ContentHashDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn content_hash(event: &Event) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        event.computer.hash(&mut hasher);
+        event.timestamp.hash(&mut hasher);
+        format!("{:?}", event.details).hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl DedupStrategy for ContentHashDedup {
+    fn filter(&mut self, events: Vec<Event>) -> Vec<Event> {
+        self.filter_at_most(events, usize::MAX)
+    }
+
+    /// Returns the first `max` new events in the order given. The window is judged from the
+    /// whole batch, including events past `max`.
+    fn filter_at_most(&mut self, events: Vec<Event>, max: usize) -> Vec<Event> {
+        let window_start = events.iter().map(|event| event.timestamp).min();
+
+        let mut fresh = Vec::new();
+        for event in events {
+            if fresh.len() >= max {
+                break;
+            }
+            if let Entry::Vacant(entry) = self.seen.entry(Self::content_hash(&event)) {
+                entry.insert(event.timestamp);
+                fresh.push(event);
+            }
+        }
+
+        if let Some(window_start) = window_start {
+            self.seen.retain(|_, timestamp| *timestamp >= window_start);
+        }
+        fresh
+    }
+}
@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::record::parse_event_record;
+use super::winevt::QueryList;
+use super::{Event, EventDetails, EventListener, build_query, forward_events, query_channel};
+
+const CHANNEL: &str = "Microsoft-Windows-Windows Defender/Operational";
+
+const MALWARE_DETECTED: u32 = 1116;
+const MALWARE_ACTION_TAKEN: u32 = 1117;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatEvent {
+    pub threat_name: String,
+    pub path: Option<String>,
+    pub severity: Option<String>,
+    /// The remediation Defender applied (e.g. `Quarantine`). `None` for the initial detection.
+    pub action: Option<String>,
+    pub event_record_id: Option<u64>,
+}
+
+/// Treats the empty and "Not Applicable" values Defender fills unused fields with as absent.
+fn meaningful(value: Option<&String>) -> Option<String> {
+    value
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty() && *value != "Not Applicable")
+        .map(str::to_string)
+}
+
+pub fn parse_threat_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, ThreatEvent)> {
+    let record = parse_event_record(xml)?;
+
+    if !matches!(record.event_id, MALWARE_DETECTED | MALWARE_ACTION_TAKEN) {
+        return Err(SentinelError::XmlParseError(format!(
+            "Expected a Defender detection event, got event ID {}",
+            record.event_id
+        ))
+        .into());
+    }
+
+    let threat_name = record.require("Threat Name")?.clone();
+
+    // Paths are reported as `file:_C:\...`, possibly several separated by `;`.
+    let path = meaningful(record.get("Path")).map(|path| {
+        path.split(';')
+            .map(|part| part.strip_prefix("file:_").unwrap_or(part))
+            .collect::<Vec<_>>()
+            .join(";")
+    });
+
+    let action = if record.event_id == MALWARE_ACTION_TAKEN {
+        meaningful(record.get("Action Name"))
+    } else {
+        None
+    };
+
+    Ok((
+        record.timestamp,
+        ThreatEvent {
+            threat_name,
+            path,
+            severity: meaningful(record.get("Severity Name")),
+            action,
+            event_record_id: record.event_record_id,
+        },
+    ))
+}
+
+pub struct DefenderListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+}
+
+impl Clone for DefenderListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+        }
+    }
+}
+
+impl DefenderListener {
+    pub fn new(tx: mpsc::Sender<Event>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+        }
+    }
+
+    fn get_query() -> QueryList {
+        build_query(CHANNEL, &[MALWARE_DETECTED, MALWARE_ACTION_TAKEN])
+    }
+
+    fn query_events() -> anyhow::Result<Vec<Event>> {
+        query_channel(CHANNEL, Self::get_query(), |xml| {
+            let (timestamp, threat_event) = parse_threat_event(xml)?;
+            Ok(Event::new(
+                EventDetails::ThreatDetected(threat_event),
+                timestamp,
+            ))
+        })
+    }
+}
+
+impl EventListener for DefenderListener {
+    fn invoke(&self) {
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            Self::query_events,
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query())
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(CHANNEL)
+    }
+}
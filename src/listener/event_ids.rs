@@ -0,0 +1,48 @@
+use crate::errors::SentinelError;
+
+/// Expands a spec like `4624-4625,4634;4648` into a sorted, deduplicated list of event IDs.
+/// Entries are separated by commas or semicolons, and each is either an ID or an inclusive range.
+pub fn parse_event_ids(spec: &str) -> Result<Vec<u32>, SentinelError> {
+    let mut ids = Vec::new();
+
+    for entry in spec.split([',', ';']).map(str::trim) {
+        if entry.is_empty() {
+            continue;
+        }
+
+        match entry.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse_id(start)?, parse_id(end)?);
+                if start > end {
+                    return Err(SentinelError::InvalidEventIds(format!(
+                        "range {} is reversed",
+                        entry
+                    )));
+                }
+                ids.extend(start..=end);
+            }
+            None => ids.push(parse_id(entry)?),
+        }
+    }
+
+    if ids.is_empty() {
+        return Err(SentinelError::InvalidEventIds(
+            "no event IDs given".to_string(),
+        ));
+    }
+
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
+
+fn parse_id(value: &str) -> Result<u32, SentinelError> {
+    let value = value.trim();
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|id| (1..=65535).contains(id))
+        .ok_or_else(|| {
+            SentinelError::InvalidEventIds(format!("'{}' is not an event ID in 1-65535", value))
+        })
+}
@@ -0,0 +1,132 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::logon::parse_login_event;
+use super::pool;
+use super::record::parse_event_record;
+use super::xpath::{QueryDirection, XPathQuery};
+use super::{Event, EventDetails, fetch_new_events, parse_events, send_events};
+
+/// Reads the logons in every `.evtx` archive in a directory once, oldest archive first, for
+/// offline analysis of rotated or collected logs. Files are read with `EvtQuery`, so this only
+/// works on Windows.
+///
+/// Archives from different machines, or from before a log was cleared, reuse record IDs, so
+/// events are deduplicated by content across files unless told otherwise.
+pub struct EvtxDirListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dir: PathBuf,
+    event_ids: Vec<u32>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+}
+
+impl EvtxDirListener {
+    pub fn new(tx: mpsc::Sender<Event>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dir: dir.into(),
+            event_ids: vec![4625],
+            dedup: DedupKey::ContentHash.shared(),
+            health: HealthTracker::new(),
+        }
+    }
+
+    /// Reads these Security event IDs instead of just 4625, as for `LogonListener`.
+    pub fn with_event_ids(mut self, event_ids: Vec<u32>) -> Self {
+        self.event_ids = event_ids;
+        self
+    }
+
+    /// Chooses how events already read from an earlier file are recognized. Defaults to
+    /// `DedupKey::ContentHash`.
+    pub fn with_dedup_key(mut self, key: DedupKey) -> Self {
+        self.dedup = key.shared();
+        self
+    }
+
+    /// The `.evtx` files directly in the directory, by name.
+    pub fn files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let is_evtx = path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("evtx"));
+            if is_evtx && path.is_file() {
+                files.push(path);
+            }
+        }
+        files.sort_by_key(|path| name(path));
+        Ok(files)
+    }
+
+    /// Sends the events of every file to the channel, each file in log order, and returns how
+    /// many were sent from each file, in the order read. A file that can't be read is recorded
+    /// in `health()`, skipped, and returned with `None`.
+    pub async fn run(self) -> Result<Vec<(PathBuf, Option<usize>)>, SentinelError> {
+        let files = self
+            .files()
+            .map_err(|e| SentinelError::ConfigError(format!("{}: {}", self.dir.display(), e)))?;
+
+        let mut starts = Vec::with_capacity(files.len());
+        for file in files {
+            let query = XPathQuery::events_in_file(&file, &self.event_ids);
+            let start = pool::shared()
+                .run(move || first_timestamp(&query))
+                .await
+                .ok()
+                .flatten();
+            starts.push((file, start));
+        }
+
+        let mut sent = Vec::new();
+        for file in chronological(starts) {
+            let query = XPathQuery::events_in_file(&file, &self.event_ids);
+            let events = fetch_new_events(&self.dedup, &self.health, None, move || {
+                parse_events(query.run(QueryDirection::Forward)?, parse_event)
+            })
+            .await;
+            let count = events.as_ref().map(Vec::len);
+            if let Some(events) = events {
+                send_events(&self.tx, events).await;
+            }
+            sent.push((file, count));
+        }
+        Ok(sent)
+    }
+
+    pub fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+}
+
+/// Orders files by when their oldest event was written, then files with no readable events by
+/// name, so archives rotated out of one log are read in the order they were written.
+pub fn chronological(mut files: Vec<(PathBuf, Option<DateTime<Utc>>)>) -> Vec<PathBuf> {
+    files.sort_by_key(|(path, start)| (start.is_none(), *start, name(path)));
+    files.into_iter().map(|(path, _)| path).collect()
+}
+
+fn name(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// When the oldest matching event in the file was written, if it has any.
+fn first_timestamp(query: &XPathQuery) -> Option<DateTime<Utc>> {
+    let xml = query.earliest(1).ok()?.into_iter().next()?;
+    parse_event_record(&xml).ok().map(|record| record.timestamp)
+}
+
+fn parse_event(xml: &str) -> anyhow::Result<Event> {
+    let (timestamp, login_event) = parse_login_event(xml)?;
+    Ok(Event::new(EventDetails::Login(login_event), timestamp))
+}
@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, Utc};
+use strum_macros::Display;
+
+/// How a listener receives events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum DeliveryMode {
+    /// The event log pushes new events as they're written.
+    Subscribe,
+    /// The listener queries the event log on an interval.
+    Poll,
+}
+
+/// How a listener's polls have been going.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListenerHealth {
+    /// When a poll last completed without error.
+    pub last_success: Option<DateTime<Utc>>,
+    /// Polls that have failed in a row since the last success.
+    pub consecutive_errors: u32,
+    pub last_error: Option<String>,
+    /// How the listener is receiving events. Unset for listeners that only ever poll, and until
+    /// one that chooses has started.
+    pub mode: Option<DeliveryMode>,
+}
+
+impl ListenerHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_errors == 0
+    }
+}
+
+/// A listener's health, updated by its polls and shared between its clones.
+#[derive(Debug, Clone, Default)]
+pub struct HealthTracker(Arc<Mutex<ListenerHealth>>);
+
+impl HealthTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_success(&self, at: DateTime<Utc>) {
+        let mut health = self.lock();
+        health.last_success = Some(at);
+        health.consecutive_errors = 0;
+    }
+
+    pub fn record_failure(&self, error: impl std::fmt::Display) {
+        let mut health = self.lock();
+        health.consecutive_errors += 1;
+        health.last_error = Some(error.to_string());
+    }
+
+    pub fn set_mode(&self, mode: DeliveryMode) {
+        self.lock().mode = Some(mode);
+    }
+
+    pub fn snapshot(&self) -> ListenerHealth {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ListenerHealth> {
+        // The guarded value is plain data, always left consistent, so a poisoned lock is safe.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
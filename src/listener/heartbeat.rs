@@ -0,0 +1,41 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+use tokio::sync::{Mutex, mpsc};
+
+use super::{Event, EventDetails, EventListener, send_events, supervise};
+
+/// Emits a heartbeat event on every poll, so a SIEM can tell a quiet collector from a dead one.
+/// Sequence numbers start at 1 and increase by one, exposing any heartbeats lost on the way.
+pub struct HeartbeatListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    seq: Arc<AtomicU64>,
+}
+
+impl Clone for HeartbeatListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            seq: Arc::clone(&self.seq),
+        }
+    }
+}
+
+impl HeartbeatListener {
+    pub fn new(tx: mpsc::Sender<Event>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            seq: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl EventListener for HeartbeatListener {
+    fn invoke(&self) {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed) + 1;
+        let event = Event::new(EventDetails::Heartbeat { seq }, Utc::now());
+        let tx = Arc::clone(&self.tx);
+        supervise::spawn(async move { send_events(&tx, vec![event]).await });
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::account::{Account, Sid};
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::logon::LogonEvent;
+use super::record::{non_placeholder, parse_event_record};
+use super::winevt::QueryList;
+use super::{
+    Event, EventDetails, EventListener, SECURITY_CHANNEL, build_query, forward_events,
+    query_channel,
+};
+
+pub const ACCOUNT_LOCKED_OUT: u32 = 4740;
+
+/// An account locked out after too many failed logons (4740), logged on the domain controller
+/// for domain accounts and on the machine itself for local ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockoutEvent {
+    /// The locked-out account. 4740 logs no domain for it.
+    pub account: Account,
+    /// The machine the failed logons came from, from `TargetDomainName`, which 4740 uses for
+    /// the caller computer name.
+    pub caller_computer: Option<String>,
+    /// The failed logons for the account leading up to the lockout. Only set by
+    /// `LockoutCorrelator`.
+    #[serde(default)]
+    pub causes: Vec<LogonEvent>,
+    pub event_record_id: Option<u64>,
+}
+
+pub fn parse_lockout_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, LockoutEvent)> {
+    let record = parse_event_record(xml)?;
+    if record.event_id != ACCOUNT_LOCKED_OUT {
+        return Err(SentinelError::XmlParseError(format!(
+            "Expected a lockout event, got event ID {}",
+            record.event_id
+        ))
+        .into());
+    }
+
+    let mut account = Account::new(record.require("TargetUserName")?, None);
+    if let Some(sid) = record.get("TargetSid").and_then(|sid| Sid::parse(sid)) {
+        account = account.with_sid(sid);
+    }
+
+    Ok((
+        record.timestamp,
+        LockoutEvent {
+            account,
+            caller_computer: non_placeholder(record.get("TargetDomainName")),
+            causes: Vec::new(),
+            event_record_id: record.event_record_id,
+        },
+    ))
+}
+
+pub struct LockoutListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+}
+
+impl Clone for LockoutListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+        }
+    }
+}
+
+impl LockoutListener {
+    pub fn new(tx: mpsc::Sender<Event>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+        }
+    }
+
+    fn get_query() -> QueryList {
+        build_query(SECURITY_CHANNEL, &[ACCOUNT_LOCKED_OUT])
+    }
+
+    fn query_events() -> anyhow::Result<Vec<Event>> {
+        query_channel(SECURITY_CHANNEL, Self::get_query(), |xml| {
+            let (timestamp, lockout_event) = parse_lockout_event(xml)?;
+            Ok(Event::new(EventDetails::Lockout(lockout_event), timestamp))
+        })
+    }
+}
+
+impl EventListener for LockoutListener {
+    fn invoke(&self) {
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            Self::query_events,
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query())
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(SECURITY_CHANNEL)
+    }
+}
@@ -1,127 +1,434 @@
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
-use serde_xml_rs::from_str;
-use std::collections::HashMap;
-use std::sync::Arc;
-use strum_macros::Display;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
+use strum_macros::{Display, IntoStaticStr};
 use tokio::sync::{Mutex, mpsc};
-use win_event_log::prelude::{Condition, EventFilter, Query, QueryItem, QueryList, WinEvents};
 
+use crate::clock::Clock;
+use crate::enrich::firewall::FirewallAction;
 use crate::errors::SentinelError;
 
-use super::EventDetails;
-use super::{Event, EventListener};
+use super::account::{Account, LogonId};
+use super::bookmark::Bookmark;
+use super::collapse::{AttemptCollapser, collapse_sessions};
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{DeliveryMode, HealthTracker, ListenerHealth};
+use super::pause::PauseHandle;
+use super::record::{audit_success, non_placeholder, parse_event_record, parse_hex, parse_ip};
+use super::saturation::DEFAULT_CHANNEL_CAPACITY;
+use super::schedule::PollSchedule;
+use super::supervise;
+use super::tail::Tail;
+use super::winevt::QueryList;
+use super::xpath::{QueryDirection, XPathQuery};
+use super::{
+    Event, EventDetails, EventListener, SECURITY_CHANNEL, Subscriber, XmlSource, build_query,
+    fetch_new_events, parse_events, poll, query_channel, send_events, subscribe_channel,
+};
 
-#[derive(Debug, Clone)]
+/// A parsed logon event. `Default` is a deliberately unset event (empty user, `Invalid` variant)
+/// for filling in the fields a test or consumer doesn't care about.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LogonEvent {
-    pub username: String,
+    /// The account being logged on.
+    pub target: Account,
+    /// The source address in canonical form, or as logged when it isn't an address (e.g. `-`).
     pub source_ip: String,
+    /// `IpAddress` exactly as logged, before normalization.
+    pub source_ip_raw: String,
+    /// The PTR name of `source_ip`. Only set by `ReverseDnsEnricher`.
+    pub source_hostname: Option<String>,
     pub variant: LogonVariant,
-    #[allow(dead_code)]
-    pub event_record_id: u32,
+    /// Whether the logon succeeded (4624) rather than failed (4625). For other event IDs, taken
+    /// from the audit success/failure bit of `keywords`.
+    pub success: bool,
+    /// The `System/EventID` the logon was parsed from, e.g. 4624 or 4648. `None` for logons
+    /// built by hand.
+    pub event_id: Option<u32>,
+    pub event_record_id: Option<u64>,
+    /// The `System/Keywords` bitmask.
+    pub keywords: Option<u64>,
+    /// The `System/Level`, 0 (log always) for Security events.
+    pub level: Option<u8>,
+    /// The `System/Task` category, e.g. 12544 for Logon.
+    pub task: Option<u16>,
+    /// Full path of the process that requested the logon, e.g. `C:\Windows\System32\services.exe`.
+    pub creator_process: Option<String>,
+    pub creator_process_id: Option<u32>,
+    /// `AuthenticationPackageName`, e.g. `NTLM`, `Kerberos`, or `Negotiate`.
+    pub authentication_package: Option<String>,
+    /// How many consecutive identical failures this event represents. Always 1 unless the
+    /// listener collapses repeated failures.
+    pub attempt_count: u32,
+    /// Whether this is the user signed in at the console. Only set by `CurrentUserEnricher`.
+    pub is_current_user: bool,
+    /// Whether no earlier logon came from `source_ip`. Only set by `FirstSeenEnricher`.
+    pub first_seen_ip: bool,
+    /// Whether no earlier logon was for `username`. Only set by `FirstSeenEnricher`.
+    pub first_seen_user: bool,
+    /// The logon session this logon created, from `TargetLogonId`. `None` on failures, which
+    /// create no session.
+    pub logon_id: Option<LogonId>,
+    /// What the Windows Firewall logged for a connection from `source_ip` around the time of
+    /// the logon. Only set by `FirewallCorrelator`.
+    pub firewall_action: Option<FirewallAction>,
+    /// The other half of a UAC split-token pair: an administrator's interactive logon creates a
+    /// filtered and an elevated session, each linked to the other.
+    pub linked_logon_id: Option<LogonId>,
+    /// The security context that requested the logon (often `SYSTEM` or the machine account),
+    /// as opposed to the target user being logged on.
+    pub subject: Option<Account>,
+    /// The logon session of `subject`.
+    pub subject_logon_id: Option<LogonId>,
+    /// How far the logon's token can act on the user's behalf. `Delegation` on a network logon
+    /// lets the server reach further hosts as the user.
+    pub impersonation_level: Option<ImpersonationLevel>,
+    /// The remote host explicit credentials (4648) were used against, from `TargetServerName`
+    /// or else the host part of `TargetInfo`. `None` when they were used on this machine
+    /// (`localhost`) or no server was logged (`-`).
+    pub target_server: Option<String>,
+    /// `FailureReason` as logged on failures, usually a message placeholder like `%%2313`.
+    pub failure_reason: Option<String>,
+    /// `failure_reason` resolved to the text Event Viewer shows, e.g. "Unknown user name or bad
+    /// password."
+    pub failure_reason_text: Option<String>,
+    /// The NTSTATUS code from `SubStatus` on failures, narrowing down why the logon failed, e.g.
+    /// `0xC0000064` (no such user). `None` when it was `0x0` or not logged.
+    pub sub_status: Option<u32>,
+    /// Whether the logon named an account that is disabled or doesn't exist, going by
+    /// `sub_status`. Attempts on such accounts are rarely honest mistakes.
+    pub suspicious_account: bool,
 }
 
-pub fn parse_login_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, LogonEvent)> {
-    #[derive(Debug, Deserialize)]
-    struct SecurityEvent {
-        #[serde(rename = "System")]
-        system: System,
-        #[serde(rename = "EventData")]
-        event_data: EventData,
+impl LogonEvent {
+    /// A logon of `target` from `source_ip`, with every optional field unset.
+    pub fn new(target: Account, source_ip: &str, variant: LogonVariant, success: bool) -> Self {
+        Self {
+            target,
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant,
+            success,
+            attempt_count: 1,
+            ..Self::default()
+        }
     }
 
-    #[derive(Debug, Deserialize)]
-    struct System {
-        #[serde(rename = "TimeCreated")]
-        time_created: TimeCreated,
-        #[serde(rename = "EventRecordID")]
-        event_record_id: u32,
+    /// Serializes the event as a JSON object.
+    pub fn into_json(&self) -> String {
+        serde_json::to_string(self).expect("LogonEvent fields always serialize")
     }
 
-    #[derive(Debug, Deserialize)]
-    struct TimeCreated {
-        #[serde(rename = "@SystemTime")]
-        system_time: String,
+    /// The outcome recorded by the audit success/failure bit of `keywords`, if any.
+    pub fn audit_success(&self) -> Option<bool> {
+        self.keywords.and_then(audit_success)
     }
 
-    #[derive(Debug, Deserialize)]
-    struct EventData {
-        #[serde(rename = "#content")]
-        data: Vec<DataField>,
+    /// Whether a 4624 or 4625 carries the audit keyword of the other outcome, which the event
+    /// log never writes itself, so the record was likely forged or tampered with.
+    pub fn audit_keyword_mismatch(&self) -> bool {
+        matches!(self.event_id, Some(LOGON_SUCCESS | LOGON_FAILURE))
+            && self
+                .audit_success()
+                .is_some_and(|audit_success| audit_success != self.success)
     }
 
-    #[derive(Debug, Deserialize)]
-    struct DataField {
-        #[serde(rename = "@Name")]
-        name: String,
-        #[serde(rename = "#text")]
-        value: String,
+    /// The target account as `user@DOMAIN`.
+    pub fn username(&self) -> String {
+        self.target.upn()
     }
 
-    let event: SecurityEvent =
-        from_str(xml).map_err(|e| SentinelError::XmlParseError(e.to_string()))?;
+    /// Whether the logon authenticated with NTLM rather than Kerberos.
+    pub fn is_ntlm(&self) -> bool {
+        self.authentication_package
+            .as_deref()
+            .is_some_and(|package| package.eq_ignore_ascii_case("NTLM"))
+    }
 
-    let timestamp_str = &event.system.time_created.system_time;
-    let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339(timestamp_str)
-        .map_err(|e| SentinelError::TimestampParseError(e.to_string()))?
-        .with_timezone(&Utc);
+    /// Whether the account requesting the logon is the one being logged on, as with service
+    /// logons where SYSTEM logs on SYSTEM. False when the subject wasn't recorded.
+    pub fn is_self_logon(&self) -> bool {
+        self.subject
+            .as_ref()
+            .is_some_and(|subject| subject.same_as(&self.target))
+    }
 
-    let mut data_fields = HashMap::<String, String>::new();
+    /// Whether the target is `ANONYMOUS LOGON`. Over the network this is a null session, often
+    /// used to enumerate users and shares.
+    pub fn is_anonymous(&self) -> bool {
+        self.target.is_anonymous()
+    }
 
-    for data_field in &event.event_data.data {
-        if let "TargetUserName" | "TargetDomainName" | "IpAddress" | "LogonType" =
-            data_field.name.as_str()
-        {
-            data_fields.insert(data_field.name.clone(), data_field.value.clone());
-        }
+    /// The source address, when the event has one.
+    pub fn source_addr(&self) -> Option<IpAddr> {
+        self.source_ip.parse().ok()
     }
 
-    let username = if let (Some(user), Some(domain)) = (
-        data_fields.get("TargetUserName"),
-        data_fields.get("TargetDomainName"),
-    ) {
-        Some(if domain.is_empty() || domain == "-" {
-            user.clone()
+    /// Combines two events describing the same logon, e.g. the network and interactive halves
+    /// of one session. `self` takes precedence, except where `other` knows more:
+    ///
+    /// - `variant`: the more interactive of the two (see [`LogonVariant::interactivity`]).
+    /// - `target`: `self`'s, unless its user is empty or `-`.
+    /// - `source_ip` and `source_ip_raw`: `self`'s, unless only `other`'s is an address (rather
+    ///   than `-` or similar).
+    /// - `success` and the enrichment flags: set if set on either.
+    /// - `attempt_count`: the larger.
+    /// - Every optional field: `self`'s when it has one, otherwise `other`'s.
+    pub fn merge(self, other: LogonEvent) -> LogonEvent {
+        let target = if self.target.user.is_empty() || self.target.user == "-" {
+            other.target
         } else {
-            format!("{}@{}", user, domain)
-        })
-    } else {
-        None
+            self.target
+        };
+        let (source_ip, source_ip_raw) =
+            if self.source_addr().is_none() && other.source_addr().is_some() {
+                (other.source_ip, other.source_ip_raw)
+            } else {
+                (self.source_ip, self.source_ip_raw)
+            };
+        let variant = if other.variant.interactivity() > self.variant.interactivity() {
+            other.variant
+        } else {
+            self.variant
+        };
+
+        LogonEvent {
+            target,
+            source_ip,
+            source_ip_raw,
+            source_hostname: self.source_hostname.or(other.source_hostname),
+            variant,
+            success: self.success || other.success,
+            event_id: self.event_id.or(other.event_id),
+            event_record_id: self.event_record_id.or(other.event_record_id),
+            keywords: self.keywords.or(other.keywords),
+            level: self.level.or(other.level),
+            task: self.task.or(other.task),
+            creator_process: self.creator_process.or(other.creator_process),
+            creator_process_id: self.creator_process_id.or(other.creator_process_id),
+            authentication_package: self.authentication_package.or(other.authentication_package),
+            attempt_count: self.attempt_count.max(other.attempt_count),
+            is_current_user: self.is_current_user || other.is_current_user,
+            first_seen_ip: self.first_seen_ip || other.first_seen_ip,
+            first_seen_user: self.first_seen_user || other.first_seen_user,
+            logon_id: self.logon_id.or(other.logon_id),
+            firewall_action: self.firewall_action.or(other.firewall_action),
+            linked_logon_id: self.linked_logon_id.or(other.linked_logon_id),
+            subject: self.subject.or(other.subject),
+            subject_logon_id: self.subject_logon_id.or(other.subject_logon_id),
+            impersonation_level: self.impersonation_level.or(other.impersonation_level),
+            target_server: self.target_server.or(other.target_server),
+            failure_reason: self.failure_reason.or(other.failure_reason),
+            failure_reason_text: self.failure_reason_text.or(other.failure_reason_text),
+            sub_status: self.sub_status.or(other.sub_status),
+            suspicious_account: self.suspicious_account || other.suspicious_account,
+        }
     }
-    .ok_or(SentinelError::XmlParseError(
-        "Username not found".to_string(),
-    ))?;
+}
 
-    let source_ip = data_fields
+const LOGON_SUCCESS: u32 = 4624;
+const LOGON_FAILURE: u32 = 4625;
+
+/// `STATUS_NO_SUCH_USER`: the account doesn't exist.
+pub const STATUS_NO_SUCH_USER: u32 = 0xC000_0064;
+/// `STATUS_ACCOUNT_DISABLED`: the account exists but is disabled.
+pub const STATUS_ACCOUNT_DISABLED: u32 = 0xC000_0072;
+
+/// Whether a failure `sub_status` means the account is disabled or doesn't exist.
+pub fn is_suspicious_account_status(sub_status: u32) -> bool {
+    matches!(sub_status, STATUS_NO_SUCH_USER | STATUS_ACCOUNT_DISABLED)
+}
+
+/// Resolves a `FailureReason` placeholder to the text Event Viewer would show. Values that aren't
+/// placeholders are already text and returned as is.
+pub fn resolve_failure_reason(reason: &str) -> Option<&str> {
+    let Some(code) = reason.strip_prefix("%%") else {
+        return Some(reason);
+    };
+    // From msobjs.dll, the message file the Security channel's parameters refer to.
+    let text = match code.trim() {
+        "2304" => "An Error occured during Logon.",
+        "2305" => "The specified user account has expired.",
+        "2306" => "The NetLogon component is not active.",
+        "2307" => "Account locked out.",
+        "2308" => "The user has not been granted the requested logon type at this machine.",
+        "2309" => "The specified account's password has expired.",
+        "2310" => "Account currently disabled.",
+        "2311" => "Account logon time restriction violation.",
+        "2312" => "User not allowed to logon at this computer.",
+        "2313" => "Unknown user name or bad password.",
+        "2314" => "Domain sid inconsistent.",
+        "2315" => "Smartcard logon is required and was not used.",
+        _ => return None,
+    };
+    Some(text)
+}
+
+pub fn parse_login_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, LogonEvent)> {
+    let record = parse_event_record(xml)?;
+
+    let Some(target) = Account::from_record(&record, "Target") else {
+        return Err(SentinelError::XmlParseError("Username not found".to_string()).into());
+    };
+
+    // Only the target is essential; forwarded and synthetic events can omit the rest.
+    let source_ip_raw = record
         .get("IpAddress")
-        .ok_or(SentinelError::XmlParseError(
-            "IpAddress not found".to_string(),
-        ))?
-        .clone();
+        .cloned()
+        .unwrap_or_else(|| "N/A".to_string());
+    let source_ip = parse_ip(&source_ip_raw)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| source_ip_raw.clone());
+
+    let variant =
+        LogonVariant::from_string(record.get("LogonType").map(String::as_str).unwrap_or(""));
 
-    let variant = LogonVariant::from_string(data_fields.get("LogonType").ok_or(
-        SentinelError::XmlParseError("Logon type not found".to_string()),
-    )?);
+    let creator_process = non_placeholder(record.get("ProcessName"));
+    let creator_process_id = record
+        .get("ProcessId")
+        .and_then(|pid| parse_hex(pid))
+        .and_then(|pid| u32::try_from(pid).ok());
+
+    let authentication_package = non_placeholder(record.get("AuthenticationPackageName"));
+
+    let subject = Account::from_record(&record, "Subject");
+    let subject_logon_id = record
+        .get("SubjectLogonId")
+        .and_then(|id| id.parse::<LogonId>().ok());
+
+    let failure_reason = non_placeholder(record.get("FailureReason"));
+    let failure_reason_text = failure_reason
+        .as_deref()
+        .and_then(resolve_failure_reason)
+        .map(str::to_string);
+    let sub_status = record
+        .get("SubStatus")
+        .and_then(|status| parse_hex(status))
+        .and_then(|status| u32::try_from(status).ok())
+        .filter(|&status| status != 0);
+    let suspicious_account = sub_status.is_some_and(is_suspicious_account_status);
+
+    let success = match record.event_id {
+        LOGON_SUCCESS | LOGON_FAILURE => record.event_id == LOGON_SUCCESS,
+        _ => record.keywords.and_then(audit_success).unwrap_or(false),
+    };
+
+    let impersonation_level = non_placeholder(record.get("ImpersonationLevel"))
+        .map(|level| ImpersonationLevel::from_string(&level));
+
+    let target_server = non_placeholder(record.get("TargetServerName"))
+        .or_else(|| {
+            // `TargetInfo` is often an SPN, e.g. `cifs/fs01.corp.local`.
+            non_placeholder(record.get("TargetInfo"))
+                .map(|info| info.rsplit('/').next().unwrap_or(&info).to_string())
+        })
+        .filter(|server| !server.eq_ignore_ascii_case("localhost"));
+
+    let logon_id = record
+        .get("TargetLogonId")
+        .and_then(|id| id.parse::<LogonId>().ok())
+        .filter(|&id| id != LogonId(0));
+    let linked_logon_id = record
+        .get("TargetLinkedLogonId")
+        .and_then(|id| id.parse::<LogonId>().ok())
+        .filter(|&id| id != LogonId(0));
 
     Ok((
-        timestamp,
+        record.timestamp,
         LogonEvent {
-            username,
+            target,
             source_ip,
+            source_ip_raw,
+            source_hostname: None,
             variant,
-            event_record_id: event.system.event_record_id,
+            success,
+            event_id: Some(record.event_id),
+            event_record_id: record.event_record_id,
+            keywords: record.keywords,
+            level: record.level,
+            task: record.task,
+            creator_process,
+            creator_process_id,
+            authentication_package,
+            attempt_count: 1,
+            is_current_user: false,
+            first_seen_ip: false,
+            first_seen_user: false,
+            logon_id,
+            firewall_action: None,
+            linked_logon_id,
+            subject,
+            subject_logon_id,
+            impersonation_level,
+            target_server,
+            failure_reason,
+            failure_reason_text,
+            sub_status,
+            suspicious_account,
         },
     ))
 }
 
+/// Renders the event behind `handle` and parses it as a logon in one step, for callers that
+/// already hold handles from their own `EvtQuery`/`EvtNext` or `win_event_log` use. The event is
+/// rendered once, straight to the XML the parser reads, instead of being stringified and then
+/// handed to [`parse_login_event`]. The handle stays owned by the caller, who must close it.
+#[cfg(all(windows, feature = "raw-handle"))]
+pub fn parse_from_handle(
+    handle: windows_sys::Win32::System::EventLog::EVT_HANDLE,
+) -> anyhow::Result<Event> {
+    let xml = super::xpath::render_xml(handle)
+        .map_err(|e| SentinelError::EventQueryError(format!("rendering event: {}", e)))?;
+    let mut events = parse_events(std::iter::once(xml), LogonListener::parse_event)?;
+    Ok(events.remove(0))
+}
+
 pub struct LogonListener {
     tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+    event_ids: Arc<[u32]>,
+    logon_types: Arc<[u32]>,
+    xpath: Option<Arc<XPathQuery>>,
+    direction: QueryDirection,
+    source: Option<XmlSource>,
+    subscriber: Option<Subscriber>,
+    max_age: Option<(chrono::Duration, Arc<dyn Clock>)>,
+    max_batch: Option<usize>,
+    collapser: Option<Arc<Mutex<AttemptCollapser>>>,
+    collapse_sessions: bool,
+    poll_interval: Duration,
+    jitter: Duration,
+    jitter_seed: Option<u64>,
+    pause: PauseHandle,
+    bookmark: Option<Arc<std::sync::Mutex<Bookmark>>>,
 }
 
 impl Clone for LogonListener {
     fn clone(&self) -> Self {
         Self {
             tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+            event_ids: Arc::clone(&self.event_ids),
+            logon_types: Arc::clone(&self.logon_types),
+            xpath: self.xpath.clone(),
+            direction: self.direction,
+            source: self.source.clone(),
+            subscriber: self.subscriber.clone(),
+            max_age: self.max_age.clone(),
+            max_batch: self.max_batch,
+            collapser: self.collapser.clone(),
+            collapse_sessions: self.collapse_sessions,
+            poll_interval: self.poll_interval,
+            jitter: self.jitter,
+            jitter_seed: self.jitter_seed,
+            pause: self.pause.clone(),
+            bookmark: self.bookmark.clone(),
         }
     }
 }
@@ -130,82 +437,432 @@ impl LogonListener {
     pub fn new(tx: mpsc::Sender<Event>) -> Self {
         Self {
             tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+            event_ids: Arc::new([4625]),
+            logon_types: Arc::new([]),
+            xpath: None,
+            direction: QueryDirection::Forward,
+            source: None,
+            subscriber: None,
+            max_age: None,
+            max_batch: None,
+            collapser: None,
+            collapse_sessions: false,
+            poll_interval: Duration::from_secs(1),
+            jitter: Duration::ZERO,
+            jitter_seed: None,
+            pause: PauseHandle::new(),
+            bookmark: None,
         }
     }
 
-    fn get_query() -> QueryList {
-        QueryList::new()
-            .with_query(
-                Query::new()
-                    .item(
-                        QueryItem::selector("Security".to_owned())
-                            .system_conditions(Condition::or(vec![Condition::filter(
-                                EventFilter::event(4625),
-                            )]))
-                            .build(),
-                    )
-                    .query(),
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Randomizes each poll delay by up to `jitter` in either direction. A fixed `seed` makes the
+    /// sequence of delays reproducible.
+    pub fn with_jitter(mut self, jitter: Duration, seed: Option<u64>) -> Self {
+        self.jitter = jitter;
+        self.jitter_seed = seed;
+        self
+    }
+
+    /// Queries `channel` with a raw XPath expression instead of selecting by event ID, for
+    /// filters the query builder can't express. Events are polled rather than subscribed to.
+    /// Fails if either argument is blank.
+    pub fn with_xpath(mut self, channel: &str, xpath: &str) -> Result<Self, SentinelError> {
+        self.xpath = Some(Arc::new(XPathQuery::new(channel, xpath)?));
+        Ok(self)
+    }
+
+    /// Only selects logons of these `LogonType` numbers (e.g. 2, 10, and 11 for interactive,
+    /// remote interactive, and cached logons), filtered by the event log itself rather than
+    /// after the fact. Like [`with_xpath`](Self::with_xpath), this polls instead of subscribing.
+    pub fn with_logon_types(mut self, logon_types: Vec<u32>) -> Self {
+        self.logon_types = logon_types.into();
+        self
+    }
+
+    /// Reads each poll's events in `direction` order. Reverse returns the newest events first, and
+    /// like an XPath query it polls instead of subscribing.
+    ///
+    /// The default `RecordId` dedup still works in reverse, since its watermark is the highest
+    /// record ID in each batch rather than the last one read. But events within a batch are then
+    /// delivered newest first, and nothing downstream may assume ascending record IDs.
+    pub fn with_direction(mut self, direction: QueryDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// The XPath this listener runs in place of the query builder's: the raw one set with
+    /// [`with_xpath`](Self::with_xpath), or one narrowed to the configured logon types.
+    pub fn xpath(&self) -> Option<Arc<XPathQuery>> {
+        if let Some(xpath) = &self.xpath {
+            return Some(Arc::clone(xpath));
+        }
+        (!self.logon_types.is_empty()).then(|| {
+            Arc::new(
+                XPathQuery::events(SECURITY_CHANNEL, &self.event_ids)
+                    .with_event_data("LogonType", &self.logon_types),
             )
-            .build()
+        })
     }
 
-    fn query_events() -> anyhow::Result<Vec<Event>> {
-        let events = {
-            let query = Self::get_query();
-            WinEvents::get(query).map_err(|e| {
-                SentinelError::EventQueryError(format!("Failed to query Security events: {}", e))
-            })?
-        };
-        let mut parsed_events = Vec::new();
-        for event in events {
-            let event_xml = event.to_string();
-            match parse_login_event(&event_xml) {
-                Ok((timestamp, login_event)) => {
-                    parsed_events.push(Event {
-                        details: EventDetails::Login(login_event),
-                        timestamp,
-                    });
+    /// Collapses runs of identical failures (same user, source, logon type, and reason) within
+    /// `window` into one event carrying the attempt count. Each run is held back until it ends.
+    pub fn with_failure_collapsing(
+        mut self,
+        window: chrono::Duration,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        self.with_failure_collapser(AttemptCollapser::new(window, clock))
+    }
+
+    /// Collapses failures with a preconfigured `collapser`, e.g. one that keys on subnets.
+    pub fn with_failure_collapser(mut self, collapser: AttemptCollapser) -> Self {
+        self.collapser = Some(Arc::new(Mutex::new(collapser)));
+        self
+    }
+
+    /// Merges successful logons that share a session (same computer and `TargetLogonId`) within
+    /// a batch into one, represented by the most interactive logon type, so a network logon
+    /// followed by an interactive one for the same session reads as a single interactive logon.
+    pub fn with_session_collapsing(mut self) -> Self {
+        self.collapse_sessions = true;
+        self
+    }
+
+    /// Drops events whose timestamp is more than `max_age` before `clock`'s now, e.g. so catching
+    /// up on a long backlog skips logons nobody will act on.
+    pub fn with_max_age(mut self, max_age: chrono::Duration, clock: Arc<dyn Clock>) -> Self {
+        self.max_age = Some((max_age, clock));
+        self
+    }
+
+    /// Forwards at most `max_batch` new events per poll, oldest first. The dedup watermark only
+    /// advances past those, so the rest follow on later polls, bounding memory and per-poll
+    /// latency when first catching up on a large log.
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = Some(max_batch.max(1));
+        self
+    }
+
+    /// Chooses how already-forwarded events are recognized. Defaults to `DedupKey::RecordId`.
+    pub fn with_dedup_key(mut self, key: DedupKey) -> Self {
+        self.dedup = key.shared();
+        self
+    }
+
+    /// Queries these Security event IDs instead of just 4625 (failed logon). Each must carry the
+    /// logon fields of a 4624/4625-style event.
+    pub fn with_event_ids(mut self, event_ids: Vec<u32>) -> Self {
+        self.event_ids = event_ids.into();
+        self
+    }
+
+    /// Reads event XML from `source` instead of querying the Security log. Everything after the
+    /// query (parsing, dedup, collapsing) behaves as usual.
+    pub fn with_xml_source(mut self, source: XmlSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Resumes from the bookmark saved at `path` (see [`Bookmark`]), skipping events delivered
+    /// before a restart and saving the bookmark after each delivery. Like an XPath query, this
+    /// polls instead of subscribing, since a subscription would miss what was written while the
+    /// listener was down. Failures held back for collapsing count as delivered.
+    pub fn with_bookmark(mut self, path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        self.bookmark = Some(Arc::new(std::sync::Mutex::new(Bookmark::open(path)?)));
+        Ok(self)
+    }
+
+    /// Pauses and resumes with `pause` instead of a handle of its own, e.g. to pause several
+    /// listeners together.
+    pub fn with_pause(mut self, pause: PauseHandle) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// The handle that pauses and resumes this listener. While paused it neither polls nor takes
+    /// subscribed events, which queue up in the subscription until it resumes.
+    pub fn pause_handle(&self) -> PauseHandle {
+        self.pause.clone()
+    }
+
+    /// Receives events from `subscriber` instead of subscribing to the Security log.
+    pub fn with_subscriber(mut self, subscriber: Subscriber) -> Self {
+        self.subscriber = Some(subscriber);
+        self
+    }
+
+    /// Subscribes to new events where the log allows it, and otherwise polls forever, sleeping
+    /// the (possibly jittered) poll interval between invocations. The chosen mode is logged and
+    /// reported by `health()`. A listener reading from an XML source only subscribes when given a
+    /// subscriber too.
+    pub async fn run(self) {
+        if let Some(subscriber) = self.subscriber() {
+            let (tx, rx) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+            match tokio::task::spawn_blocking(move || subscriber(tx)).await {
+                Ok(Ok(())) => {
+                    eprintln!("Logon listener: subscribed to new events");
+                    self.health.set_mode(DeliveryMode::Subscribe);
+                    self.receive(rx).await;
+                    eprintln!("Logon listener: subscription ended, polling instead");
                 }
-                Err(e) => {
-                    return Err(e);
+                Ok(Err(e)) => eprintln!("Logon listener: subscribing failed ({}), polling", e),
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(e) => eprintln!("Logon listener: subscribing failed ({}), polling", e),
+            }
+        } else {
+            eprintln!("Logon listener: polling");
+        }
+
+        self.health.set_mode(DeliveryMode::Poll);
+        let schedule = PollSchedule::new(self.poll_interval, self.jitter, self.jitter_seed);
+        poll(self, schedule).await;
+    }
+
+    /// Polls in the background and yields each new event as it's read, forever. Events go to the
+    /// returned stream instead of the sender this listener was created with. With a bookmark (see
+    /// [`with_bookmark`](Self::with_bookmark)), a tail started after a restart picks up where the
+    /// last one left off.
+    pub fn tail(mut self) -> Tail {
+        let (tx, rx) = mpsc::channel(DEFAULT_CHANNEL_CAPACITY);
+        self.tx = Arc::new(Mutex::new(tx));
+        Tail::new(rx, tokio::spawn(self.run()))
+    }
+
+    /// Blocks on a one-shot query for the `n` most recent matching logons, newest first, without
+    /// polling, subscribing, or sending anything to the channel.
+    pub fn last(&self, n: usize) -> anyhow::Result<Vec<Event>> {
+        if let Some(source) = &self.source {
+            let mut events = parse_events(source()?, Self::parse_event)?;
+            events.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            events.truncate(n);
+            return Ok(events);
+        }
+        let query = self
+            .xpath()
+            .unwrap_or_else(|| Arc::new(XPathQuery::events(SECURITY_CHANNEL, &self.event_ids)));
+        parse_events(query.latest(n)?, Self::parse_event)
+    }
+
+    /// Counts the logons written within the last `duration`, letting the event log do the
+    /// counting instead of rendering and parsing each one, e.g. for "how many 4625s in the last
+    /// hour". Nothing is deduplicated, collapsed, or suppressed, so the count can differ from
+    /// the number of events a poll over the same window would send.
+    pub fn count_since(&self, duration: chrono::Duration) -> Result<u64, SentinelError> {
+        if let Some(source) = &self.source {
+            let cutoff = Utc::now() - duration;
+            let xmls = source().map_err(|e| SentinelError::EventQueryError(e.to_string()))?;
+            return Ok(xmls
+                .iter()
+                .filter_map(|xml| parse_event_record(xml).ok())
+                .filter(|record| self.event_ids.contains(&record.event_id))
+                .filter(|record| record.timestamp >= cutoff)
+                .count() as u64);
+        }
+        let query = self
+            .xpath()
+            .map(|xpath| XPathQuery::clone(&xpath))
+            .unwrap_or_else(|| XPathQuery::events(SECURITY_CHANNEL, &self.event_ids));
+        query.with_max_age(duration).count()
+    }
+
+    fn get_query(event_ids: &[u32]) -> QueryList {
+        build_query(SECURITY_CHANNEL, event_ids)
+    }
+
+    fn parse_event(xml: &str) -> anyhow::Result<Event> {
+        let (timestamp, login_event) = parse_login_event(xml)?;
+        Ok(Event::new(EventDetails::Login(login_event), timestamp))
+    }
+
+    fn query_events(
+        event_ids: &[u32],
+        xpath: Option<&XPathQuery>,
+        direction: QueryDirection,
+        source: Option<&XmlSource>,
+    ) -> anyhow::Result<Vec<Event>> {
+        match (source, xpath) {
+            (Some(source), _) => {
+                // Sources supply events oldest first, as the log stores them.
+                let mut xmls = source()?;
+                if direction == QueryDirection::Reverse {
+                    xmls.reverse();
                 }
+                parse_events(xmls, Self::parse_event)
+            }
+            (None, Some(xpath)) => parse_events(xpath.run(direction)?, Self::parse_event),
+            (None, None) => query_channel(
+                SECURITY_CHANNEL,
+                Self::get_query(event_ids),
+                Self::parse_event,
+            ),
+        }
+    }
+
+    fn subscriber(&self) -> Option<Subscriber> {
+        if let Some(subscriber) = &self.subscriber {
+            return Some(Arc::clone(subscriber));
+        }
+        if self.source.is_some()
+            || self.bookmark.is_some()
+            || self.xpath().is_some()
+            || self.direction == QueryDirection::Reverse
+        {
+            return None;
+        }
+        let event_ids = Arc::clone(&self.event_ids);
+        Some(Arc::new(move |tx| {
+            subscribe_channel(SECURITY_CHANNEL, Self::get_query(&event_ids), tx)
+        }))
+    }
+
+    /// Forwards subscribed events until the subscription closes, handling whatever has arrived
+    /// since the last batch together.
+    async fn receive(&self, mut xmls: mpsc::Receiver<String>) {
+        loop {
+            self.pause.resumed().await;
+            let mut batch = Vec::new();
+            match tokio::time::timeout(self.poll_interval, xmls.recv()).await {
+                Ok(Some(xml)) => batch.push(xml),
+                Ok(None) => return,
+                // Nothing new, but a collapsed run may have expired and be due for release.
+                Err(_) if self.collapser.is_some() => {}
+                Err(_) => continue,
+            }
+            while let Ok(xml) = xmls.try_recv() {
+                batch.push(xml);
+            }
+
+            let max_age = self.max_age.clone();
+            // Subscribed events aren't delivered again, so none can be held back for later.
+            self.deliver(None, move || {
+                let mut events = parse_events(std::mem::take(&mut batch), Self::parse_event)?;
+                drop_stale(&mut events, max_age.as_ref());
+                Ok(events)
+            })
+            .await;
+        }
+    }
+
+    /// The blocking query for one poll, including the max age cutoff.
+    fn poll_query(&self) -> impl FnMut() -> anyhow::Result<Vec<Event>> + Send + use<> {
+        let event_ids = Arc::clone(&self.event_ids);
+        // Only EvtQuery can read backwards, so a reverse poll always goes through XPath.
+        let xpath = match (self.xpath(), self.direction) {
+            (None, QueryDirection::Reverse) => Some(Arc::new(XPathQuery::events(
+                SECURITY_CHANNEL,
+                &self.event_ids,
+            ))),
+            (xpath, _) => xpath,
+        };
+        let direction = self.direction;
+        let source = self.source.clone();
+        let max_age = self.max_age.clone();
+        move || {
+            let mut events =
+                Self::query_events(&event_ids, xpath.as_deref(), direction, source.as_ref())?;
+            drop_stale(&mut events, max_age.as_ref());
+            Ok(events)
+        }
+    }
+
+    /// Runs `query` and forwards the events not already seen, at most `max_batch` of them,
+    /// collapsing failures if enabled, then advances the bookmark past them.
+    async fn deliver<F>(&self, max_batch: Option<usize>, mut query: F)
+    where
+        F: FnMut() -> anyhow::Result<Vec<Event>> + Send + 'static,
+    {
+        let bookmark = self.bookmark.clone();
+        let query = move || {
+            let events = query()?;
+            Ok(match &bookmark {
+                Some(bookmark) => bookmark
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .skip_delivered(events),
+                None => events,
+            })
+        };
+        let mut events = fetch_new_events(&self.dedup, &self.health, max_batch, query).await;
+        let delivered = events.iter().flatten().filter_map(Event::record_id).max();
+        if self.collapse_sessions {
+            events = events.map(collapse_sessions);
+        }
+        let events = match &self.collapser {
+            // Process even an empty batch, so a run that has expired is still released.
+            Some(collapser) => collapser.lock().await.process(events.unwrap_or_default()),
+            None => events.unwrap_or_default(),
+        };
+        send_events(&self.tx, events).await;
+
+        // With the receiver gone nothing was delivered, so the bookmark stays put.
+        if self.tx.lock().await.is_closed() {
+            return;
+        }
+        if let (Some(bookmark), Some(record_id)) = (self.bookmark.clone(), delivered) {
+            let result = tokio::task::spawn_blocking(move || {
+                bookmark
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .advance(record_id)
+            })
+            .await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => eprintln!("Failed to save the logon bookmark: {}", e),
+                Err(e) => eprintln!("Failed to save the logon bookmark: {}", e),
             }
         }
-        Ok(parsed_events)
+    }
+}
+
+fn drop_stale(events: &mut Vec<Event>, max_age: Option<&(chrono::Duration, Arc<dyn Clock>)>) {
+    if let Some((max_age, clock)) = max_age {
+        let cutoff = clock.now() - *max_age;
+        events.retain(|event| event.timestamp >= cutoff);
     }
 }
 
 impl EventListener for LogonListener {
     fn invoke(&self) {
-        let tx_clone = Arc::clone(&self.tx);
-
-        tokio::spawn(async move {
-            let processing_task = tokio::task::spawn_blocking(Self::query_events);
-
-            match processing_task.await {
-                Ok(Ok(events)) => {
-                    for event in events {
-                        if tx_clone.lock().await.send(event).await.is_err() {
-                            eprintln!(
-                                "Failed to send event to channel, receiver dropped: {}",
-                                SentinelError::ChannelSendError
-                            );
-                            break;
-                        }
-                    }
-                }
-                Ok(Err(e)) => {
-                    eprintln!("Error processing security events: {}", e);
-                }
-                Err(e) => {
-                    eprintln!("Processing task failed: {}", e);
-                }
-            }
-        });
+        if self.pause.is_paused() {
+            return;
+        }
+        let listener = self.clone();
+        let query = self.poll_query();
+        supervise::spawn(async move { listener.deliver(listener.max_batch, query).await });
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        self.xpath()
+            .is_none()
+            .then(|| Self::get_query(&self.event_ids))
+    }
+
+    /// `None` when reading from an XML source rather than the event log.
+    fn channel(&self) -> Option<&str> {
+        if self.source.is_some() {
+            return None;
+        }
+        Some(
+            self.xpath
+                .as_deref()
+                .map_or(SECURITY_CHANNEL, XPathQuery::channel),
+        )
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, PartialEq, Eq, Display, IntoStaticStr, Serialize, Deserialize)]
 pub enum LogonVariant {
     Interactive,
     Network,
@@ -222,24 +879,104 @@ pub enum LogonVariant {
     Invalid(String),
 }
 
+/// The `ImpersonationLevel` of a logon's token, resolved from its `%%18xx` placeholder.
+#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum ImpersonationLevel {
+    Anonymous,
+    Identification,
+    Impersonation,
+    Delegation,
+    /// A placeholder or text this doesn't recognize, as logged.
+    Other(String),
+}
+
+impl ImpersonationLevel {
+    pub fn from_string(s: &str) -> Self {
+        // From msobjs.dll; some sources log the resolved text instead.
+        match s.trim() {
+            "%%1831" | "Anonymous" => ImpersonationLevel::Anonymous,
+            "%%1832" | "Identification" => ImpersonationLevel::Identification,
+            "%%1833" | "Impersonation" => ImpersonationLevel::Impersonation,
+            "%%1840" | "Delegation" => ImpersonationLevel::Delegation,
+            other => ImpersonationLevel::Other(other.to_string()),
+        }
+    }
+}
+
+impl Default for LogonVariant {
+    fn default() -> Self {
+        LogonVariant::Invalid(String::new())
+    }
+}
+
 impl LogonVariant {
+    /// Every logon type Windows defines, in numeric order.
+    pub const KNOWN: [LogonVariant; 11] = [
+        LogonVariant::Interactive,
+        LogonVariant::Network,
+        LogonVariant::Batch,
+        LogonVariant::Service,
+        LogonVariant::Unlock,
+        LogonVariant::NetworkCleartext,
+        LogonVariant::NewCredentials,
+        LogonVariant::RemoteInteractive,
+        LogonVariant::CachedInteractive,
+        LogonVariant::CachedRemoteInteractive,
+        LogonVariant::CachedUnlock,
+    ];
+
     pub fn from_string(s: &str) -> Self {
         match s.parse::<isize>() {
-            Ok(num) => match num {
-                2 => LogonVariant::Interactive,
-                3 => LogonVariant::Network,
-                4 => LogonVariant::Batch,
-                5 => LogonVariant::Service,
-                7 => LogonVariant::Unlock,
-                8 => LogonVariant::NetworkCleartext,
-                9 => LogonVariant::NewCredentials,
-                10 => LogonVariant::RemoteInteractive,
-                11 => LogonVariant::CachedInteractive,
-                12 => LogonVariant::CachedRemoteInteractive,
-                13 => LogonVariant::CachedUnlock,
-                _ => LogonVariant::Unknown(num),
-            },
+            Ok(num) => Self::KNOWN
+                .into_iter()
+                .find(|variant| variant.as_number() == Some(num))
+                .unwrap_or(LogonVariant::Unknown(num)),
             Err(_) => LogonVariant::Invalid(s.to_string()),
         }
     }
+
+    /// The `LogonType` number as logged, or `None` for a value that wasn't a number.
+    pub fn as_number(&self) -> Option<isize> {
+        Some(match self {
+            LogonVariant::Interactive => 2,
+            LogonVariant::Network => 3,
+            LogonVariant::Batch => 4,
+            LogonVariant::Service => 5,
+            LogonVariant::Unlock => 7,
+            LogonVariant::NetworkCleartext => 8,
+            LogonVariant::NewCredentials => 9,
+            LogonVariant::RemoteInteractive => 10,
+            LogonVariant::CachedInteractive => 11,
+            LogonVariant::CachedRemoteInteractive => 12,
+            LogonVariant::CachedUnlock => 13,
+            LogonVariant::Unknown(num) => *num,
+            LogonVariant::Invalid(_) => return None,
+        })
+    }
+
+    /// Each known logon type's number and display name, e.g. `(10, "RemoteInteractive")`, for
+    /// documentation and pickers.
+    pub fn all() -> Vec<(isize, &'static str)> {
+        Self::KNOWN
+            .iter()
+            .map(|variant| {
+                let number = variant.as_number().expect("known variants have a number");
+                (number, <&'static str>::from(variant))
+            })
+            .collect()
+    }
+
+    /// How directly a person is behind the logon: 2 for a console or RDP session, 1 for an
+    /// unlock, 0 for everything else. Ranks which type represents a session that logged on
+    /// more than once.
+    pub fn interactivity(&self) -> u8 {
+        match self {
+            LogonVariant::Interactive
+            | LogonVariant::RemoteInteractive
+            | LogonVariant::CachedInteractive
+            | LogonVariant::CachedRemoteInteractive => 2,
+            LogonVariant::Unlock | LogonVariant::CachedUnlock => 1,
+            _ => 0,
+        }
+    }
 }
@@ -1,20 +1,477 @@
+pub mod account;
+pub mod applocker;
+pub mod bookmark;
+pub mod capture;
+pub mod channels;
+pub mod collapse;
+pub mod dedup;
+pub mod defender;
+pub mod event_ids;
+pub mod evtx;
+pub mod health;
+pub mod heartbeat;
+pub mod lockout;
 pub mod logon;
+pub mod pause;
+pub mod pool;
+mod record;
+pub mod remote_exec;
+pub mod retry;
+pub mod saturation;
+pub mod schedule;
+pub mod schema;
+pub mod screen_lock;
+pub mod stats;
+pub mod supervise;
+pub mod tail;
+pub mod usb;
+pub mod winevt;
+pub mod xpath;
+
+use std::sync::Arc;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, mpsc};
+
+use crate::enrich::Severity;
+use crate::errors::SentinelError;
+use dedup::SharedDedup;
+use health::{HealthTracker, ListenerHealth};
+use schema::SchemaVersion;
+pub(crate) use winevt::build_query;
+use winevt::{QueryList, WinEvents, WinEventsSubscriber};
+
+pub(crate) const SECURITY_CHANNEL: &str = "Security";
+use schedule::PollSchedule;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
+    /// Which version of this structure the event was serialized with. See
+    /// [`SCHEMA_VERSION`](schema::SCHEMA_VERSION) for the compatibility policy.
+    pub schema_version: SchemaVersion,
     pub details: EventDetails,
+    /// When the event was generated, from `System/TimeCreated`.
     pub timestamp: DateTime<Utc>,
+    /// When Hosho read the event from the log. The event XML doesn't record when a collector
+    /// received a forwarded event, so for WEF data the gap from `timestamp` is an upper bound on
+    /// forwarding latency (plus up to one poll interval).
+    pub collected_at: Option<DateTime<Utc>>,
+    /// The `System/Computer` that generated the event. Differs from the local machine for
+    /// events forwarded to a WEF collector.
+    pub computer: Option<String>,
+    /// How much attention the event deserves. Only set by `SeverityPolicy`.
+    #[serde(default)]
+    pub severity: Severity,
+    /// How risky a logon looks, from 0 to 100. Only set by `RiskScorer`.
+    pub risk_score: Option<u8>,
+    /// Windows' own localized description of the event, when the source included one. Its text
+    /// depends on the display language, so it's for display only; detection keys on event IDs,
+    /// numeric fields, SIDs, and `%%` placeholder codes instead.
+    pub rendering: Option<RenderingInfo>,
+    /// Labels for routing and filtering. Only set by `Tagger`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// The localized strings of an event's `RenderingInfo` section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderingInfo {
+    pub message: Option<String>,
+    pub level: Option<String>,
+    pub task: Option<String>,
+}
+
+impl RenderingInfo {
+    /// Reads the `RenderingInfo` section of an event's XML, if it has one.
+    pub fn parse(xml: &str) -> Option<Self> {
+        record::parse_rendering_info(xml)
+    }
+}
+
+impl Event {
+    pub fn new(details: EventDetails, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            schema_version: SchemaVersion,
+            details,
+            timestamp,
+            collected_at: None,
+            computer: None,
+            severity: Severity::Info,
+            risk_score: None,
+            rendering: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Builds a synthetic event used to validate sink configuration. Listeners never produce this.
+    pub fn self_test() -> Self {
+        Self::new(EventDetails::SelfTest, Utc::now())
+    }
+
+    pub fn kind(&self) -> EventKind {
+        match &self.details {
+            EventDetails::Login(_) => EventKind::Login,
+            EventDetails::UsbDevice(_) => EventKind::UsbDevice,
+            EventDetails::ScreenLock(_) => EventKind::ScreenLock,
+            EventDetails::ThreatDetected(_) => EventKind::ThreatDetected,
+            EventDetails::AppBlocked(_) => EventKind::AppBlocked,
+            EventDetails::RemoteExecution(_) => EventKind::RemoteExecution,
+            EventDetails::Lockout(_) => EventKind::Lockout,
+            EventDetails::Heartbeat { .. } => EventKind::Heartbeat,
+            EventDetails::QueryStats { .. } => EventKind::QueryStats,
+            EventDetails::SelfTest => EventKind::SelfTest,
+            EventDetails::ListenerPanicked { .. } => EventKind::ListenerPanicked,
+        }
+    }
+
+    /// The `EventRecordID` of the underlying log entry, if the event came from one that has it.
+    pub fn record_id(&self) -> Option<u64> {
+        match &self.details {
+            EventDetails::Login(login_event) => login_event.event_record_id,
+            EventDetails::UsbDevice(usb_event) => usb_event.event_record_id,
+            EventDetails::ScreenLock(lock_event) => lock_event.event_record_id,
+            EventDetails::ThreatDetected(threat_event) => threat_event.event_record_id,
+            EventDetails::AppBlocked(blocked_event) => blocked_event.event_record_id,
+            EventDetails::RemoteExecution(exec_event) => exec_event.event_record_id,
+            EventDetails::Lockout(lockout_event) => lockout_event.event_record_id,
+            EventDetails::Heartbeat { .. }
+            | EventDetails::QueryStats { .. }
+            | EventDetails::SelfTest
+            | EventDetails::ListenerPanicked { .. } => None,
+        }
+    }
+
+    /// The Windows event ID of the underlying log entry, for kinds that come from a known one.
+    /// Logons parsed before event IDs were recorded fall back to 4624 or 4625.
+    pub fn event_id(&self) -> Option<u32> {
+        match &self.details {
+            EventDetails::Login(login_event) => Some(
+                login_event
+                    .event_id
+                    .unwrap_or(if login_event.success { 4624 } else { 4625 }),
+            ),
+            EventDetails::ScreenLock(lock_event) => {
+                Some(if lock_event.locked { 4800 } else { 4801 })
+            }
+            EventDetails::Lockout(_) => Some(lockout::ACCOUNT_LOCKED_OUT),
+            EventDetails::UsbDevice(_)
+            | EventDetails::ThreatDetected(_)
+            | EventDetails::AppBlocked(_)
+            | EventDetails::RemoteExecution(_)
+            | EventDetails::Heartbeat { .. }
+            | EventDetails::QueryStats { .. }
+            | EventDetails::SelfTest
+            | EventDetails::ListenerPanicked { .. } => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+/// What happened. Add new variants at the end: archives encode a variant by its position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventDetails {
     Login(LogonEvent),
+    UsbDevice(UsbDeviceEvent),
+    ScreenLock(ScreenLockEvent),
+    ThreatDetected(ThreatEvent),
+    AppBlocked(AppBlockedEvent),
+    RemoteExecution(RemoteExecutionEvent),
+    /// A periodic liveness signal, numbered so gaps are detectable.
+    Heartbeat {
+        seq: u64,
+    },
+    /// How one event log query went. Only sent when [`stats`] are configured.
+    QueryStats {
+        channel: String,
+        /// How long the query itself took, not counting parsing.
+        duration: Duration,
+        /// How many events the query returned.
+        event_count: usize,
+        /// How many of them failed to parse.
+        parse_errors: usize,
+    },
+    SelfTest,
+    Lockout(LockoutEvent),
+    /// A supervised listener panicked. Only sent when [`supervise`] events are configured.
+    ListenerPanicked {
+        listener: String,
+        message: String,
+        /// How many times the listener has been restarted, counting the one this announces.
+        restarts: u32,
+        /// Whether it's being restarted, rather than stopped for good after its last restart.
+        restarting: bool,
+    },
+}
+
+/// Which [`EventDetails`] variant an event is, without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum EventKind {
+    Login,
+    UsbDevice,
+    ScreenLock,
+    ThreatDetected,
+    AppBlocked,
+    RemoteExecution,
+    Heartbeat,
+    QueryStats,
+    SelfTest,
+    Lockout,
+    ListenerPanicked,
+}
+
+impl EventKind {
+    /// Whether events of this kind report on Hosho itself rather than on the machine it watches.
+    pub fn is_operational(self) -> bool {
+        matches!(
+            self,
+            EventKind::Heartbeat
+                | EventKind::QueryStats
+                | EventKind::SelfTest
+                | EventKind::ListenerPanicked
+        )
+    }
 }
 
 pub trait EventListener: Clone {
     fn invoke(&self);
+
+    /// How recent polls have gone. Listeners that don't track this always look healthy.
+    fn health(&self) -> ListenerHealth {
+        ListenerHealth::default()
+    }
+
+    /// The query this listener sends to the event log, for listeners that read one.
+    fn query(&self) -> Option<QueryList> {
+        None
+    }
+
+    /// The event log channel this listener reads, for listeners that read one.
+    fn channel(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Invokes `listener` forever, sleeping according to `schedule` between polls.
+pub async fn poll<L: EventListener>(listener: L, mut schedule: PollSchedule) {
+    loop {
+        listener.invoke();
+        tokio::time::sleep(schedule.next_delay()).await;
+    }
+}
+
+/// Classifies a failed query. `win_event_log` only hands back a message, so the specific cause is
+/// recovered from the thread's last OS error, which `EvtQuery` sets on failure. Off Windows there
+/// was no `EvtQuery` to set it.
+fn query_error(channel: &str, e: impl std::fmt::Display) -> SentinelError {
+    let os_error = if cfg!(windows) {
+        std::io::Error::last_os_error().raw_os_error()
+    } else {
+        None
+    };
+    SentinelError::from_query_failure(channel, os_error, &e.to_string())
+}
+
+/// Finds the `System/Computer` value without deserializing the whole event again.
+fn extract_computer(xml: &str) -> Option<String> {
+    extract_text(xml, "Computer").map(str::to_string)
+}
+
+/// Finds the text of the first `<name>` element with no attributes, e.g. `System/Channel`,
+/// without deserializing the event.
+pub(crate) fn extract_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(xml[start..end].trim()).filter(|text| !text.is_empty())
+}
+
+/// Supplies raw event XML in place of an event log query, e.g. to replay captured events.
+pub type XmlSource = Arc<dyn Fn() -> anyhow::Result<Vec<String>> + Send + Sync>;
+
+/// Starts a subscription that sends each new event's XML to the sender as it's written. Fails if
+/// the subscription can't be set up, e.g. because the channel doesn't support it.
+pub type Subscriber = Arc<dyn Fn(mpsc::Sender<String>) -> anyhow::Result<()> + Send + Sync>;
+
+/// How long a subscription thread waits before checking for more events once it has caught up.
+const SUBSCRIPTION_IDLE: Duration = Duration::from_millis(100);
+
+/// Subscribes to `query` on `channel` with `EvtSubscribe`, sending each event's XML to `tx` from
+/// a dedicated thread until `tx` is closed. Returns once the subscription is set up.
+pub(crate) fn subscribe_channel(
+    channel: &str,
+    query: QueryList,
+    tx: mpsc::Sender<String>,
+) -> anyhow::Result<()> {
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut subscriber = match WinEventsSubscriber::get(query) {
+            Ok(subscriber) => {
+                let _ = ready_tx.send(Ok(()));
+                subscriber
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e.to_string()));
+                return;
+            }
+        };
+        while !tx.is_closed() {
+            while let Some(event) = subscriber.next() {
+                if tx.blocking_send(event.to_string()).is_err() {
+                    return;
+                }
+            }
+            std::thread::sleep(SUBSCRIPTION_IDLE);
+        }
+    });
+
+    match ready_rx.recv() {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(SentinelError::EventQueryError(format!("{}: {}", channel, e)).into()),
+        Err(_) => Err(SentinelError::EventQueryError(format!(
+            "{}: subscription thread exited",
+            channel
+        ))
+        .into()),
+    }
+}
+
+/// Runs `query` against `channel` and parses every returned event with `parse`, failing on the
+/// first event that doesn't parse. Each event is stamped with the time it was read.
+pub(crate) fn query_channel(
+    channel: &str,
+    query: QueryList,
+    parse: impl Fn(&str) -> anyhow::Result<Event>,
+) -> anyhow::Result<Vec<Event>> {
+    let started = std::time::Instant::now();
+    let events = WinEvents::get(query).map_err(|e| query_error(channel, e))?;
+    let duration = started.elapsed();
+
+    let (parsed, parse_errors) =
+        parse_counting_errors(events.iter().map(|event| event.to_string()), parse);
+    stats::record(channel, duration, events.len(), parse_errors);
+    parsed
+}
+
+/// Parses each event's XML with `parse`, filling in the fields common to every event. Completes
+/// the stats report of the [`XPathQuery`](xpath::XPathQuery) the events came from, if any.
+pub(crate) fn parse_events(
+    xmls: impl IntoIterator<Item = String>,
+    parse: impl Fn(&str) -> anyhow::Result<Event>,
+) -> anyhow::Result<Vec<Event>> {
+    let (parsed, parse_errors) = parse_counting_errors(xmls, parse);
+    stats::parsed(parse_errors);
+    parsed
+}
+
+/// Like [`parse_events`], but goes on past the first failure to count how many events failed to
+/// parse. The result is still that first failure.
+fn parse_counting_errors(
+    xmls: impl IntoIterator<Item = String>,
+    parse: impl Fn(&str) -> anyhow::Result<Event>,
+) -> (anyhow::Result<Vec<Event>>, usize) {
+    let collected_at = Utc::now();
+    let mut parsed_events = Vec::new();
+    let mut first_error = None;
+    let mut parse_errors = 0;
+    for xml in xmls {
+        capture::record(&xml);
+        match parse(&xml) {
+            Ok(mut parsed) => {
+                parsed.collected_at = Some(collected_at);
+                parsed.computer = extract_computer(&xml);
+                parsed.rendering = RenderingInfo::parse(&xml);
+                parsed_events.push(parsed);
+            }
+            Err(e) => {
+                parse_errors += 1;
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    let parsed = match first_error {
+        Some(e) => Err(e),
+        None => Ok(parsed_events),
+    };
+    (parsed, parse_errors)
+}
+
+/// Runs the blocking `query` on the shared query pool, retrying transient failures with the
+/// shared retry policy, and returns only the events `dedup` hasn't already seen, and no more than
+/// `max_batch` of them. Failures are logged, recorded in `health`, and yield `None`. A panic in
+/// `query` is resumed here, so it reaches the supervisor.
+pub(crate) async fn fetch_new_events<F>(
+    dedup: &SharedDedup,
+    health: &HealthTracker,
+    max_batch: Option<usize>,
+    query: F,
+) -> Option<Vec<Event>>
+where
+    F: FnMut() -> anyhow::Result<Vec<Event>> + Send + 'static,
+{
+    match retry::shared().run(pool::shared(), query).await {
+        Ok(Ok(events)) => {
+            health.record_success(Utc::now());
+            let mut dedup = dedup.lock().await;
+            Some(match max_batch {
+                Some(max) => dedup.filter_at_most(events, max),
+                None => dedup.filter(events),
+            })
+        }
+        Ok(Err(e)) => {
+            eprintln!("Error processing events: {}", e);
+            health.record_failure(&e);
+            None
+        }
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => {
+            eprintln!("Processing task failed: {}", e);
+            health.record_failure(&e);
+            None
+        }
+    }
+}
+
+/// Sends each event to `tx`, stopping early if the receiver has been dropped.
+pub(crate) async fn send_events(tx: &Mutex<mpsc::Sender<Event>>, events: Vec<Event>) {
+    for event in events {
+        if tx.lock().await.send(event).await.is_err() {
+            eprintln!(
+                "Failed to send event to channel, receiver dropped: {}",
+                SentinelError::ChannelSendError
+            );
+            break;
+        }
+    }
+}
+
+/// Runs the blocking `query` as [`fetch_new_events`] does, and forwards each event `dedup`
+/// hasn't already seen to `tx`.
+pub(crate) fn forward_events<F>(
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+    query: F,
+) where
+    F: FnMut() -> anyhow::Result<Vec<Event>> + Send + 'static,
+{
+    supervise::spawn(async move {
+        if let Some(events) = fetch_new_events(&dedup, &health, None, query).await {
+            send_events(&tx, events).await;
+        }
+    });
 }
 
+pub use account::{Account, LogonId, Sid};
+pub use applocker::{AppBlockedEvent, AppLockerListener};
+pub use defender::{DefenderListener, ThreatEvent};
+pub use evtx::EvtxDirListener;
+pub use health::{DeliveryMode, ListenerHealth};
+pub use heartbeat::HeartbeatListener;
+pub use lockout::{LockoutEvent, LockoutListener};
 pub use logon::{LogonEvent, LogonListener};
+pub use pause::PauseHandle;
+pub use remote_exec::{RemoteExecutionEvent, RemoteExecutionListener};
+pub use screen_lock::{ScreenLockEvent, ScreenLockListener};
+pub use tail::Tail;
+pub use usb::{UsbAction, UsbDeviceEvent, UsbListener};
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Pauses and resumes a listener at runtime, shared between the listener's clones and whoever
+/// controls it. A paused listener keeps its task and dedup watermark but stops reading, so on
+/// resume it catches up on everything logged in between.
+#[derive(Debug, Clone)]
+pub struct PauseHandle(Arc<watch::Sender<bool>>);
+
+impl PauseHandle {
+    /// A handle that starts out running.
+    pub fn new() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+
+    pub fn pause(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.0.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Waits until the handle isn't paused, returning at once if it already isn't.
+    pub async fn resumed(&self) {
+        let mut paused = self.0.subscribe();
+        // The sender lives as long as `self`, so this can't fail.
+        let _ = paused.wait_for(|paused| !paused).await;
+    }
+}
+
+impl Default for PauseHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinError;
+
+/// Runs blocking event log queries on tokio's blocking thread pool, at most `max_concurrent` at a
+/// time. Listeners query concurrently, so without a bound a machine watching many channels could
+/// tie up one blocking thread per channel on every poll.
+#[derive(Debug, Clone)]
+pub struct QueryPool {
+    permits: Arc<Semaphore>,
+}
+
+impl QueryPool {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+        }
+    }
+
+    /// Runs `query` once a slot is free.
+    pub async fn run<F, T>(&self, query: F) -> Result<T, JoinError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        // Owned and moved into the blocking task, so the slot stays taken until the query
+        // itself finishes, even if the caller is cancelled while awaiting it.
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("query pool semaphore is never closed");
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            query()
+        })
+        .await
+    }
+}
+
+static SHARED: OnceLock<QueryPool> = OnceLock::new();
+
+/// Sets the bound on the pool shared by all listeners. Only takes effect before the first query;
+/// returns whether it did.
+pub fn configure(max_concurrent: usize) -> bool {
+    SHARED.set(QueryPool::new(max_concurrent)).is_ok()
+}
+
+/// The pool shared by all listeners, bounded by the number of CPUs unless configured otherwise.
+pub fn shared() -> &'static QueryPool {
+    SHARED
+        .get_or_init(|| QueryPool::new(std::thread::available_parallelism().map_or(4, |n| n.get())))
+}
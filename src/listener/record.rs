@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_xml_rs::from_str;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::errors::SentinelError;
+
+use super::RenderingInfo;
+
+/// The `System` fields shared by every event, with `EventData` flattened into a name → value
+/// map. Covers the Security channel and any other channel whose payload is named `Data` fields.
+#[derive(Debug)]
+pub(crate) struct EventRecord {
+    pub event_id: u32,
+    pub timestamp: DateTime<Utc>,
+    /// Absent from some synthetic and forwarded events.
+    pub event_record_id: Option<u64>,
+    /// The `Keywords` bitmask, e.g. `0x8020000000000000` for an audit success.
+    pub keywords: Option<u64>,
+    pub level: Option<u8>,
+    pub task: Option<u16>,
+    pub data: HashMap<String, String>,
+}
+
+impl EventRecord {
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.data.get(name)
+    }
+
+    /// Looks up a required field, failing with a parse error naming it when absent.
+    pub fn require(&self, name: &str) -> Result<&String, SentinelError> {
+        self.get(name)
+            .ok_or_else(|| SentinelError::XmlParseError(format!("{} not found", name)))
+    }
+}
+
+pub(crate) fn parse_event_record(xml: &str) -> anyhow::Result<EventRecord> {
+    #[derive(Debug, Deserialize)]
+    struct RawEvent {
+        #[serde(rename = "System")]
+        system: System,
+        #[serde(rename = "EventData")]
+        event_data: EventData,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct System {
+        #[serde(rename = "EventID", default)]
+        event_id: u32,
+        #[serde(rename = "TimeCreated")]
+        time_created: TimeCreated,
+        #[serde(rename = "EventRecordID", default)]
+        event_record_id: Option<u64>,
+        #[serde(rename = "Keywords", default)]
+        keywords: Option<String>,
+        #[serde(rename = "Level", default)]
+        level: Option<u8>,
+        #[serde(rename = "Task", default)]
+        task: Option<u16>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct TimeCreated {
+        #[serde(rename = "@SystemTime")]
+        system_time: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct EventData {
+        #[serde(rename = "#content")]
+        data: Vec<DataField>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct DataField {
+        #[serde(rename = "@Name")]
+        name: String,
+        #[serde(rename = "#text", default)]
+        value: String,
+    }
+
+    let event: RawEvent =
+        from_str(&inline_cdata(xml)).map_err(|e| SentinelError::XmlParseError(e.to_string()))?;
+
+    let timestamp_str = &event.system.time_created.system_time;
+    let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339(timestamp_str)
+        .map_err(|e| SentinelError::TimestampParseError(e.to_string()))?
+        .with_timezone(&Utc);
+
+    let data = event
+        .event_data
+        .data
+        .into_iter()
+        .map(|field| (field.name, field.value))
+        .collect();
+
+    Ok(EventRecord {
+        event_id: event.system.event_id,
+        timestamp,
+        event_record_id: event.system.event_record_id,
+        keywords: event.system.keywords.as_deref().and_then(parse_hex),
+        level: event.system.level,
+        task: event.system.task,
+        data,
+    })
+}
+
+/// Reads the `RenderingInfo` section some event sources include, or `None` when it's absent.
+pub(crate) fn parse_rendering_info(xml: &str) -> Option<RenderingInfo> {
+    #[derive(Debug, Deserialize)]
+    struct RawEvent {
+        #[serde(rename = "RenderingInfo")]
+        rendering_info: Option<RawRenderingInfo>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct RawRenderingInfo {
+        #[serde(rename = "Message")]
+        message: Option<String>,
+        #[serde(rename = "Level")]
+        level: Option<String>,
+        #[serde(rename = "Task")]
+        task: Option<String>,
+    }
+
+    if !xml.contains("<RenderingInfo") {
+        return None;
+    }
+
+    let event: RawEvent = from_str(&inline_cdata(xml)).ok()?;
+    let info = event.rendering_info?;
+    let non_empty = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    Some(RenderingInfo {
+        message: non_empty(info.message),
+        level: non_empty(info.level),
+        task: non_empty(info.task),
+    })
+}
+
+/// Rewrites each CDATA section as escaped text. A field mixing plain text and CDATA otherwise
+/// deserializes as several text nodes, of which only one would be kept.
+pub(crate) fn inline_cdata(xml: &str) -> Cow<'_, str> {
+    const OPEN: &str = "<![CDATA[";
+    const CLOSE: &str = "]]>";
+
+    if !xml.contains(OPEN) {
+        return Cow::Borrowed(xml);
+    }
+
+    let mut inlined = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(start) = rest.find(OPEN) {
+        inlined.push_str(&rest[..start]);
+        let content = &rest[start + OPEN.len()..];
+        let Some(end) = content.find(CLOSE) else {
+            // Unterminated; leave it for the XML parser to reject.
+            inlined.push_str(&rest[start..]);
+            return Cow::Owned(inlined);
+        };
+
+        for c in content[..end].chars() {
+            match c {
+                '&' => inlined.push_str("&amp;"),
+                '<' => inlined.push_str("&lt;"),
+                '>' => inlined.push_str("&gt;"),
+                c => inlined.push(c),
+            }
+        }
+        rest = &content[end + CLOSE.len()..];
+    }
+    inlined.push_str(rest);
+    Cow::Owned(inlined)
+}
+
+/// Parses a `0x`-prefixed hexadecimal field such as a process or logon ID.
+pub(crate) fn parse_hex(value: &str) -> Option<u64> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))?;
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// Parses an address field, accepting the bracketed (`[::1]`), port-suffixed (`10.0.0.1:445`)
+/// and zone-scoped (`fe80::1%12`) forms some sources log. IPv4-mapped IPv6 addresses are unwrapped
+/// to plain IPv4.
+pub(crate) fn parse_ip(value: &str) -> Option<IpAddr> {
+    let value = value.trim();
+    let host = match value.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']')?.0,
+        // A single colon can only separate an IPv4 address from its port.
+        None if value.matches(':').count() == 1 => value.split_once(':')?.0,
+        None => value,
+    };
+    let host = host
+        .split_once('%')
+        .map_or(host, |(address, _zone)| address);
+
+    match host.parse().ok()? {
+        IpAddr::V6(v6) => Some(v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4)),
+        v4 => Some(v4),
+    }
+}
+
+/// The audit outcome a `Keywords` bitmask records: `Some(true)` for audit success, `Some(false)`
+/// for audit failure, `None` when it records neither.
+pub(crate) fn audit_success(keywords: u64) -> Option<bool> {
+    const AUDIT_FAILURE: u64 = 0x0010_0000_0000_0000;
+    const AUDIT_SUCCESS: u64 = 0x0020_0000_0000_0000;
+
+    match (keywords & AUDIT_SUCCESS != 0, keywords & AUDIT_FAILURE != 0) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Treats the empty and `-` values Windows fills unused fields with as absent.
+pub(crate) fn non_placeholder(value: Option<&String>) -> Option<String> {
+    value
+        .filter(|value| !value.is_empty() && *value != "-")
+        .cloned()
+}
@@ -0,0 +1,221 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use strum_macros::Display;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::record::{non_placeholder, parse_event_record};
+use super::winevt::QueryList;
+use super::{Event, EventDetails, EventListener, build_query, forward_events, query_channel};
+
+const POWERSHELL_CHANNEL: &str = "Microsoft-Windows-PowerShell/Operational";
+const WINRM_CHANNEL: &str = "Microsoft-Windows-WinRM/Operational";
+
+const PIPELINE_EXECUTION: u32 = 4103;
+const SCRIPT_BLOCK: u32 = 4104;
+const WINRM_SHELL_CREATED: u32 = 91;
+
+#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum RemoteExecutionKind {
+    ScriptBlock,
+    PipelineExecution,
+    WinRmShell,
+}
+
+/// How much script text to keep. Script blocks can run to megabytes, so callers that only need
+/// to know *that* something ran can drop or cap it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptStorage {
+    Omit,
+    Truncate(usize),
+    Full,
+}
+
+impl ScriptStorage {
+    /// Applies the policy, returning the kept text and whether anything was cut.
+    fn apply(self, script: String) -> (Option<String>, bool) {
+        match self {
+            ScriptStorage::Omit => (None, !script.is_empty()),
+            ScriptStorage::Full => (Some(script), false),
+            ScriptStorage::Truncate(max_len) if script.len() <= max_len => (Some(script), false),
+            ScriptStorage::Truncate(max_len) => {
+                let mut end = max_len;
+                while !script.is_char_boundary(end) {
+                    end -= 1;
+                }
+                (Some(script[..end].to_string()), true)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteExecutionEvent {
+    pub kind: RemoteExecutionKind,
+    /// The script block text (4104) or pipeline payload (4103), subject to `ScriptStorage`.
+    pub script: Option<String>,
+    pub script_truncated: bool,
+    /// Identifies the script block; long blocks are logged as several events sharing this ID.
+    pub script_block_id: Option<String>,
+    /// The host application that ran the command, e.g. `wsmprovhost.exe` for remoting sessions.
+    pub host: Option<String>,
+    /// The WinRM resource (plugin) a remote shell was created against.
+    pub resource_uri: Option<String>,
+    pub event_record_id: Option<u64>,
+}
+
+/// Pulls `key = value` out of the multi-line `ContextInfo` block on 4103 events.
+fn context_value(context: &str, key: &str) -> Option<String> {
+    context.lines().find_map(|line| {
+        let (name, value) = line.split_once('=')?;
+        (name.trim() == key)
+            .then(|| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    })
+}
+
+pub fn parse_remote_execution_event(
+    xml: &str,
+    storage: ScriptStorage,
+) -> anyhow::Result<(DateTime<Utc>, RemoteExecutionEvent)> {
+    let record = parse_event_record(xml)?;
+
+    let (kind, script, host) = match record.event_id {
+        SCRIPT_BLOCK => (
+            RemoteExecutionKind::ScriptBlock,
+            record.require("ScriptBlockText")?.clone(),
+            None,
+        ),
+        PIPELINE_EXECUTION => {
+            let context = record.get("ContextInfo").map(String::as_str).unwrap_or("");
+            let host = context_value(context, "Host Application")
+                .or_else(|| context_value(context, "Host Name"));
+            (
+                RemoteExecutionKind::PipelineExecution,
+                record.get("Payload").cloned().unwrap_or_default(),
+                host,
+            )
+        }
+        WINRM_SHELL_CREATED => (RemoteExecutionKind::WinRmShell, String::new(), None),
+        other => {
+            return Err(SentinelError::XmlParseError(format!(
+                "Expected a PowerShell or WinRM event, got event ID {}",
+                other
+            ))
+            .into());
+        }
+    };
+
+    let (script, script_truncated) = storage.apply(script);
+
+    Ok((
+        record.timestamp,
+        RemoteExecutionEvent {
+            kind,
+            script: script.filter(|script| !script.is_empty()),
+            script_truncated,
+            script_block_id: non_placeholder(record.get("ScriptBlockId")),
+            host,
+            resource_uri: non_placeholder(record.get("resourceUri")),
+            event_record_id: record.event_record_id,
+        },
+    ))
+}
+
+/// Polls the PowerShell or WinRM operational channel. Each channel numbers its records
+/// independently, so they're watched by separate listeners.
+pub struct RemoteExecutionListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+    channel: &'static str,
+    event_ids: &'static [u32],
+    storage: ScriptStorage,
+}
+
+impl Clone for RemoteExecutionListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+            channel: self.channel,
+            event_ids: self.event_ids,
+            storage: self.storage,
+        }
+    }
+}
+
+impl RemoteExecutionListener {
+    /// Watches PowerShell pipeline execution (4103) and script block logging (4104).
+    pub fn powershell(tx: mpsc::Sender<Event>) -> Self {
+        Self::new(tx, POWERSHELL_CHANNEL, &[PIPELINE_EXECUTION, SCRIPT_BLOCK])
+    }
+
+    /// Watches WinRM remote shell creation.
+    pub fn winrm(tx: mpsc::Sender<Event>) -> Self {
+        Self::new(tx, WINRM_CHANNEL, &[WINRM_SHELL_CREATED])
+    }
+
+    fn new(tx: mpsc::Sender<Event>, channel: &'static str, event_ids: &'static [u32]) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+            channel,
+            event_ids,
+            storage: ScriptStorage::Full,
+        }
+    }
+
+    pub fn with_script_storage(mut self, storage: ScriptStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    fn get_query(channel: &str, event_ids: &[u32]) -> QueryList {
+        build_query(channel, event_ids)
+    }
+
+    fn query_events(
+        channel: &str,
+        event_ids: &[u32],
+        storage: ScriptStorage,
+    ) -> anyhow::Result<Vec<Event>> {
+        query_channel(channel, Self::get_query(channel, event_ids), |xml| {
+            let (timestamp, exec_event) = parse_remote_execution_event(xml, storage)?;
+            Ok(Event::new(
+                EventDetails::RemoteExecution(exec_event),
+                timestamp,
+            ))
+        })
+    }
+}
+
+impl EventListener for RemoteExecutionListener {
+    fn invoke(&self) {
+        let (channel, event_ids, storage) = (self.channel, self.event_ids, self.storage);
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            move || Self::query_events(channel, event_ids, storage),
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query(self.channel, self.event_ids))
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(self.channel)
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tokio::task::JoinError;
+
+use crate::errors::SentinelError;
+
+use super::pool::QueryPool;
+
+/// How many times a failed event log query is retried before its error reaches the listener,
+/// and how long to wait in between. Only errors [`SentinelError::is_transient`] considers
+/// transient are retried: an RPC hiccup may clear up, a malformed query or a denied channel
+/// won't.
+///
+/// This is separate from [`RestartPolicy`](super::supervise::RestartPolicy), which restarts a
+/// listener's whole task after a panic; retries happen within a single poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Retries up to `max_retries` times, waiting `initial_backoff` before the first retry and
+    /// doubling the wait for each one after, up to five seconds.
+    pub fn new(max_retries: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Whether `error` is worth retrying.
+    pub fn is_retryable(error: &anyhow::Error) -> bool {
+        error
+            .downcast_ref::<SentinelError>()
+            .is_some_and(SentinelError::is_transient)
+    }
+
+    /// Runs the blocking `query` on `pool`, running it again after a backoff whenever it fails
+    /// with a retryable error, until it succeeds, fails otherwise, or runs out of retries. Each
+    /// attempt takes its own slot in the pool, and the backoff waits on the runtime without one,
+    /// so a retrying listener doesn't hold up the others' queries.
+    pub async fn run<F, T>(
+        &self,
+        pool: &QueryPool,
+        mut query: F,
+    ) -> Result<anyhow::Result<T>, JoinError>
+    where
+        F: FnMut() -> anyhow::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut retries = 0;
+        let mut backoff = self.initial_backoff;
+        loop {
+            // The query moves to the blocking thread for each attempt and comes back with its
+            // result.
+            let (returned, result) = pool
+                .run(move || {
+                    let result = query();
+                    (query, result)
+                })
+                .await?;
+            query = returned;
+            match result {
+                Err(e) if retries < self.max_retries && Self::is_retryable(&e) => {
+                    retries += 1;
+                    eprintln!(
+                        "Query failed: {}. Retrying in {:?} ({}/{})",
+                        e, backoff, retries, self.max_retries
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                }
+                result => return Ok(result),
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(2, Duration::from_millis(500))
+    }
+}
+
+static SHARED: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Sets the policy every listener's queries use. Only takes effect before the first query;
+/// returns whether it did.
+pub fn configure(policy: RetryPolicy) -> bool {
+    SHARED.set(policy).is_ok()
+}
+
+/// The policy every listener's queries use: two retries, starting at 500ms, unless configured
+/// otherwise.
+pub fn shared() -> RetryPolicy {
+    *SHARED.get_or_init(RetryPolicy::default)
+}
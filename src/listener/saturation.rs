@@ -0,0 +1,101 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use tokio::sync::mpsc;
+
+/// How many events a listener can queue for the pipeline by default. Enough to absorb a poll's
+/// burst from a busy log while the sinks catch up; a listener that fills it waits rather than
+/// dropping events, so a channel that's often full points at a slow sink.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 100;
+
+/// How full a channel got since it was last reported on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelSaturation {
+    pub name: &'static str,
+    pub capacity: usize,
+    /// The most events queued at once.
+    pub high_water: usize,
+    /// How many events were received from a full channel, each a moment its senders may have
+    /// been waiting.
+    pub times_full: u64,
+}
+
+impl fmt::Display for ChannelSaturation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: peaked at {}/{} queued, full {} times",
+            self.name, self.high_water, self.capacity, self.times_full
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Gauge {
+    name: &'static str,
+    capacity: usize,
+    high_water: AtomicUsize,
+    times_full: AtomicU64,
+}
+
+/// Tracks how full a channel gets, shared between its receiving end and whoever reports on it.
+#[derive(Debug, Clone)]
+pub struct ChannelGauge(Arc<Gauge>);
+
+impl ChannelGauge {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self(Arc::new(Gauge {
+            name,
+            capacity,
+            high_water: AtomicUsize::new(0),
+            times_full: AtomicU64::new(0),
+        }))
+    }
+
+    /// Records that `queued` events were waiting in the channel.
+    pub fn observe(&self, queued: usize) {
+        self.0.high_water.fetch_max(queued, Ordering::Relaxed);
+        if queued >= self.0.capacity {
+            self.0.times_full.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// How full the channel got since the last call, starting afresh from now.
+    pub fn take(&self) -> ChannelSaturation {
+        ChannelSaturation {
+            name: self.0.name,
+            capacity: self.0.capacity,
+            high_water: self.0.high_water.swap(0, Ordering::Relaxed),
+            times_full: self.0.times_full.swap(0, Ordering::Relaxed),
+        }
+    }
+}
+
+/// The receiving end of a channel made by [`channel`], measuring how full the channel is each
+/// time an event is taken from it.
+#[derive(Debug)]
+pub struct GaugedReceiver<T> {
+    rx: mpsc::Receiver<T>,
+    gauge: ChannelGauge,
+}
+
+impl<T> GaugedReceiver<T> {
+    pub async fn recv(&mut self) -> Option<T> {
+        let item = self.rx.recv().await?;
+        // Counting the event just taken, this is how many were waiting.
+        self.gauge.observe(self.rx.len() + 1);
+        Some(item)
+    }
+
+    pub fn gauge(&self) -> ChannelGauge {
+        self.gauge.clone()
+    }
+}
+
+/// Like `mpsc::channel`, but the receiver keeps a [`ChannelGauge`] named `name`.
+pub fn channel<T>(name: &'static str, capacity: usize) -> (mpsc::Sender<T>, GaugedReceiver<T>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    let gauge = ChannelGauge::new(name, capacity);
+    (tx, GaugedReceiver { rx, gauge })
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Produces the delay between polls as `interval ± random(0..=jitter)`, so that many hosts
+/// started together don't end up polling (and forwarding) on the same boundary. Jitter is
+/// clamped below the interval, so a poll is never scheduled immediately after the last one.
+#[derive(Debug, Clone)]
+pub struct PollSchedule {
+    interval: Duration,
+    jitter: Duration,
+    rng: StdRng,
+}
+
+impl PollSchedule {
+    pub fn new(interval: Duration, jitter: Duration, seed: Option<u64>) -> Self {
+        Self {
+            interval,
+            jitter: jitter.min(interval.saturating_sub(Duration::from_millis(1))),
+            rng: match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_os_rng(),
+            },
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+
+        let jitter_ms = self.jitter.as_millis() as u64;
+        let offset = Duration::from_millis(self.rng.random_range(0..=jitter_ms * 2));
+        (self.interval + offset).saturating_sub(self.jitter)
+    }
+}
+
+/// Derives one listener's jitter seed from a shared `seed`, so listeners started with the same
+/// `--jitter-seed` stay reproducible without polling in lockstep.
+pub fn listener_seed(seed: Option<u64>, listener: &str) -> Option<u64> {
+    // FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+    seed.map(|seed| {
+        listener
+            .bytes()
+            .fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    })
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The version of the serialized event structure, stamped on every serialized [`Event`] as
+/// `schema_version`.
+///
+/// Compatibility policy: the version is bumped whenever a serialized field is added, removed,
+/// renamed, or changes type, or an enum variant is added or renamed. Deserialization accepts
+/// this version and the one before it, so a consumer upgraded one release behind still reads
+/// its producer's output; fields the older version lacks take their defaults. Anything newer is
+/// rejected rather than misread.
+///
+/// - 1: the unstamped output from before the field existed.
+/// - 2: added `schema_version`.
+/// - 3: added `sub_status` and `suspicious_account` to logons.
+/// - 4: added `logon_id` to logons.
+/// - 5: added the `QueryStats` event.
+/// - 6: added the `Lockout` event.
+/// - 7: added `event_id` to logons.
+/// - 8: added the `ListenerPanicked` event.
+///
+/// Binary archives encode fields and variants by position rather than by name, so every bump
+/// needs a matching archive version bump in [`sink::archive`](crate::sink::archive) too; the
+/// build fails until it has one.
+///
+/// [`Event`]: super::Event
+pub const SCHEMA_VERSION: u32 = 8;
+
+/// The oldest schema version events still deserialize from.
+pub const MIN_SCHEMA_VERSION: u32 = SCHEMA_VERSION - 1;
+
+/// The `schema_version` field of a serialized event. Always serializes as [`SCHEMA_VERSION`],
+/// since an event in memory has the current structure however it was read; deserializing
+/// checks the stamp is one this build understands.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchemaVersion;
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(SCHEMA_VERSION)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let version = u32::deserialize(deserializer)?;
+        if !(MIN_SCHEMA_VERSION..=SCHEMA_VERSION).contains(&version) {
+            return Err(serde::de::Error::custom(format!(
+                "unsupported schema version {} (expected {} to {})",
+                version, MIN_SCHEMA_VERSION, SCHEMA_VERSION
+            )));
+        }
+        Ok(SchemaVersion)
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::account::Account;
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::record::{non_placeholder, parse_event_record};
+use super::winevt::QueryList;
+use super::{
+    Event, EventDetails, EventListener, SECURITY_CHANNEL, build_query, forward_events,
+    query_channel,
+};
+
+const WORKSTATION_LOCKED: u32 = 4800;
+const WORKSTATION_UNLOCKED: u32 = 4801;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenLockEvent {
+    pub locked: bool,
+    pub username: String,
+    pub event_record_id: Option<u64>,
+}
+
+pub fn parse_screen_lock_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, ScreenLockEvent)> {
+    let record = parse_event_record(xml)?;
+
+    let locked = match record.event_id {
+        WORKSTATION_LOCKED => true,
+        WORKSTATION_UNLOCKED => false,
+        other => {
+            return Err(SentinelError::XmlParseError(format!(
+                "Expected a lock/unlock event, got event ID {}",
+                other
+            ))
+            .into());
+        }
+    };
+
+    let user = record.require("TargetUserName")?;
+    let username = Account::new(
+        user,
+        non_placeholder(record.get("TargetDomainName")).as_deref(),
+    )
+    .upn();
+
+    Ok((
+        record.timestamp,
+        ScreenLockEvent {
+            locked,
+            username,
+            event_record_id: record.event_record_id,
+        },
+    ))
+}
+
+pub struct ScreenLockListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+}
+
+impl Clone for ScreenLockListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+        }
+    }
+}
+
+impl ScreenLockListener {
+    pub fn new(tx: mpsc::Sender<Event>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+        }
+    }
+
+    fn get_query() -> QueryList {
+        build_query(
+            SECURITY_CHANNEL,
+            &[WORKSTATION_LOCKED, WORKSTATION_UNLOCKED],
+        )
+    }
+
+    fn query_events() -> anyhow::Result<Vec<Event>> {
+        query_channel(SECURITY_CHANNEL, Self::get_query(), |xml| {
+            let (timestamp, lock_event) = parse_screen_lock_event(xml)?;
+            Ok(Event::new(EventDetails::ScreenLock(lock_event), timestamp))
+        })
+    }
+}
+
+impl EventListener for ScreenLockListener {
+    fn invoke(&self) {
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            Self::query_events,
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query())
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(SECURITY_CHANNEL)
+    }
+}
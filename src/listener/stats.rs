@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::sync::mpsc;
+
+use super::{Event, EventDetails};
+
+static SINK: OnceLock<mpsc::Sender<Event>> = OnceLock::new();
+
+/// Sends a `QueryStats` event to `tx` after every event log query, for diagnosing slow queries.
+/// Off unless configured. Only takes effect once; returns whether it did.
+pub fn configure(tx: mpsc::Sender<Event>) -> bool {
+    SINK.set(tx).is_ok()
+}
+
+/// Reports one query's timing and results, if stats are configured. Called from the blocking
+/// pool, so a full channel drops the report rather than waiting.
+pub(crate) fn record(channel: &str, duration: Duration, event_count: usize, parse_errors: usize) {
+    let Some(tx) = SINK.get() else {
+        return;
+    };
+    let event = Event::new(
+        EventDetails::QueryStats {
+            channel: channel.to_string(),
+            duration,
+            event_count,
+            parse_errors,
+        },
+        Utc::now(),
+    );
+    let _ = tx.try_send(event);
+}
+
+/// A query whose events were rendered but not yet parsed.
+struct Rendered {
+    channel: String,
+    duration: Duration,
+    event_count: usize,
+}
+
+thread_local! {
+    /// The last query rendered on this thread, held until its events are parsed on it too.
+    static RENDERED: RefCell<Option<Rendered>> = const { RefCell::new(None) };
+}
+
+/// Holds a query's report until [`parsed`] counts how many of its events failed to parse. Any
+/// report still held from an earlier query whose events were never parsed is sent as it was.
+pub(crate) fn rendered(channel: &str, duration: Duration, event_count: usize) {
+    if SINK.get().is_none() {
+        return;
+    }
+    let earlier = RENDERED.replace(Some(Rendered {
+        channel: channel.to_string(),
+        duration,
+        event_count,
+    }));
+    if let Some(earlier) = earlier {
+        record(&earlier.channel, earlier.duration, earlier.event_count, 0);
+    }
+}
+
+/// Sends the report held by [`rendered`] on this thread, if any, with its parse errors.
+pub(crate) fn parsed(parse_errors: usize) {
+    if let Some(query) = RENDERED.take() {
+        record(
+            &query.channel,
+            query.duration,
+            query.event_count,
+            parse_errors,
+        );
+    }
+}
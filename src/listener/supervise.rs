@@ -0,0 +1,149 @@
+use std::any::Any;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::select;
+use tokio::sync::mpsc;
+
+use super::{Event, EventDetails};
+
+type Panic = Box<dyn Any + Send>;
+
+tokio::task_local! {
+    /// Where tasks spawned on behalf of a supervised task report their panics.
+    static PANICS: mpsc::UnboundedSender<Panic>;
+}
+
+static SINK: OnceLock<mpsc::Sender<Event>> = OnceLock::new();
+
+/// Sends a `ListenerPanicked` event to `tx` whenever a supervised task panics. Off unless
+/// configured. Only takes effect once; returns whether it did.
+pub fn configure(tx: mpsc::Sender<Event>) -> bool {
+    SINK.set(tx).is_ok()
+}
+
+fn report(listener: &str, message: &str, restarts: u32, restarting: bool) {
+    let Some(tx) = SINK.get() else {
+        return;
+    };
+    let event = Event::new(
+        EventDetails::ListenerPanicked {
+            listener: listener.to_string(),
+            message: message.to_string(),
+            restarts,
+            restarting,
+        },
+        Utc::now(),
+    );
+    let _ = tx.try_send(event);
+}
+
+/// Spawns `task` like `tokio::spawn`, except that when called from a supervised task, a panic in
+/// `task` restarts the supervised task as if it had panicked itself. Listeners run each poll's
+/// work this way, so a panic in a query isn't lost in a detached task.
+pub(crate) fn spawn<F>(task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let panics = PANICS.try_with(Clone::clone).ok();
+    let handle = tokio::spawn(task);
+    if let Some(panics) = panics {
+        tokio::spawn(async move {
+            if let Err(e) = handle.await
+                && e.is_panic()
+            {
+                let _ = panics.send(e.into_panic());
+            }
+        });
+    }
+}
+
+/// How often, and how eagerly, a supervised task is restarted after panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    max_restarts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// Restarts up to `max_restarts` times, waiting `initial_backoff` before the first restart
+    /// and doubling the wait for each one after, up to a minute.
+    pub fn new(max_restarts: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_restarts,
+            initial_backoff,
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(1))
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Runs the task `start` creates on its own tokio task, starting a fresh one whenever it, or a
+/// task it [`spawn`]s, panics until `policy`'s restart cap is reached. Other tasks are unaffected
+/// either way. Each panic is also reported as a `ListenerPanicked` event if [`configure`]d.
+/// Returns once the task finishes without panicking, is cancelled, or has used up its restarts.
+pub async fn supervise<F, Fut>(name: &str, policy: RestartPolicy, mut start: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let mut restarts = 0;
+    let mut backoff = policy.initial_backoff;
+    loop {
+        // A channel per run, so a straggler from a run already restarted can't restart the next.
+        let (panics_tx, mut panics_rx) = mpsc::unbounded_channel();
+        let mut task = tokio::spawn(PANICS.scope(panics_tx, start()));
+        let panic = select! {
+            result = &mut task => match result {
+                Ok(()) => return,
+                Err(e) if e.is_panic() => e.into_panic(),
+                Err(_) => return,
+            },
+            Some(panic) = panics_rx.recv() => {
+                task.abort();
+                panic
+            }
+        };
+        let message = panic_message(panic.as_ref());
+
+        if restarts == policy.max_restarts {
+            eprintln!(
+                "Listener {} panicked: {}. Giving up after {} restarts",
+                name, message, restarts
+            );
+            report(name, message, restarts, false);
+            return;
+        }
+        restarts += 1;
+        eprintln!(
+            "Listener {} panicked: {}. Restarting in {:?} ({}/{})",
+            name, message, backoff, restarts, policy.max_restarts
+        );
+        report(name, message, restarts, true);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(policy.max_backoff);
+    }
+}
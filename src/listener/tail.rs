@@ -0,0 +1,40 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::Stream;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::Event;
+
+/// A never-ending stream of events from a listener polling in the background. Each event is
+/// yielded once, per the listener's dedup strategy. Dropping the stream stops the listener.
+pub struct Tail {
+    events: ReceiverStream<Event>,
+    task: JoinHandle<()>,
+}
+
+impl Tail {
+    /// Wraps the receiving end of a listener's channel, taking ownership of the task driving it.
+    pub(crate) fn new(rx: mpsc::Receiver<Event>, task: JoinHandle<()>) -> Self {
+        Self {
+            events: ReceiverStream::new(rx),
+            task,
+        }
+    }
+}
+
+impl Stream for Tail {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        Pin::new(&mut self.events).poll_next(cx)
+    }
+}
+
+impl Drop for Tail {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_xml_rs::from_str;
+use std::sync::Arc;
+use strum_macros::Display;
+use tokio::sync::{Mutex, mpsc};
+
+use crate::errors::SentinelError;
+
+use super::dedup::{DedupKey, SharedDedup};
+use super::health::{HealthTracker, ListenerHealth};
+use super::record::inline_cdata;
+use super::winevt::QueryList;
+use super::{Event, EventDetails, EventListener, build_query, forward_events, query_channel};
+
+const CHANNEL: &str = "Microsoft-Windows-DriverFrameworks-UserMode/Operational";
+
+// IRP_MJ_PNP, and the minor functions that indicate the device is going away.
+const IRP_MJ_PNP: u32 = 0x1B;
+const IRP_MN_REMOVE_DEVICE: u32 = 0x02;
+const IRP_MN_SURPRISE_REMOVAL: u32 = 0x17;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsbDeviceEvent {
+    pub device_id: String,
+    pub friendly_name: Option<String>,
+    pub action: UsbAction,
+    pub event_record_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum UsbAction {
+    Connected,
+    Removed,
+    Other(u32),
+}
+
+pub fn parse_usb_event(xml: &str) -> anyhow::Result<(DateTime<Utc>, UsbDeviceEvent)> {
+    #[derive(Debug, Deserialize)]
+    struct DriverFrameworksEvent {
+        #[serde(rename = "System")]
+        system: System,
+        #[serde(rename = "UserData")]
+        user_data: UserData,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct System {
+        #[serde(rename = "EventID")]
+        event_id: u32,
+        #[serde(rename = "TimeCreated")]
+        time_created: TimeCreated,
+        #[serde(rename = "EventRecordID", default)]
+        event_record_id: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct TimeCreated {
+        #[serde(rename = "@SystemTime")]
+        system_time: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct UserData {
+        #[serde(rename = "UMDFHostDeviceArrivalBegin")]
+        arrival: Option<DeviceRecord>,
+        #[serde(rename = "UMDFHostDeviceRequest")]
+        request: Option<DeviceRecord>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct DeviceRecord {
+        #[serde(rename = "@instance")]
+        instance: String,
+        #[serde(rename = "FriendlyName")]
+        friendly_name: Option<String>,
+        #[serde(rename = "Request")]
+        request: Option<PnpRequest>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct PnpRequest {
+        #[serde(rename = "@major")]
+        major: u32,
+        #[serde(rename = "@minor")]
+        minor: u32,
+    }
+
+    let event: DriverFrameworksEvent =
+        from_str(&inline_cdata(xml)).map_err(|e| SentinelError::XmlParseError(e.to_string()))?;
+
+    let timestamp: DateTime<Utc> =
+        DateTime::parse_from_rfc3339(&event.system.time_created.system_time)
+            .map_err(|e| SentinelError::TimestampParseError(e.to_string()))?
+            .with_timezone(&Utc);
+
+    let Some(record) = event.user_data.arrival.or(event.user_data.request) else {
+        return Err(SentinelError::XmlParseError("Device record not found".to_string()).into());
+    };
+
+    let action = match (event.system.event_id, &record.request) {
+        (2003, _) => UsbAction::Connected,
+        (_, Some(request))
+            if request.major == IRP_MJ_PNP
+                && matches!(
+                    request.minor,
+                    IRP_MN_REMOVE_DEVICE | IRP_MN_SURPRISE_REMOVAL
+                ) =>
+        {
+            UsbAction::Removed
+        }
+        (event_id, _) => UsbAction::Other(event_id),
+    };
+
+    let friendly_name = record
+        .friendly_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    Ok((
+        timestamp,
+        UsbDeviceEvent {
+            device_id: record.instance,
+            friendly_name,
+            action,
+            event_record_id: event.system.event_record_id,
+        },
+    ))
+}
+
+pub struct UsbListener {
+    tx: Arc<Mutex<mpsc::Sender<Event>>>,
+    dedup: SharedDedup,
+    health: HealthTracker,
+}
+
+impl Clone for UsbListener {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Arc::clone(&self.tx),
+            dedup: Arc::clone(&self.dedup),
+            health: self.health.clone(),
+        }
+    }
+}
+
+impl UsbListener {
+    pub fn new(tx: mpsc::Sender<Event>) -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(tx)),
+            dedup: DedupKey::RecordId.shared(),
+            health: HealthTracker::new(),
+        }
+    }
+
+    fn get_query() -> QueryList {
+        build_query(CHANNEL, &[2003, 2100, 2102])
+    }
+
+    fn query_events() -> anyhow::Result<Vec<Event>> {
+        query_channel(CHANNEL, Self::get_query(), |xml| {
+            let (timestamp, usb_event) = parse_usb_event(xml)?;
+            Ok(Event::new(EventDetails::UsbDevice(usb_event), timestamp))
+        })
+    }
+}
+
+impl EventListener for UsbListener {
+    fn invoke(&self) {
+        forward_events(
+            Arc::clone(&self.tx),
+            Arc::clone(&self.dedup),
+            self.health.clone(),
+            Self::query_events,
+        );
+    }
+
+    fn health(&self) -> ListenerHealth {
+        self.health.snapshot()
+    }
+
+    fn query(&self) -> Option<QueryList> {
+        Some(Self::get_query())
+    }
+
+    fn channel(&self) -> Option<&str> {
+        Some(CHANNEL)
+    }
+}
@@ -0,0 +1,82 @@
+//! The parts of `win_event_log` the listeners use. That crate only builds on Windows, so
+//! elsewhere these are stand-ins that build the same queries but fail to run them, leaving
+//! parsing, detectors, and sinks buildable and testable on any platform (e.g. Linux CI).
+
+#[cfg(windows)]
+pub use win_event_log::prelude::{QueryList, WinEvents, WinEventsSubscriber};
+
+#[cfg(not(windows))]
+pub use unsupported::{QueryList, WinEvents, WinEventsSubscriber};
+
+/// Why an event log call failed on a platform without one.
+#[cfg(not(windows))]
+pub(crate) const UNSUPPORTED: &str = "the event log is only available on Windows";
+
+/// Builds a query selecting any of `event_ids` from `channel`.
+#[cfg(windows)]
+pub(crate) fn build_query(channel: &str, event_ids: &[u32]) -> QueryList {
+    use win_event_log::prelude::{Condition, EventFilter, Query, QueryItem};
+
+    let conditions = event_ids
+        .iter()
+        .map(|&id| Condition::filter(EventFilter::event(id)))
+        .collect();
+
+    QueryList::new()
+        .with_query(
+            Query::new()
+                .item(
+                    QueryItem::selector(channel.to_owned())
+                        .system_conditions(Condition::or(conditions))
+                        .build(),
+                )
+                .query(),
+        )
+        .build()
+}
+
+/// Builds a query selecting any of `event_ids` from `channel`.
+#[cfg(not(windows))]
+pub(crate) fn build_query(channel: &str, event_ids: &[u32]) -> QueryList {
+    QueryList(super::xpath::XPathQuery::events(channel, event_ids).to_string())
+}
+
+#[cfg(not(windows))]
+mod unsupported {
+    use std::fmt;
+
+    use super::UNSUPPORTED;
+
+    /// A query, kept as the `QueryList` XML it would be sent as so it can still be printed.
+    #[derive(Debug, Clone)]
+    pub struct QueryList(pub(super) String);
+
+    impl fmt::Display for QueryList {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    pub struct WinEvents;
+
+    impl WinEvents {
+        pub fn get(_query: QueryList) -> Result<Vec<String>, &'static str> {
+            Err(UNSUPPORTED)
+        }
+    }
+
+    /// Never constructed: subscribing always fails.
+    pub enum WinEventsSubscriber {}
+
+    impl WinEventsSubscriber {
+        pub fn get(_query: QueryList) -> Result<Self, &'static str> {
+            Err(UNSUPPORTED)
+        }
+
+        // Mirrors win_event_log's inherent `next`.
+        #[allow(clippy::should_implement_trait)]
+        pub fn next(&mut self) -> Option<String> {
+            match *self {}
+        }
+    }
+}
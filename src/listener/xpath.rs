@@ -0,0 +1,413 @@
+use std::fmt;
+use std::path::Path;
+
+#[cfg(windows)]
+use windows_sys::Win32::System::EventLog::{
+    EVT_HANDLE, EVT_QUERY_FLAGS, EvtClose, EvtNext, EvtQuery, EvtQueryChannelPath,
+    EvtQueryFilePath, EvtQueryForwardDirection, EvtQueryReverseDirection, EvtRender,
+    EvtRenderEventXml,
+};
+
+use crate::errors::SentinelError;
+
+use super::stats;
+
+#[cfg(windows)]
+const ERROR_INSUFFICIENT_BUFFER: i32 = 122;
+#[cfg(windows)]
+const ERROR_NO_MORE_ITEMS: i32 = 259;
+
+/// How many event handles to fetch per `EvtNext` call.
+#[cfg(windows)]
+const BATCH_SIZE: usize = 64;
+
+/// The order a query returns events in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum QueryDirection {
+    /// Oldest first, in ascending record ID order.
+    #[default]
+    Forward,
+    /// Newest first, so recent activity comes back without reading the whole log first.
+    Reverse,
+}
+
+#[cfg(windows)]
+impl QueryDirection {
+    fn flag(self) -> EVT_QUERY_FLAGS {
+        match self {
+            QueryDirection::Forward => EvtQueryForwardDirection,
+            QueryDirection::Reverse => EvtQueryReverseDirection,
+        }
+    }
+}
+
+/// A raw XPath query against one channel, passed to the event log as written. Unlike the query
+/// builder, it can filter on `EventData` values server-side, e.g.
+/// `*[System[(EventID=4625)]] and *[EventData[Data[@Name='LogonType']='10']]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XPathQuery {
+    channel: String,
+    xpath: String,
+    /// Whether `channel` is the path of an exported `.evtx` file rather than a channel name.
+    file: bool,
+}
+
+impl XPathQuery {
+    /// Fails if either the channel or the XPath is blank. Whether the XPath compiles is only
+    /// known once the event log runs it.
+    pub fn new(channel: &str, xpath: &str) -> Result<Self, SentinelError> {
+        let (channel, xpath) = (channel.trim(), xpath.trim());
+        if channel.is_empty() {
+            return Err(SentinelError::ConfigError(
+                "XPath query needs a channel".to_string(),
+            ));
+        }
+        if xpath.is_empty() {
+            return Err(SentinelError::InvalidXPath(
+                channel.to_string(),
+                "the query is empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            channel: channel.to_string(),
+            xpath: xpath.to_string(),
+            file: false,
+        })
+    }
+
+    /// Selects any of `event_ids` from `channel`, like the query builder does.
+    pub fn events(channel: &str, event_ids: &[u32]) -> Self {
+        let ids = event_ids
+            .iter()
+            .map(|id| format!("EventID={}", id))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        Self {
+            channel: channel.to_string(),
+            xpath: format!("*[System[({})]]", ids),
+            file: false,
+        }
+    }
+
+    /// Selects any of `event_ids` from the exported or archived `.evtx` file at `path` instead
+    /// of a live channel.
+    pub fn events_in_file(path: &Path, event_ids: &[u32]) -> Self {
+        Self {
+            file: true,
+            ..Self::events(&path.display().to_string(), event_ids)
+        }
+    }
+
+    /// Narrows the query to events whose `EventData` field `name` is one of `values`, so the
+    /// event log filters them instead of Hosho. Neither may contain a `'`.
+    pub fn with_event_data(mut self, name: &str, values: &[impl fmt::Display]) -> Self {
+        if values.is_empty() {
+            return self;
+        }
+        let matches = values
+            .iter()
+            .map(|value| format!("Data[@Name='{}']='{}'", name, value))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        self.xpath = format!("({}) and *[EventData[({})]]", self.xpath, matches);
+        self
+    }
+
+    /// Narrows the query to events written within `max_age` of when it runs.
+    pub fn with_max_age(mut self, max_age: chrono::Duration) -> Self {
+        self.xpath = format!(
+            "({}) and *[System[TimeCreated[timediff(@SystemTime) <= {}]]]",
+            self.xpath,
+            max_age.num_milliseconds().max(0)
+        );
+        self
+    }
+
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    pub fn xpath(&self) -> &str {
+        &self.xpath
+    }
+
+    /// Runs the query with `EvtQuery`, returning each matching event's XML in `direction` order.
+    /// A query the event log can't compile fails with [`SentinelError::InvalidXPath`].
+    pub(crate) fn run(&self, direction: QueryDirection) -> Result<Vec<String>, SentinelError> {
+        self.fetch(direction, usize::MAX)
+    }
+
+    /// Returns the XML of the `n` most recent matching events, newest first. Reads the log
+    /// backwards, so only those `n` events are ever fetched.
+    pub(crate) fn latest(&self, n: usize) -> Result<Vec<String>, SentinelError> {
+        self.fetch(QueryDirection::Reverse, n)
+    }
+
+    /// Returns the XML of the `n` oldest matching events, oldest first.
+    pub(crate) fn earliest(&self, n: usize) -> Result<Vec<String>, SentinelError> {
+        self.fetch(QueryDirection::Forward, n)
+    }
+
+    /// Counts the matching events without rendering or parsing them.
+    #[cfg(windows)]
+    pub(crate) fn count(&self) -> Result<u64, SentinelError> {
+        let results = self.open(QueryDirection::Forward)?;
+        let mut count = 0;
+        loop {
+            let batch = self.next_batch(&results, BATCH_SIZE)?;
+            if batch.is_empty() {
+                return Ok(count);
+            }
+            count += batch.len() as u64;
+        }
+    }
+
+    /// Renders up to `limit` matching events, holding the query's timing for the stats report
+    /// sent once they're parsed.
+    fn fetch(&self, direction: QueryDirection, limit: usize) -> Result<Vec<String>, SentinelError> {
+        let started = std::time::Instant::now();
+        let xmls = self.render(direction, limit)?;
+        stats::rendered(&self.channel, started.elapsed(), xmls.len());
+        Ok(xmls)
+    }
+
+    #[cfg(windows)]
+    fn render(
+        &self,
+        direction: QueryDirection,
+        limit: usize,
+    ) -> Result<Vec<String>, SentinelError> {
+        let results = self.open(direction)?;
+        let mut xmls = Vec::new();
+        while xmls.len() < limit {
+            let events = self.next_batch(&results, BATCH_SIZE.min(limit - xmls.len()))?;
+            if events.is_empty() {
+                break;
+            }
+            for event in &events {
+                xmls.push(render_xml(event.0).map_err(|e| {
+                    SentinelError::EventQueryError(format!("{}: {}", self.channel, e))
+                })?);
+            }
+        }
+        Ok(xmls)
+    }
+
+    #[cfg(windows)]
+    fn open(&self, direction: QueryDirection) -> Result<EvtHandle, SentinelError> {
+        let channel = wide(&self.channel);
+        let xpath = wide(&self.xpath);
+        let path_flag = if self.file {
+            EvtQueryFilePath
+        } else {
+            EvtQueryChannelPath
+        };
+        // SAFETY: both strings are NUL-terminated and outlive the call; the returned handle is
+        // closed by EvtHandle's Drop.
+        let results = EvtHandle(unsafe {
+            EvtQuery(
+                0,
+                channel.as_ptr(),
+                xpath.as_ptr(),
+                (path_flag | direction.flag()) as u32,
+            )
+        });
+        if results.0 == 0 {
+            let error = std::io::Error::last_os_error();
+            return Err(
+                match SentinelError::from_query_failure(
+                    &self.channel,
+                    error.raw_os_error(),
+                    &error.to_string(),
+                ) {
+                    SentinelError::QueryMalformed(_) => {
+                        SentinelError::InvalidXPath(self.channel.clone(), self.xpath.clone())
+                    }
+                    other => other,
+                },
+            );
+        }
+        Ok(results)
+    }
+
+    /// Fetches up to `max` (at most [`BATCH_SIZE`]) more event handles from `results`, or none
+    /// once the query is exhausted.
+    #[cfg(windows)]
+    fn next_batch(&self, results: &EvtHandle, max: usize) -> Result<Vec<EvtHandle>, SentinelError> {
+        let mut handles = [0 as EVT_HANDLE; BATCH_SIZE];
+        let mut returned = 0u32;
+        // SAFETY: `handles` has room for BATCH_SIZE handles and `returned` is valid for writes;
+        // each returned handle is closed by EvtHandle's Drop.
+        let ok = unsafe {
+            EvtNext(
+                results.0,
+                max.min(BATCH_SIZE) as u32,
+                handles.as_mut_ptr(),
+                0,
+                0,
+                &mut returned,
+            )
+        };
+        if ok == 0 {
+            let error = std::io::Error::last_os_error();
+            if error.raw_os_error() == Some(ERROR_NO_MORE_ITEMS) {
+                return Ok(Vec::new());
+            }
+            return Err(SentinelError::EventQueryError(format!(
+                "{}: {}",
+                self.channel, error
+            )));
+        }
+        Ok(handles[..returned as usize]
+            .iter()
+            .map(|&handle| EvtHandle(handle))
+            .collect())
+    }
+
+    #[cfg(not(windows))]
+    pub(crate) fn count(&self) -> Result<u64, SentinelError> {
+        Err(self.unsupported())
+    }
+
+    #[cfg(not(windows))]
+    fn render(
+        &self,
+        _direction: QueryDirection,
+        _limit: usize,
+    ) -> Result<Vec<String>, SentinelError> {
+        Err(self.unsupported())
+    }
+
+    #[cfg(not(windows))]
+    fn unsupported(&self) -> SentinelError {
+        SentinelError::EventQueryError(format!("{}: {}", self.channel, super::winevt::UNSUPPORTED))
+    }
+}
+
+/// Checks that the event log accepts `query`, the `QueryList` XML of a listener's query, without
+/// reading any events. Fails the way the listener's first poll would: for a channel that doesn't
+/// exist or can't be read, or a query the event log can't compile.
+#[cfg(windows)]
+pub fn validate_query(channel: &str, query: &impl fmt::Display) -> Result<(), SentinelError> {
+    let query = wide(&query.to_string());
+    // SAFETY: a null path makes EvtQuery read the channels from the structured query, which is
+    // NUL-terminated and outlives the call; the handle is closed by EvtHandle's Drop.
+    let results = EvtHandle(unsafe {
+        EvtQuery(
+            0,
+            std::ptr::null(),
+            query.as_ptr(),
+            (EvtQueryChannelPath | QueryDirection::Forward.flag()) as u32,
+        )
+    });
+    if results.0 == 0 {
+        let error = std::io::Error::last_os_error();
+        return Err(SentinelError::from_query_failure(
+            channel,
+            error.raw_os_error(),
+            &error.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Queries can't be checked off Windows.
+#[cfg(not(windows))]
+pub fn validate_query(channel: &str, _query: &impl fmt::Display) -> Result<(), SentinelError> {
+    Err(SentinelError::EventQueryError(format!(
+        "{}: {}",
+        channel,
+        super::winevt::UNSUPPORTED
+    )))
+}
+
+/// The query as the `QueryList` XML Event Viewer's "Filter Current Log" dialog accepts.
+impl fmt::Display for XPathQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.file {
+            format!("file://{}", self.channel)
+        } else {
+            self.channel.clone()
+        };
+        write!(
+            f,
+            "<QueryList>\n  <Query Id=\"0\" Path=\"{0}\">\n    <Select Path=\"{0}\">{1}</Select>\n  </Query>\n</QueryList>",
+            escape(&path),
+            escape(&self.xpath)
+        )
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(windows)]
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Closes an event log handle when dropped.
+#[cfg(windows)]
+pub(super) struct EvtHandle(pub(super) EVT_HANDLE);
+
+#[cfg(windows)]
+impl Drop for EvtHandle {
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            // SAFETY: the handle came from an Evt* call that opens one and is closed exactly once.
+            unsafe { EvtClose(self.0) };
+        }
+    }
+}
+
+/// Renders one event as XML, sizing the buffer with a first call that reports the length needed.
+/// The handle stays owned by the caller.
+#[cfg(windows)]
+pub(super) fn render_xml(event: EVT_HANDLE) -> std::io::Result<String> {
+    let mut used = 0u32;
+    let mut properties = 0u32;
+    // SAFETY: a zero-sized call with a null buffer only reports the required size in `used`.
+    let ok = unsafe {
+        EvtRender(
+            0,
+            event,
+            EvtRenderEventXml as u32,
+            0,
+            std::ptr::null_mut(),
+            &mut used,
+            &mut properties,
+        )
+    };
+    if ok == 0 {
+        let error = std::io::Error::last_os_error();
+        if error.raw_os_error() != Some(ERROR_INSUFFICIENT_BUFFER) {
+            return Err(error);
+        }
+    }
+
+    let mut buffer = vec![0u16; (used as usize).div_ceil(size_of::<u16>())];
+    // SAFETY: `buffer` holds at least `used` bytes, as the previous call asked for.
+    let ok = unsafe {
+        EvtRender(
+            0,
+            event,
+            EvtRenderEventXml as u32,
+            used,
+            buffer.as_mut_ptr().cast(),
+            &mut used,
+            &mut properties,
+        )
+    };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    // `used` counts bytes, including the trailing NUL.
+    let len = (used as usize / size_of::<u16>()).saturating_sub(1);
+    Ok(String::from_utf16_lossy(&buffer[..len]))
+}
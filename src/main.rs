@@ -1,58 +1,1183 @@
-mod errors;
-mod listener;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use clap::Parser;
+use tokio::time::{Instant, sleep_until};
 use tokio::{select, sync::mpsc};
+use tokio_stream::StreamExt;
 
-use crate::listener::{EventDetails, EventListener, LogonListener};
+use hosho::alert::{AlertGate, AlertThreshold};
+use hosho::clock::SystemClock;
+use hosho::enrich::firewall::DEFAULT_FIREWALL_LOG;
+use hosho::enrich::{
+    CurrentUserEnricher, FirewallCorrelator, FirstSeenEnricher, LockoutCorrelator,
+    ReverseDnsEnricher, RiskScorer, Severity, SeverityPolicy,
+};
+use hosho::errors::SentinelError;
+use hosho::listener::capture::{self, Capture};
+use hosho::listener::channels::{ChannelSet, list_channels};
+use hosho::listener::collapse::{AttemptCollapser, KeyPrefix};
+use hosho::listener::dedup::DedupKey;
+use hosho::listener::event_ids::parse_event_ids;
+use hosho::listener::pool;
+use hosho::listener::remote_exec::ScriptStorage;
+use hosho::listener::retry::{self, RetryPolicy};
+use hosho::listener::saturation::{self, ChannelGauge, DEFAULT_CHANNEL_CAPACITY};
+use hosho::listener::schedule::{PollSchedule, listener_seed};
+use hosho::listener::stats;
+use hosho::listener::supervise::{self, RestartPolicy, supervise};
+use hosho::listener::xpath::{QueryDirection, validate_query};
+use hosho::listener::{
+    AppLockerListener, DefenderListener, Event, EventKind, EventListener, EvtxDirListener,
+    HeartbeatListener, LockoutListener, LogonListener, PauseHandle, RemoteExecutionListener,
+    ScreenLockListener, UsbListener, poll,
+};
+use hosho::pipeline::{Pipeline, Tap};
+use hosho::sink::archive::{ArchiveSink, Compression};
+use hosho::sink::batch::Batcher;
+#[cfg(windows)]
+use hosho::sink::etw::EtwSink;
+#[cfg(windows)]
+use hosho::sink::eventlog::EventLogSink;
+use hosho::sink::file::FileSink;
+use hosho::sink::follow::FollowPrinter;
+use hosho::sink::projection::Projection;
+use hosho::sink::redact::{RedactedSink, RedactionRule, Redactor};
+use hosho::sink::spec::SinkSpec;
+use hosho::sink::stdout::StdoutSink;
+use hosho::sink::{DisplayTz, MultiSink, OutputFormat, Sink};
+use hosho::suppress::Suppressor;
+use hosho::tag::Tagger;
+use hosho::time_dedup::TimeDedup;
 
-// Helper function to create select! branches for multiple receivers
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Args {
+    /// Push a synthetic test event through every sink, report the result per sink, then exit
+    #[arg(long)]
+    self_test: bool,
+
+    /// Load every configured rule and state file, build the listeners and sinks, and check each
+    /// listener's channel exists and its query is accepted, then exit without processing events.
+    /// Exits non-zero if anything fails
+    #[arg(long)]
+    check_config: bool,
+
+    /// Print the query XML each listener would send to the event log, then exit. The output can
+    /// be pasted into Event Viewer's "Filter Current Log" XML tab
+    #[arg(long)]
+    dump_queries: bool,
+
+    /// List the event log channels on this machine, then exit
+    #[arg(long)]
+    list_channels: bool,
+
+    /// Run at most this many event log queries at once (defaults to the number of CPUs)
+    #[arg(long)]
+    max_concurrent_queries: Option<usize>,
+
+    /// Retry a query failing with a transient error (e.g. RPC) up to this many times within a
+    /// poll. Malformed queries and access denied errors are never retried
+    #[arg(long, default_value_t = 2)]
+    query_retries: u32,
+
+    /// How long to wait before the first query retry, doubling for each one after
+    #[arg(long, default_value_t = 500)]
+    query_retry_backoff_ms: u64,
+
+    /// Print each new logon event as it occurs, colorized, instead of running every listener
+    /// through the configured sinks
+    #[arg(long)]
+    follow: bool,
+
+    /// With --follow, resume after the last logon printed, remembering its record ID in this file
+    #[arg(long, requires = "follow")]
+    bookmark_file: Option<PathBuf>,
+
+    /// Watch logon events in a full-screen monitor with top offenders and per-type counts,
+    /// instead of running every listener through the configured sinks
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "follow")]
+    tui: bool,
+
+    /// Print the N most recent logon events, newest first, and exit
+    #[arg(long, value_name = "N", conflicts_with = "follow")]
+    last: Option<usize>,
+
+    /// Run the logons in every .evtx archive in this directory, oldest first, through the
+    /// pipeline and sinks, then exit, instead of reading the live logs
+    #[arg(long, value_name = "DIR", conflicts_with = "follow")]
+    evtx_dir: Option<PathBuf>,
+
+    /// Delay between polls of each listener, in milliseconds
+    #[arg(long, default_value_t = 1000)]
+    poll_interval_ms: u64,
+
+    /// Randomize each poll delay by up to this many milliseconds in either direction, kept below
+    /// --poll-interval-ms
+    #[arg(long, default_value_t = 0)]
+    jitter_ms: u64,
+
+    /// Seed for the jitter RNG, making poll delays reproducible. Each listener derives its own
+    /// seed from it, so they don't poll in lockstep
+    #[arg(long)]
+    jitter_seed: Option<u64>,
+
+    /// Give up on a listener after it has panicked and been restarted this many times
+    #[arg(long, default_value_t = 5)]
+    max_listener_restarts: u32,
+
+    /// Send events to this sink, as KIND[:ARG]; repeat for several. Kinds: stdout, file:PATH,
+    /// archive:PATH, etw (Windows), eventlog (Windows), otlp[:URL] (with the otel feature). Replaces the default
+    /// stdout sink, so include stdout to keep it. A failing sink never stops the others
+    #[arg(long = "sink", value_name = "SPEC")]
+    sinks: Vec<SinkSpec>,
+
+    /// Also append events to this file
+    #[arg(long)]
+    output_file: Option<PathBuf>,
+
+    /// Also append events to this binary archive, compact enough for long-term retention
+    #[arg(long)]
+    archive: Option<PathBuf>,
+
+    /// How the archive's records are compressed
+    #[arg(long, value_enum, default_value_t = Compression::None)]
+    archive_compression: Compression,
+
+    /// Archive events as parsed, before suppression, tagging, and enrichment, rather than as
+    /// delivered to the other sinks. Use this to keep a raw archive while alerting on enriched
+    /// events
+    #[arg(long, requires = "archive")]
+    archive_raw: bool,
+
+    /// Also write events to the Hosho ETW provider, as ECS JSON
+    #[cfg(windows)]
+    #[arg(long)]
+    etw: bool,
+
+    /// Also export events as OpenTelemetry logs to this OTLP/HTTP endpoint (a local collector if
+    /// no URL is given)
+    #[cfg(feature = "otel")]
+    #[arg(long, num_args = 0..=1, default_missing_value = hosho::sink::otel::DEFAULT_ENDPOINT)]
+    otlp_endpoint: Option<String>,
+
+    /// How events are written to stdout and the output file
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    /// Only keep these fields in JSON and ECS output to stdout and the output file, as
+    /// comma-separated dotted paths (e.g. timestamp,details.Login.variant). Keeps every field
+    /// when unset
+    #[arg(long, value_delimiter = ',')]
+    fields: Vec<String>,
+
+    /// Time zone for human-readable timestamps and for judging business hours: "local" or an
+    /// IANA name such as Europe/Berlin
+    #[arg(long, default_value = "local")]
+    display_tz: DisplayTz,
+
+    /// Redact a field in events printed to stdout, as FIELD=MODE. Fields: source_ip,
+    /// source_hostname, username. Modes: mask, hash, network (keep the /24 or /48)
+    #[arg(long)]
+    redact_stdout: Vec<RedactionRule>,
+
+    /// Redact a field in events written to the output file, as for --redact-stdout
+    #[arg(long)]
+    redact_file: Vec<RedactionRule>,
+
+    /// Only print these kinds of events to stdout, comma-separated (e.g. login,threat-detected).
+    /// Prints every kind when unset
+    #[arg(long, value_enum, value_delimiter = ',')]
+    stdout_events: Vec<EventKind>,
+
+    /// Only write these kinds of events to the output file, as for --stdout-events
+    #[arg(long, value_enum, value_delimiter = ',')]
+    file_events: Vec<EventKind>,
+
+    /// Salt for hashed redactions. Random for each run if unset, so hashes only correlate within
+    /// a run
+    #[arg(long)]
+    redact_salt: Option<String>,
+
+    /// Deliver events to sinks in batches of up to this many
+    #[arg(long, default_value_t = 1)]
+    batch_size: usize,
+
+    /// Deliver a partial batch once its oldest event has waited this many milliseconds
+    #[arg(long, default_value_t = 250)]
+    batch_delay_ms: u64,
+
+    /// Collapse identical failed logons within this many seconds into one event with a count
+    #[arg(long)]
+    collapse_failures_secs: Option<i64>,
+
+    /// Report a network logon followed by an interactive one for the same session (TargetLogonId)
+    /// as a single interactive logon
+    #[arg(long)]
+    collapse_sessions: bool,
+
+    /// Treat failed logons from the same subnet as one source when collapsing, given as IPv4 and
+    /// IPv6 prefix lengths (e.g. `24,64`)
+    #[arg(long, requires = "collapse_failures_secs")]
+    collapse_key_prefix: Option<KeyPrefix>,
+
+    /// Score each logon's risk from 0 to 100 by logon type, source, time, NTLM use, and outcome
+    #[arg(long)]
+    risk_score: bool,
+
+    /// Only send alerts to the sinks: events at or above --alert-min-severity, with a risk score
+    /// of at least --alert-min-risk-score, or account lockouts, plus heartbeats and query stats.
+    /// Routine events are counted and written as a JSON summary every --summary-interval-mins
+    /// instead
+    #[arg(long)]
+    alerts_only: bool,
+
+    /// The lowest severity --alerts-only passes on and the eventlog sink writes
+    #[arg(long, value_enum, default_value_t = Severity::High)]
+    alert_min_severity: Severity,
+
+    /// Also pass on logons with at least this risk score under --alerts-only. Implies
+    /// --risk-score
+    #[arg(long, requires = "alerts_only")]
+    alert_min_risk_score: Option<u8>,
+
+    /// How often --alerts-only prints a summary of the routine events it held back
+    #[arg(long, default_value_t = 60)]
+    summary_interval_mins: u64,
+
+    /// Append --alerts-only summaries to this file instead of printing them
+    #[arg(long, requires = "alerts_only")]
+    summary_file: Option<PathBuf>,
+
+    /// Flag logons by the user currently signed in at the console
+    #[arg(long)]
+    tag_current_user: bool,
+
+    /// Resolve public logon source addresses to hostnames, waiting at most this many milliseconds
+    #[arg(long)]
+    resolve_source_ms: Option<u64>,
+
+    /// Flag logons from never-before-seen addresses and users, remembering them in this JSON Lines
+    /// file
+    #[arg(long)]
+    first_seen_file: Option<PathBuf>,
+
+    /// Tag logons with whether the Windows Firewall allowed or dropped a connection from the
+    /// same address, read from this log (the default location if no path is given)
+    #[arg(long, num_args = 0..=1, default_missing_value = DEFAULT_FIREWALL_LOG)]
+    firewall_log: Option<PathBuf>,
+
+    /// How many seconds apart a firewall log entry and a logon may be to correlate
+    #[arg(long, default_value_t = 5, requires = "firewall_log")]
+    firewall_window_secs: i64,
+
+    /// How many minutes of failed logons to attach to an account lockout as its causes
+    #[arg(long, default_value_t = 30)]
+    lockout_window_mins: i64,
+
+    /// Pause the logon listeners while this file exists, e.g. during a backup job that floods
+    /// the log with service logons. They catch up on what was logged once it's removed
+    #[arg(long)]
+    pause_file: Option<PathBuf>,
+
+    /// Also emit a QueryStats event after every event log query, with how long it took and how
+    /// many events it returned, for diagnosing slow queries, report how full each listener's
+    /// channel got every minute, and report how many events each `--evtx-dir` file held
+    #[arg(long)]
+    verbose: bool,
+
+    /// How many events each listener can queue for the pipeline before it waits for the sinks
+    #[arg(long, default_value_t = DEFAULT_CHANNEL_CAPACITY)]
+    channel_capacity: usize,
+
+    /// Append the raw XML of every event read to this file, to reproduce a missed or misparsed
+    /// event later with --replay
+    #[arg(long)]
+    capture: Option<PathBuf>,
+
+    /// Stop capturing once the capture file reaches this many megabytes
+    #[arg(long, default_value_t = capture::DEFAULT_MAX_BYTES / (1024 * 1024), requires = "capture")]
+    capture_max_mb: u64,
+
+    /// Feed the logon events in this capture (or any file of event XML) to the logon listeners
+    /// instead of reading the Security log
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    /// Emit a heartbeat event every this many seconds
+    #[arg(long)]
+    heartbeat_secs: Option<u64>,
+
+    /// Never forward events matching any rule in this JSON file
+    #[arg(long)]
+    suppress_rules: Option<PathBuf>,
+
+    /// Forward events identical on --time-dedup-fields at most once per this many seconds, by
+    /// event time, whatever their record IDs
+    #[arg(long)]
+    time_dedup_secs: Option<i64>,
+
+    /// The fields --time-dedup-secs compares, comma-separated, using suppression rule field
+    /// names (default: username,source_ip,variant,success)
+    #[arg(long, value_delimiter = ',', requires = "time_dedup_secs")]
+    time_dedup_fields: Option<Vec<String>>,
+
+    /// Tag events matching rules in this JSON file, for routing and downstream filtering
+    #[arg(long)]
+    tag_rules: Option<PathBuf>,
+
+    /// Keep at most this many bytes of PowerShell script text per event (0 drops it entirely)
+    #[arg(long)]
+    max_script_len: Option<usize>,
+
+    /// Security event IDs the logon listeners query, as a list of IDs and ranges
+    /// (e.g. `4624-4625,4648`)
+    #[arg(long, value_parser = parse_event_ids)]
+    logon_event_ids: Option<::std::vec::Vec<u32>>,
+
+    /// Only collect logons of these LogonType numbers (e.g. `2,10,11`), filtered by the event log
+    #[arg(long, value_delimiter = ',', conflicts_with = "logon_xpath")]
+    logon_types: Vec<u32>,
+
+    /// The order the logon listeners read each poll's events in. Reverse returns the newest first
+    /// and disables subscribing
+    #[arg(long, value_enum, default_value_t = QueryDirection::Forward)]
+    query_direction: QueryDirection,
+
+    /// Query the logon listeners with this raw XPath instead of by event ID, e.g. to filter on
+    /// EventData values: `*[System[(EventID=4625)]] and *[EventData[Data[@Name='LogonType']='10']]`
+    #[arg(long, conflicts_with = "logon_event_ids")]
+    logon_xpath: Option<String>,
+
+    /// The channel `--logon-xpath` is run against
+    #[arg(long, default_value = "Security", requires = "logon_xpath")]
+    logon_channel: String,
+
+    /// Drop logon events older than this many hours, e.g. when catching up on a long backlog
+    #[arg(long)]
+    max_event_age_hours: Option<i64>,
+
+    /// Forward at most this many new logon events per poll; the rest follow on later polls
+    #[arg(long)]
+    max_batch: Option<usize>,
+
+    /// How already-forwarded logon events are recognized: record-id, record-id-plus-computer
+    /// (for WEF collectors), or content-hash
+    #[arg(long, default_value = "record-id")]
+    dedup_key: DedupKey,
+}
+
+// Helper macro to select the next event from whichever receiver has one ready
 macro_rules! select_all {
-    ([$($receiver:expr),*], $handler:ident) => {
+    ([$($receiver:expr),*]) => {
         select! {
             $(
-                Some(event) = $receiver.recv() => {
-                    $handler(event);
-                }
+                Some(event) = $receiver.recv() => event,
             )*
         }
     };
 }
 
+/// Sends a synthetic event through each sink, returning whether all of them succeeded.
+async fn self_test(sinks: &MultiSink) -> bool {
+    let event = Event::self_test();
+
+    let mut all_ok = true;
+    for (name, result) in sinks.emit_each(&event).await {
+        match result {
+            Ok(()) => eprintln!("Self-test: {} OK", name),
+            Err(e) => {
+                eprintln!("Self-test: {} FAILED: {}", name, e);
+                all_ok = false;
+            }
+        }
+    }
+    all_ok
+}
+
+/// Loads an optional configured file with `load`, reporting the outcome. Unset files pass.
+fn check_file<'a, T>(
+    what: &str,
+    path: Option<&'a PathBuf>,
+    load: impl FnOnce(&'a PathBuf) -> Result<T, SentinelError>,
+) -> bool {
+    let Some(path) = path else {
+        return true;
+    };
+    match load(path) {
+        Ok(_) => {
+            println!("{} ({}): OK", what, path.display());
+            true
+        }
+        Err(e) => {
+            println!("{} ({}): FAILED: {}", what, path.display(), e);
+            false
+        }
+    }
+}
+
+/// Reports whether a listener named `name` can run: that the channel it reads exists and the
+/// event log accepts its query. Listeners that read no channel always can.
+fn check_listener(
+    name: &str,
+    channel: Option<&str>,
+    query: Option<String>,
+    channels: &ChannelSet,
+) -> bool {
+    let result = match channel {
+        None => Ok(()),
+        Some(channel) if !channels.contains(channel) => Err(format!(
+            "the {} channel doesn't exist on this machine",
+            channel
+        )),
+        Some(channel) => query.map_or(Ok(()), |query| {
+            validate_query(channel, &query).map_err(|e| e.to_string())
+        }),
+    };
+    match result {
+        Ok(()) => {
+            println!("Listener {}: OK", name);
+            true
+        }
+        Err(e) => {
+            println!("Listener {}: FAILED: {}", name, e);
+            false
+        }
+    }
+}
+
+/// Checks the listener `name` with [`check_listener`].
+fn check(name: &str, listener: &impl EventListener, channels: &ChannelSet) -> bool {
+    check_listener(
+        name,
+        listener.channel(),
+        listener.query().map(|query| query.to_string()),
+        channels,
+    )
+}
+
+/// Reports whether each configured file loads, whether each listener main would start can run,
+/// and what would run with `sinks`.
+fn check_config(args: &Args, sinks: &MultiSink) -> bool {
+    let files = [
+        check_file(
+            "Suppression rules",
+            args.suppress_rules.as_ref(),
+            Suppressor::load,
+        ),
+        check_file("Tag rules", args.tag_rules.as_ref(), Tagger::load),
+        check_file(
+            "First-seen state",
+            args.first_seen_file.as_ref(),
+            FirstSeenEnricher::open,
+        ),
+    ];
+
+    let (tx, _rx) = mpsc::channel(1);
+    let channels = ChannelSet::list();
+    let logon = configure_logon(LogonListener::new(tx.clone()), args).and_then(|logon| {
+        let Some(path) = &args.replay else {
+            return Ok(logon);
+        };
+        let event_ids = args.logon_event_ids.as_deref().unwrap_or(&[4625]);
+        capture::replay_source(path, event_ids)
+            .map(|source| logon.with_xml_source(source))
+            .map_err(|e| SentinelError::ConfigError(format!("{}: {}", path.display(), e)))
+    });
+    let logon_ok = match &logon {
+        Ok(logon) => check_listener(
+            "logon",
+            logon.channel(),
+            logon
+                .query()
+                .map(|query| query.to_string())
+                .or_else(|| logon.xpath().map(|xpath| xpath.to_string())),
+            &channels,
+        ),
+        Err(e) => {
+            println!("Listener logon: FAILED: {}", e);
+            false
+        }
+    };
+    let listeners = [
+        logon_ok,
+        check("usb", &UsbListener::new(tx.clone()), &channels),
+        check(
+            "screen-lock",
+            &ScreenLockListener::new(tx.clone()),
+            &channels,
+        ),
+        check("lockout", &LockoutListener::new(tx.clone()), &channels),
+        check("defender", &DefenderListener::new(tx.clone()), &channels),
+        check(
+            "applocker-exe",
+            &AppLockerListener::executables(tx.clone()),
+            &channels,
+        ),
+        check(
+            "applocker-script",
+            &AppLockerListener::scripts(tx.clone()),
+            &channels,
+        ),
+        check(
+            "powershell",
+            &RemoteExecutionListener::powershell(tx.clone()),
+            &channels,
+        ),
+        check(
+            "winrm",
+            &RemoteExecutionListener::winrm(tx.clone()),
+            &channels,
+        ),
+        args.heartbeat_secs
+            .is_none_or(|_| check("heartbeat", &HeartbeatListener::new(tx), &channels)),
+    ];
+
+    println!("Sinks: {}", sinks.names().join(", "));
+    files.into_iter().chain(listeners).all(|ok| ok)
+}
+
+/// Chains the configured filters and enrichers, in the order events pass through them. A raw
+/// archive is tapped ahead of all of them, and the alert gate comes after.
+fn build_pipeline(
+    args: &Args,
+    raw_archive: Option<ArchiveSink>,
+    alert_gate: Option<Arc<AlertGate>>,
+) -> Result<Pipeline, SentinelError> {
+    let mut pipeline = Pipeline::new();
+    if let Some(archive) = raw_archive {
+        pipeline = pipeline.with_transform(Tap::new(archive));
+    }
+    // Ahead of suppression and dedup, so a lockout's causes include every failure.
+    pipeline = pipeline.with_transform(LockoutCorrelator::new(chrono::Duration::minutes(
+        args.lockout_window_mins,
+    )));
+    if let Some(path) = &args.suppress_rules {
+        pipeline = pipeline.with_transform(Suppressor::load(path)?);
+    }
+    if let Some(secs) = args.time_dedup_secs {
+        let window = chrono::Duration::seconds(secs);
+        pipeline = pipeline.with_transform(match &args.time_dedup_fields {
+            Some(fields) => TimeDedup::with_fields(window, fields.clone()),
+            None => TimeDedup::new(window),
+        });
+    }
+    if let Some(path) = &args.tag_rules {
+        pipeline = pipeline.with_transform(Tagger::load(path)?);
+    }
+    if args.tag_current_user {
+        pipeline = pipeline.with_transform(CurrentUserEnricher::new());
+    }
+    if let Some(ms) = args.resolve_source_ms {
+        pipeline = pipeline.with_transform(ReverseDnsEnricher::new(Duration::from_millis(ms)));
+    }
+    if let Some(path) = &args.first_seen_file {
+        pipeline = pipeline.with_transform(Mutex::new(FirstSeenEnricher::open(path)?));
+    }
+    if let Some(path) = &args.firewall_log {
+        let window = chrono::Duration::seconds(args.firewall_window_secs);
+        pipeline = pipeline.with_transform(FirewallCorrelator::new(path, window));
+    }
+    if args.risk_score || args.alert_min_risk_score.is_some() {
+        pipeline = pipeline.with_transform(RiskScorer::default().with_display_tz(args.display_tz));
+    }
+    pipeline = pipeline.with_transform(SeverityPolicy::default().with_display_tz(args.display_tz));
+    if let Some(gate) = alert_gate {
+        pipeline = pipeline.with_transform(gate);
+    }
+    Ok(pipeline)
+}
+
+/// What --alerts-only and the event log sink treat as an alert.
+fn alert_threshold(args: &Args) -> AlertThreshold {
+    let mut threshold = AlertThreshold::new(args.alert_min_severity);
+    if let Some(min_risk_score) = args.alert_min_risk_score {
+        threshold = threshold.with_min_risk_score(min_risk_score);
+    }
+    threshold
+}
+
+/// Prints every listener's query without running it.
+fn dump_queries(args: &Args) -> Result<(), SentinelError> {
+    let (tx, _rx) = mpsc::channel(1);
+
+    let logon = configure_logon(LogonListener::new(tx.clone()), args)?;
+    if let Some(xpath) = logon.xpath() {
+        println!("<!-- logon -->\n{}\n", xpath);
+    }
+
+    let queries = [
+        ("logon", logon.query()),
+        ("usb", UsbListener::new(tx.clone()).query()),
+        ("screen lock", ScreenLockListener::new(tx.clone()).query()),
+        ("lockout", LockoutListener::new(tx.clone()).query()),
+        ("defender", DefenderListener::new(tx.clone()).query()),
+        (
+            "applocker executables",
+            AppLockerListener::executables(tx.clone()).query(),
+        ),
+        (
+            "applocker scripts",
+            AppLockerListener::scripts(tx.clone()).query(),
+        ),
+        (
+            "powershell",
+            RemoteExecutionListener::powershell(tx.clone()).query(),
+        ),
+        ("winrm", RemoteExecutionListener::winrm(tx).query()),
+    ];
+
+    let queries = queries
+        .into_iter()
+        .filter_map(|(name, query)| Some((name, query?)));
+    for (name, query) in queries {
+        println!("<!-- {} -->\n{}\n", name, query);
+    }
+    Ok(())
+}
+
+fn redactor(rules: &[RedactionRule], salt: &str) -> Redactor {
+    rules.iter().fold(Redactor::new(salt), |redactor, rule| {
+        redactor.with_rule(rule.field, rule.redaction)
+    })
+}
+
+/// Builds the sink `spec` describes and adds it to `sinks`. Stdout and file sinks take the
+/// output format, redaction, and event kinds configured for them, and the event log sink only
+/// receives alerts.
+async fn with_spec(
+    sinks: MultiSink,
+    spec: &SinkSpec,
+    args: &Args,
+    salt: &str,
+) -> Result<MultiSink, SentinelError> {
+    Ok(match spec {
+        SinkSpec::Stdout => with_redaction(
+            sinks,
+            StdoutSink::new()
+                .with_format(args.output_format)
+                .with_display_tz(args.display_tz)
+                .with_projection(Projection::new(&args.fields)),
+            redactor(&args.redact_stdout, salt),
+            &args.stdout_events,
+        ),
+        SinkSpec::File(path) => with_redaction(
+            sinks,
+            FileSink::open(path)
+                .await?
+                .with_format(args.output_format)
+                .with_display_tz(args.display_tz)
+                .with_projection(Projection::new(&args.fields)),
+            redactor(&args.redact_file, salt),
+            &args.file_events,
+        ),
+        SinkSpec::Archive(path) => {
+            sinks.with_sink(ArchiveSink::open(path, args.archive_compression).await?)
+        }
+        #[cfg(windows)]
+        SinkSpec::Etw => sinks.with_sink(EtwSink::new()?),
+        #[cfg(windows)]
+        SinkSpec::EventLog => {
+            let threshold = alert_threshold(args);
+            sinks.with_sink_where(EventLogSink::new()?, move |event| threshold.is_alert(event))
+        }
+        #[cfg(feature = "otel")]
+        SinkSpec::Otlp(endpoint) => sinks.with_sink(match endpoint {
+            Some(endpoint) => hosho::sink::otel::OtelSink::export_to(endpoint)?,
+            None => hosho::sink::otel::OtelSink::new()?,
+        }),
+    })
+}
+
+/// Adds `sink` to `sinks`, behind `redactor` if it has any rules.
+fn with_redaction(
+    sinks: MultiSink,
+    sink: impl Sink + 'static,
+    redactor: Redactor,
+    kinds: &[EventKind],
+) -> MultiSink {
+    if redactor.is_empty() {
+        with_kinds(sinks, sink, kinds)
+    } else {
+        with_kinds(sinks, RedactedSink::new(sink, redactor), kinds)
+    }
+}
+
+/// Adds `sink` to `sinks`, receiving only events of `kinds` unless that's empty.
+fn with_kinds(sinks: MultiSink, sink: impl Sink + 'static, kinds: &[EventKind]) -> MultiSink {
+    if kinds.is_empty() {
+        sinks.with_sink(sink)
+    } else {
+        sinks.with_sink_for(sink, kinds)
+    }
+}
+
+/// Applies the logon listener options from the command line.
+fn configure_logon(listener: LogonListener, args: &Args) -> Result<LogonListener, SentinelError> {
+    let mut listener = listener
+        .with_poll_interval(Duration::from_millis(args.poll_interval_ms))
+        .with_jitter(Duration::from_millis(args.jitter_ms), args.jitter_seed)
+        .with_dedup_key(args.dedup_key)
+        .with_direction(args.query_direction);
+    if let Some(event_ids) = &args.logon_event_ids {
+        listener = listener.with_event_ids(event_ids.clone());
+    }
+    if !args.logon_types.is_empty() {
+        listener = listener.with_logon_types(args.logon_types.clone());
+    }
+    if let Some(xpath) = &args.logon_xpath {
+        listener = listener.with_xpath(&args.logon_channel, xpath)?;
+    }
+    if let Some(max_batch) = args.max_batch {
+        listener = listener.with_max_batch(max_batch);
+    }
+    if let Some(hours) = args.max_event_age_hours {
+        listener = listener.with_max_age(chrono::Duration::hours(hours), Arc::new(SystemClock));
+    }
+    if args.collapse_sessions {
+        listener = listener.with_session_collapsing();
+    }
+    if let Some(secs) = args.collapse_failures_secs {
+        let mut collapser =
+            AttemptCollapser::new(chrono::Duration::seconds(secs), Arc::new(SystemClock));
+        if let Some(key_prefix) = args.collapse_key_prefix {
+            collapser = collapser.with_key_prefix(key_prefix);
+        }
+        listener = listener.with_failure_collapser(collapser);
+    }
+    Ok(listener)
+}
+
+/// Polls `listener` on its own task, restarting it with `restarts` if it panics. A listener
+/// whose channel isn't in `channels` is skipped with a warning, so the others still run.
+fn spawn_listener<L>(
+    name: &'static str,
+    listener: L,
+    schedule: PollSchedule,
+    restarts: RestartPolicy,
+    channels: &ChannelSet,
+) where
+    L: EventListener + Send + 'static,
+{
+    if let Some(channel) = channels.missing(&listener) {
+        warn_missing_channel(name, channel);
+        return;
+    }
+    tokio::spawn(supervise(name, restarts, move || {
+        poll(listener.clone(), schedule.clone())
+    }));
+}
+
+fn warn_missing_channel(name: &str, channel: &str) {
+    eprintln!(
+        "Warning: the {} channel doesn't exist on this machine, so the {} listener is disabled",
+        channel, name
+    );
+}
+
+/// Pauses `pause` while `path` exists and resumes it once the file is gone, checking every
+/// `interval`.
+async fn watch_pause_file(path: PathBuf, pause: PauseHandle, interval: Duration) {
+    loop {
+        let exists = path.exists();
+        if exists != pause.is_paused() {
+            if exists {
+                eprintln!("Pausing logon listeners while {} exists", path.display());
+                pause.pause();
+            } else {
+                eprintln!("Resuming logon listeners");
+                pause.resume();
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Prints how full each channel got every `interval`, for tuning `--channel-capacity`.
+async fn report_saturation(gauges: Vec<ChannelGauge>, interval: Duration) {
+    let mut ticks = tokio::time::interval_at(Instant::now() + interval, interval);
+    loop {
+        ticks.tick().await;
+        for gauge in &gauges {
+            eprintln!("Channel {}", gauge.take());
+        }
+    }
+}
+
+/// Where `--alerts-only` writes its summaries: `--summary-file` if given, otherwise stdout.
+fn summary_writer(args: &Args) -> std::io::Result<Box<dyn Write + Send>> {
+    Ok(match &args.summary_file {
+        Some(path) => Box::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?,
+        ),
+        None => Box::new(std::io::stdout()),
+    })
+}
+
+/// Tails the logon listener, printing each event until interrupted.
+async fn follow(listener: LogonListener, display_tz: DisplayTz) {
+    let printer = FollowPrinter::from_env().with_display_tz(display_tz);
+    let mut events = listener.tail();
+    while let Some(event) = events.next().await {
+        println!("{}", printer.render(&event));
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let (logon_tx, mut logon_rx) = mpsc::channel(100);
-    let (logon_tx2, mut logon_rx2) = mpsc::channel(100);
-    let listeners = vec![LogonListener::new(logon_tx), LogonListener::new(logon_tx2)];
-
-    for listener in listeners {
-        let listener_clone = listener.clone();
-        tokio::spawn(async move {
-            loop {
-                listener_clone.invoke();
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    let args = Args::parse();
+
+    let salt = args
+        .redact_salt
+        .clone()
+        .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()));
+    let specs = if args.sinks.is_empty() {
+        vec![SinkSpec::Stdout]
+    } else {
+        args.sinks.clone()
+    };
+    let mut sinks = MultiSink::new();
+    for spec in &specs {
+        sinks = with_spec(sinks, spec, &args, &salt).await?;
+    }
+    if let Some(path) = &args.output_file {
+        sinks = with_spec(sinks, &SinkSpec::File(path.clone()), &args, &salt).await?;
+    }
+    let mut raw_archive = None;
+    if let Some(path) = &args.archive {
+        let archive = ArchiveSink::open(path, args.archive_compression).await?;
+        if args.archive_raw {
+            raw_archive = Some(archive);
+        } else {
+            sinks = sinks.with_sink(archive);
+        }
+    }
+    #[cfg(windows)]
+    if args.etw {
+        sinks = sinks.with_sink(EtwSink::new()?);
+    }
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &args.otlp_endpoint {
+        sinks = sinks.with_sink(hosho::sink::otel::OtelSink::export_to(endpoint)?);
+    }
+
+    if args.check_config {
+        return if check_config(&args, &sinks) {
+            Ok(())
+        } else {
+            Err("Configuration check failed".into())
+        };
+    }
+
+    if args.dump_queries {
+        dump_queries(&args)?;
+        return Ok(());
+    }
+
+    if args.list_channels {
+        for channel in list_channels()? {
+            println!("{}", channel);
+        }
+        return Ok(());
+    }
+
+    if args.self_test {
+        return if self_test(&sinks).await {
+            Ok(())
+        } else {
+            Err("Self-test failed for one or more sinks".into())
+        };
+    }
+
+    // Only live queries need elevation: EVTX files and replays are read like any other file, and
+    // off Windows there's no event log to query.
+    let queries_live_log = cfg!(windows) && args.evtx_dir.is_none() && args.replay.is_none();
+    if queries_live_log && !hosho::privileges::is_elevated() {
+        eprintln!(
+            "Warning: not running as Administrator. Reading the Security log requires Administrator privileges, so logon and screen lock events will fail to load."
+        );
+    }
+
+    if let Some(max_concurrent) = args.max_concurrent_queries {
+        pool::configure(max_concurrent);
+    }
+    retry::configure(RetryPolicy::new(
+        args.query_retries,
+        Duration::from_millis(args.query_retry_backoff_ms),
+    ));
+
+    if let Some(n) = args.last {
+        let (tx, _rx) = mpsc::channel(1);
+        let printer = FollowPrinter::from_env().with_display_tz(args.display_tz);
+        for event in configure_logon(LogonListener::new(tx), &args)?.last(n)? {
+            println!("{}", printer.render(&event));
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "tui")]
+    if args.tui {
+        let (tx, _rx) = mpsc::channel(1);
+        hosho::tui::run(
+            configure_logon(LogonListener::new(tx), &args)?,
+            args.display_tz,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if args.follow {
+        let (tx, _rx) = mpsc::channel(1);
+        let mut listener = configure_logon(LogonListener::new(tx), &args)?;
+        if let Some(path) = &args.bookmark_file {
+            listener = listener.with_bookmark(path)?;
+        }
+        follow(listener, args.display_tz).await;
+        return Ok(());
+    }
+
+    let channel_capacity = args.channel_capacity.max(1);
+    if let Some(dir) = &args.evtx_dir {
+        let (tx, mut rx) = mpsc::channel(channel_capacity);
+        let mut listener = EvtxDirListener::new(tx, dir);
+        if let Some(event_ids) = &args.logon_event_ids {
+            listener = listener.with_event_ids(event_ids.clone());
+        }
+        let reading = tokio::spawn(listener.run());
+
+        let pipeline = build_pipeline(&args, raw_archive, None)?;
+        while let Some(event) = rx.recv().await {
+            if let Some(event) = pipeline.run(event).await
+                && let Err(e) = sinks.emit(&event).await
+            {
+                eprintln!("Failed to deliver event: {}", e);
             }
-        });
+        }
+        let files = reading.await??;
+        sinks.close().await?;
+        let mut count = 0;
+        for (file, sent) in files {
+            match sent {
+                Some(sent) => {
+                    if args.verbose {
+                        eprintln!("{}: {} events", file.display(), sent);
+                    }
+                    count += sent;
+                }
+                None => eprintln!("Skipped {}", file.display()),
+            }
+        }
+        eprintln!("Read {} events from {}", count, dir.display());
+        return Ok(());
     }
+    // Configured before any listener runs, so every query is covered.
+    let (stats_tx, mut stats_rx) = saturation::channel("stats", channel_capacity);
+    if args.verbose {
+        stats::configure(stats_tx);
+    }
+    let (panics_tx, mut panics_rx) = saturation::channel("supervise", channel_capacity);
+    supervise::configure(panics_tx);
+    if let Some(path) = &args.capture {
+        capture::configure(Capture::open(path, args.capture_max_mb * 1024 * 1024)?);
+    }
+    let replay = match &args.replay {
+        Some(path) => Some(capture::replay_source(
+            path,
+            args.logon_event_ids.as_deref().unwrap_or(&[4625]),
+        )?),
+        None => None,
+    };
+
+    let (logon_tx, mut logon_rx) = saturation::channel("logon", channel_capacity);
+    let (logon_tx2, mut logon_rx2) = saturation::channel("logon-2", channel_capacity);
+    let listeners = [
+        ("logon", LogonListener::new(logon_tx)),
+        ("logon-2", LogonListener::new(logon_tx2)),
+    ];
 
-    let handle_event = |event: crate::listener::Event| match event.details {
-        EventDetails::Login(login_event) => {
-            println!(
-                r#"Event: Failed Login for {} ({}) on {} from {}"#,
-                login_event.username,
-                login_event.variant,
-                event
-                    .timestamp
-                    .with_timezone(&chrono::Local)
-                    .format("%A, %B %d, %Y at %I:%M:%S %p"),
-                login_event.source_ip
-            );
+    let poll_interval = Duration::from_millis(args.poll_interval_ms);
+    let jitter = Duration::from_millis(args.jitter_ms);
+    let schedule = |name: &str| {
+        PollSchedule::new(poll_interval, jitter, listener_seed(args.jitter_seed, name))
+    };
+    let restarts = RestartPolicy::new(args.max_listener_restarts, Duration::from_secs(1));
+
+    let logon_pause = PauseHandle::new();
+    let channels = ChannelSet::list();
+    for (name, listener) in listeners {
+        let mut listener = configure_logon(listener, &args)?
+            .with_jitter(jitter, listener_seed(args.jitter_seed, name))
+            .with_pause(logon_pause.clone());
+        if let Some(source) = &replay {
+            listener = listener.with_xml_source(Arc::clone(source));
+        }
+        if let Some(channel) = channels.missing(&listener) {
+            warn_missing_channel(name, channel);
+            continue;
         }
+        tokio::spawn(supervise(name, restarts, move || listener.clone().run()));
+    }
+    if let Some(path) = args.pause_file.clone() {
+        tokio::spawn(watch_pause_file(path, logon_pause, poll_interval));
+    }
+
+    let (usb_tx, mut usb_rx) = saturation::channel("usb", channel_capacity);
+    spawn_listener(
+        "usb",
+        UsbListener::new(usb_tx),
+        schedule("usb"),
+        restarts,
+        &channels,
+    );
+
+    let (lock_tx, mut lock_rx) = saturation::channel("screen-lock", channel_capacity);
+    spawn_listener(
+        "screen-lock",
+        ScreenLockListener::new(lock_tx),
+        schedule("screen-lock"),
+        restarts,
+        &channels,
+    );
+
+    let (lockout_tx, mut lockout_rx) = saturation::channel("lockout", channel_capacity);
+    spawn_listener(
+        "lockout",
+        LockoutListener::new(lockout_tx),
+        schedule("lockout"),
+        restarts,
+        &channels,
+    );
+
+    let (defender_tx, mut defender_rx) = saturation::channel("defender", channel_capacity);
+    spawn_listener(
+        "defender",
+        DefenderListener::new(defender_tx),
+        schedule("defender"),
+        restarts,
+        &channels,
+    );
+
+    let (applocker_tx, mut applocker_rx) = saturation::channel("applocker", channel_capacity);
+    spawn_listener(
+        "applocker-exe",
+        AppLockerListener::executables(applocker_tx.clone()),
+        schedule("applocker-exe"),
+        restarts,
+        &channels,
+    );
+    spawn_listener(
+        "applocker-script",
+        AppLockerListener::scripts(applocker_tx),
+        schedule("applocker-script"),
+        restarts,
+        &channels,
+    );
+
+    let (remote_exec_tx, mut remote_exec_rx) = saturation::channel("remote-exec", channel_capacity);
+    let script_storage = match args.max_script_len {
+        Some(0) => ScriptStorage::Omit,
+        Some(max_len) => ScriptStorage::Truncate(max_len),
+        None => ScriptStorage::Full,
     };
+    spawn_listener(
+        "powershell",
+        RemoteExecutionListener::powershell(remote_exec_tx.clone())
+            .with_script_storage(script_storage),
+        schedule("powershell"),
+        restarts,
+        &channels,
+    );
+    spawn_listener(
+        "winrm",
+        RemoteExecutionListener::winrm(remote_exec_tx),
+        schedule("winrm"),
+        restarts,
+        &channels,
+    );
+
+    let (heartbeat_tx, mut heartbeat_rx) = saturation::channel("heartbeat", channel_capacity);
+    if let Some(secs) = args.heartbeat_secs {
+        spawn_listener(
+            "heartbeat",
+            HeartbeatListener::new(heartbeat_tx),
+            PollSchedule::new(Duration::from_secs(secs), Duration::ZERO, None),
+            restarts,
+            &channels,
+        );
+    }
+
+    if args.verbose {
+        let gauges = [
+            &logon_rx,
+            &logon_rx2,
+            &usb_rx,
+            &lock_rx,
+            &lockout_rx,
+            &defender_rx,
+            &applocker_rx,
+            &remote_exec_rx,
+            &heartbeat_rx,
+        ]
+        .map(|rx| rx.gauge());
+        tokio::spawn(report_saturation(gauges.to_vec(), Duration::from_secs(60)));
+    }
+
+    let alert_gate = args
+        .alerts_only
+        .then(|| Arc::new(AlertGate::new(alert_threshold(&args))));
+    let pipeline = build_pipeline(&args, raw_archive, alert_gate.clone())?;
+    let summary_interval = Duration::from_secs(args.summary_interval_mins.max(1) * 60);
+    let mut summaries =
+        tokio::time::interval_at(Instant::now() + summary_interval, summary_interval);
+    let mut summary_out = summary_writer(&args)?;
+
+    let mut batcher = Batcher::new(args.batch_size, Duration::from_millis(args.batch_delay_ms));
 
     loop {
-        select_all! {
-            [&mut logon_rx, &mut logon_rx2],
-            handle_event
+        let deadline = batcher.deadline();
+        let flush_at = deadline.unwrap_or_else(Instant::now);
+        let next_event = async {
+            select_all!([
+                &mut logon_rx,
+                &mut logon_rx2,
+                &mut usb_rx,
+                &mut lock_rx,
+                &mut lockout_rx,
+                &mut defender_rx,
+                &mut applocker_rx,
+                &mut remote_exec_rx,
+                &mut heartbeat_rx,
+                &mut stats_rx,
+                &mut panics_rx
+            ])
         };
+
+        let batch_ready = select! {
+            event = next_event => {
+                let Some(event) = pipeline.run(event).await else {
+                    continue;
+                };
+                batcher.push(event)
+            }
+            _ = sleep_until(flush_at), if deadline.is_some() => true,
+            _ = summaries.tick(), if alert_gate.is_some() => {
+                if let Some(gate) = &alert_gate
+                    && let Err(e) = gate.take_summary().write_json(&mut summary_out)
+                {
+                    eprintln!("Failed to write summary: {}", e);
+                }
+                false
+            }
+        };
+
+        if batch_ready && let Err(e) = sinks.emit_batch(&batcher.take()).await {
+            eprintln!("Failed to deliver events: {}", e);
+        }
     }
 }
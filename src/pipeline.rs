@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::listener::Event;
+use crate::sink::Sink;
+
+/// One step between the listeners and the sinks: an enricher that adds to events, or a filter
+/// that drops them.
+#[async_trait]
+pub trait Transform: Send + Sync {
+    /// A short name identifying the step in logs.
+    fn name(&self) -> &str;
+
+    /// Returns the event to pass on, or `None` to drop it.
+    async fn transform(&self, event: Event) -> Option<Event>;
+}
+
+/// Lets a transform stay reachable from outside the pipeline, e.g. to read state it gathers.
+#[async_trait]
+impl<T: Transform + ?Sized> Transform for Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    async fn transform(&self, event: Event) -> Option<Event> {
+        (**self).transform(event).await
+    }
+}
+
+/// Sends each event to a sink as it is at this point in the pipeline, then passes it on
+/// unchanged. This routes events to different sinks at different stages: a tap added first sees
+/// events as parsed, before anything is suppressed or enriched, while the sinks after the
+/// pipeline only see what survives it. For example, to archive raw events but alert on enriched
+/// ones, put a tap to the archive sink first and the alerting sinks after the pipeline.
+///
+/// A failing sink is reported and the event still continues down the pipeline.
+pub struct Tap<S> {
+    sink: S,
+}
+
+impl<S: Sink> Tap<S> {
+    pub fn new(sink: S) -> Self {
+        Self { sink }
+    }
+}
+
+#[async_trait]
+impl<S: Sink> Transform for Tap<S> {
+    fn name(&self) -> &str {
+        "tap"
+    }
+
+    async fn transform(&self, event: Event) -> Option<Event> {
+        if let Err(e) = self.sink.emit(&event).await {
+            eprintln!("Sink {} failed: {}", self.sink.name(), e);
+        }
+        Some(event)
+    }
+}
+
+/// Runs each event through a sequence of transforms, in the order they were added. An event
+/// dropped by one transform never reaches the rest.
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_transform(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// The name of each transform, in the order events pass through them.
+    pub fn names(&self) -> Vec<&str> {
+        self.transforms.iter().map(|t| t.name()).collect()
+    }
+
+    pub async fn run(&self, mut event: Event) -> Option<Event> {
+        for transform in &self.transforms {
+            event = transform.transform(event).await?;
+        }
+        Some(event)
+    }
+}
@@ -0,0 +1,15 @@
+#[cfg(windows)]
+use windows_sys::Win32::UI::Shell::IsUserAnAdmin;
+
+/// Whether the process is running elevated. The Security log can't be read otherwise.
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    // SAFETY: IsUserAnAdmin takes no arguments and only inspects the current process token.
+    unsafe { IsUserAnAdmin() != 0 }
+}
+
+/// Off Windows there's no Security log to be elevated for.
+#[cfg(not(windows))]
+pub fn is_elevated() -> bool {
+    false
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::listener::{Account, Event, EventDetails, LogonEvent};
+
+/// `FailureReason` of a failure against a locked-out account.
+pub(crate) const ACCOUNT_LOCKED_OUT: &str = "%%2307";
+
+/// Builds a per-user summary of logon activity from a batch of events, e.g. for a daily
+/// compliance report.
+#[derive(Debug, Clone, Default)]
+pub struct PerUserReport {
+    exclude_machine_accounts: bool,
+}
+
+impl PerUserReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leaves out machine accounts (`NAME$`), whose logons are mostly routine.
+    pub fn with_machine_accounts_excluded(mut self, exclude: bool) -> Self {
+        self.exclude_machine_accounts = exclude;
+        self
+    }
+
+    /// Groups the logon and lockout events in `events` by account. Other kinds of events are
+    /// ignored.
+    pub fn build(&self, events: &[Event]) -> Report {
+        let mut report = Report::default();
+        for event in events {
+            self.add(&mut report, event);
+        }
+        report
+    }
+
+    /// Adds one event to `report`, for building a report as events arrive.
+    pub fn add(&self, report: &mut Report, event: &Event) {
+        let account = match &event.details {
+            EventDetails::Login(login) => &login.target,
+            EventDetails::Lockout(lockout) => &lockout.account,
+            _ => return,
+        };
+        if self.exclude_machine_accounts && account.is_machine() {
+            return;
+        }
+
+        report.start = Some(
+            report
+                .start
+                .map_or(event.timestamp, |t| t.min(event.timestamp)),
+        );
+        report.end = Some(
+            report
+                .end
+                .map_or(event.timestamp, |t| t.max(event.timestamp)),
+        );
+        let key = match &event.details {
+            // 4740 logs no domain, so a lockout counts toward the account's entry under any domain.
+            EventDetails::Lockout(_) => report
+                .users
+                .iter()
+                .find(|(_, activity)| activity.account.user.eq_ignore_ascii_case(&account.user))
+                .map(|(key, _)| key.clone())
+                .unwrap_or_else(|| account.upn().to_lowercase()),
+            _ => account.upn().to_lowercase(),
+        };
+        let activity = report
+            .users
+            .entry(key)
+            .or_insert_with(|| UserActivity::new(account.clone(), event.timestamp));
+        match &event.details {
+            EventDetails::Login(login) => activity.record(login, event.timestamp),
+            EventDetails::Lockout(_) => activity.record_lockout(event.timestamp),
+            _ => {}
+        }
+    }
+}
+
+/// Logon activity over a period, keyed by each account's `user@domain`, lowercased since
+/// Windows account names aren't case-sensitive.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Report {
+    /// The earliest and latest event covered, or `None` for an empty report.
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub users: BTreeMap<String, UserActivity>,
+}
+
+/// One account's logon activity.
+#[derive(Debug, Clone, Serialize)]
+pub struct UserActivity {
+    pub account: Account,
+    pub logons: u32,
+    /// Failed attempts, counting each attempt of a collapsed failure.
+    pub failures: u32,
+    /// Times the account was locked out (4740).
+    pub lockouts: u32,
+    /// Failures against the account while it was locked out, counting each attempt of a
+    /// collapsed failure.
+    pub locked_out_failures: u32,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Every source address the account logged on or failed from.
+    pub sources: BTreeSet<String>,
+}
+
+impl UserActivity {
+    fn new(account: Account, at: DateTime<Utc>) -> Self {
+        Self {
+            account,
+            logons: 0,
+            failures: 0,
+            lockouts: 0,
+            locked_out_failures: 0,
+            first_seen: at,
+            last_seen: at,
+            sources: BTreeSet::new(),
+        }
+    }
+
+    fn record(&mut self, login: &LogonEvent, at: DateTime<Utc>) {
+        if login.success {
+            self.logons += 1;
+        } else {
+            let attempts = login.attempt_count.max(1);
+            self.failures += attempts;
+            if login.failure_reason.as_deref() == Some(ACCOUNT_LOCKED_OUT) {
+                self.locked_out_failures += attempts;
+            }
+        }
+        self.seen(at);
+        if login.source_addr().is_some() {
+            self.sources.insert(login.source_ip.clone());
+        }
+    }
+
+    fn record_lockout(&mut self, at: DateTime<Utc>) {
+        self.lockouts += 1;
+        self.seen(at);
+    }
+
+    fn seen(&mut self, at: DateTime<Utc>) {
+        self.first_seen = self.first_seen.min(at);
+        self.last_seen = self.last_seen.max(at);
+    }
+}
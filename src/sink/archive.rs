@@ -0,0 +1,237 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::sync::Mutex;
+
+use crate::errors::SentinelError;
+use crate::listener::Event;
+use crate::listener::schema::SCHEMA_VERSION;
+
+use super::Sink;
+
+const MAGIC: &[u8; 4] = b"HSHA";
+/// Bumped along with [`SCHEMA_VERSION`]: records are postcard, which encodes fields and variants
+/// by position, so even a schema change JSON readers absorb through `#[serde(default)]` breaks
+/// decoding older records.
+const VERSION: u8 = 4;
+/// The schema version of the events [`VERSION`] archives hold. Changing [`SCHEMA_VERSION`] fails
+/// the build until this is updated, as a reminder to bump [`VERSION`] with it.
+const ARCHIVED_SCHEMA_VERSION: u32 = 8;
+const _: () = assert!(
+    ARCHIVED_SCHEMA_VERSION == SCHEMA_VERSION,
+    "SCHEMA_VERSION changed: bump the archive VERSION and ARCHIVED_SCHEMA_VERSION"
+);
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// Records claiming to be larger than this are treated as corruption rather than allocated.
+const MAX_RECORD_LEN: u32 = 16 * 1024 * 1024;
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// How the records after an archive's header are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Compression {
+    #[default]
+    None,
+    /// A sequence of zstd frames, one per write.
+    Zstd,
+}
+
+impl Compression {
+    fn to_byte(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+fn header(compression: Compression) -> [u8; HEADER_LEN] {
+    let mut header = [0; HEADER_LEN];
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    header[MAGIC.len()] = VERSION;
+    header[MAGIC.len() + 1] = compression.to_byte();
+    header
+}
+
+fn parse_header(header: &[u8; HEADER_LEN]) -> Result<Compression, SentinelError> {
+    if &header[..MAGIC.len()] != MAGIC {
+        return Err(SentinelError::ArchiveError(
+            "not a Hosho archive".to_string(),
+        ));
+    }
+    if header[MAGIC.len()] != VERSION {
+        return Err(SentinelError::ArchiveError(format!(
+            "unsupported archive version {} (expected {}); replay it with the Hosho release that \
+             wrote it",
+            header[MAGIC.len()],
+            VERSION
+        )));
+    }
+    Compression::from_byte(header[MAGIC.len() + 1]).ok_or_else(|| {
+        SentinelError::ArchiveError(format!("unknown compression {}", header[MAGIC.len() + 1]))
+    })
+}
+
+/// Encodes `events` as length-prefixed postcard records, compressed as a single frame if asked.
+fn encode_records(events: &[Event], compression: Compression) -> Result<Vec<u8>, SentinelError> {
+    let mut records = Vec::new();
+    for event in events {
+        let record = postcard::to_stdvec(event)
+            .map_err(|e| SentinelError::ArchiveError(format!("failed to encode event: {}", e)))?;
+        records.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        records.extend_from_slice(&record);
+    }
+
+    match compression {
+        Compression::None => Ok(records),
+        Compression::Zstd => zstd::encode_all(records.as_slice(), ZSTD_LEVEL)
+            .map_err(|e| SentinelError::ArchiveError(format!("failed to compress: {}", e))),
+    }
+}
+
+/// Writes `events` to `writer` as a complete archive: a header followed by one record per event.
+pub fn write_archive(
+    mut writer: impl Write,
+    events: &[Event],
+    compression: Compression,
+) -> Result<(), SentinelError> {
+    let records = encode_records(events, compression)?;
+    writer
+        .write_all(&header(compression))
+        .and_then(|()| writer.write_all(&records))
+        .and_then(|()| writer.flush())
+        .map_err(|e| SentinelError::ArchiveError(e.to_string()))
+}
+
+/// Reads the length prefix of the next record, or `None` at the end of the archive. A prefix cut
+/// off by a torn final write also ends the archive.
+fn read_len(reader: &mut impl Read) -> io::Result<Option<u32>> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => Ok(Some(u32::from_le_bytes(len))),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads every event from an archive written by [`write_archive`] or [`ArchiveSink`].
+pub fn read_archive(mut reader: impl Read) -> Result<Vec<Event>, SentinelError> {
+    let error = |e: io::Error| SentinelError::ArchiveError(e.to_string());
+
+    let mut header = [0; HEADER_LEN];
+    reader.read_exact(&mut header).map_err(error)?;
+    let mut records: Box<dyn Read + '_> = match parse_header(&header)? {
+        Compression::None => Box::new(reader),
+        Compression::Zstd => Box::new(zstd::Decoder::new(reader).map_err(error)?),
+    };
+
+    let mut events = Vec::new();
+    let mut record = Vec::new();
+    while let Some(len) = read_len(&mut records).map_err(error)? {
+        if len > MAX_RECORD_LEN {
+            return Err(SentinelError::ArchiveError(format!(
+                "record of {} bytes exceeds the {} byte limit",
+                len, MAX_RECORD_LEN
+            )));
+        }
+        record.resize(len as usize, 0);
+        records.read_exact(&mut record).map_err(error)?;
+        let event = postcard::from_bytes(&record)
+            .map_err(|e| SentinelError::ArchiveError(format!("failed to decode event: {}", e)))?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Appends events to a binary archive, far smaller than NDJSON for long-term retention. Replay
+/// it with [`read_archive`]. With zstd, each batch is compressed as its own frame.
+pub struct ArchiveSink {
+    path: PathBuf,
+    compression: Compression,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl ArchiveSink {
+    /// Opens `path` for appending, creating it with a header if it doesn't exist. An existing
+    /// archive must be of this version and written with the same `compression`.
+    pub async fn open(
+        path: impl AsRef<Path>,
+        compression: Compression,
+    ) -> Result<Self, SentinelError> {
+        let path = path.as_ref().to_path_buf();
+        let error = |e: io::Error| SentinelError::SinkError(format!("{}: {}", path.display(), e));
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(error)?;
+
+        if file.metadata().await.map_err(error)?.len() == 0 {
+            file.write_all(&header(compression)).await.map_err(error)?;
+        } else {
+            let mut existing = [0; HEADER_LEN];
+            file.read_exact(&mut existing).await.map_err(error)?;
+            if parse_header(&existing)? != compression {
+                return Err(SentinelError::ConfigError(format!(
+                    "{} was written with different compression",
+                    path.display()
+                )));
+            }
+        }
+
+        Ok(Self {
+            path,
+            compression,
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    fn error(&self, e: io::Error) -> SentinelError {
+        SentinelError::SinkError(format!("{}: {}", self.path.display(), e))
+    }
+}
+
+#[async_trait]
+impl Sink for ArchiveSink {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        self.emit_batch(std::slice::from_ref(event)).await
+    }
+
+    async fn emit_batch(&self, events: &[Event]) -> Result<(), SentinelError> {
+        let records = encode_records(events, self.compression)?;
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(&records)
+            .await
+            .map_err(|e| self.error(e))?;
+        writer.flush().await.map_err(|e| self.error(e))
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        self.writer
+            .lock()
+            .await
+            .flush()
+            .await
+            .map_err(|e| self.error(e))
+    }
+}
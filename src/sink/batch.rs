@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::listener::Event;
+
+/// Accumulates events until either `max_events` are pending or the oldest pending event has
+/// waited `max_delay`, whichever comes first, so sinks can write bursts with a single call.
+#[derive(Debug)]
+pub struct Batcher {
+    max_events: usize,
+    max_delay: Duration,
+    pending: Vec<Event>,
+    oldest: Option<Instant>,
+}
+
+impl Batcher {
+    pub fn new(max_events: usize, max_delay: Duration) -> Self {
+        Self {
+            max_events: max_events.max(1),
+            max_delay,
+            pending: Vec::new(),
+            oldest: None,
+        }
+    }
+
+    /// Queues `event`, returning whether the batch is now full and should be taken.
+    pub fn push(&mut self, event: Event) -> bool {
+        self.oldest.get_or_insert_with(Instant::now);
+        self.pending.push(event);
+        self.pending.len() >= self.max_events
+    }
+
+    /// When the pending batch must be taken, if anything is pending.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.oldest.map(|oldest| oldest + self.max_delay)
+    }
+
+    pub fn take(&mut self) -> Vec<Event> {
+        self.oldest = None;
+        std::mem::take(&mut self.pending)
+    }
+}
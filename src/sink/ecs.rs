@@ -0,0 +1,210 @@
+use serde_json::{Map, Value, json};
+
+use crate::listener::{Account, Event, EventDetails};
+
+use super::format_event;
+
+const ECS_VERSION: &str = "8.11.0";
+
+/// Maps an event to an Elastic Common Schema document, using the field names Winlogbeat would
+/// produce so events can be indexed without an ingest pipeline.
+pub fn to_ecs(event: &Event) -> Value {
+    let mut doc = json!({
+        "@timestamp": event.timestamp.to_rfc3339(),
+        "ecs": { "version": ECS_VERSION },
+        "event": {
+            "module": "hosho",
+            "kind": "event",
+            "severity": event.severity as u8,
+        },
+        "log": { "level": event.severity.to_string().to_lowercase() },
+    });
+
+    if let Some(collected_at) = event.collected_at {
+        doc["event"]["ingested"] = json!(collected_at.to_rfc3339());
+    }
+    if let Some(computer) = &event.computer {
+        doc["host"] = json!({ "name": computer });
+    }
+    // Prefer Windows' localized description, falling back to our own.
+    doc["message"] = match event.rendering.as_ref().and_then(|r| r.message.as_ref()) {
+        Some(message) => json!(message),
+        None => json!(format_event(event)),
+    };
+    if let Some(record_id) = event.record_id() {
+        doc["winlog"]["record_id"] = json!(record_id);
+    }
+    if let Some(risk_score) = event.risk_score {
+        doc["event"]["risk_score"] = json!(risk_score);
+    }
+    if !event.tags.is_empty() {
+        doc["tags"] = json!(event.tags);
+    }
+
+    if let Some(event_id) = event.event_id() {
+        set_event_id(&mut doc, event_id);
+    }
+
+    match &event.details {
+        EventDetails::Login(login_event) => {
+            let (action, outcome) = if login_event.success {
+                ("logged-in", "success")
+            } else {
+                ("logon-failed", "failure")
+            };
+            set_event(&mut doc, action, &["authentication"], &["start"]);
+            doc["event"]["outcome"] = json!(outcome);
+            doc["winlog"]["logon"] = json!({ "type": login_event.variant.to_string() });
+            doc["user"] = user(&login_event.target);
+            if let Some(ip) = login_event.source_addr() {
+                doc["source"] = json!({ "ip": ip.to_string() });
+            }
+            if let Some(server) = &login_event.target_server {
+                doc["destination"] = json!({ "address": server });
+            }
+            if let Some(reason) = &login_event.failure_reason_text {
+                doc["event"]["reason"] = json!(reason);
+            }
+            if let Some(sub_status) = login_event.sub_status {
+                doc["winlog"]["event_data"]["SubStatus"] = json!(format!("{:#x}", sub_status));
+            }
+            if login_event.attempt_count > 1 {
+                doc["event"]["count"] = json!(login_event.attempt_count);
+            }
+            if let Some(process) = &login_event.creator_process {
+                doc["process"]["executable"] = json!(process);
+            }
+            if let Some(pid) = login_event.creator_process_id {
+                doc["process"]["pid"] = json!(pid);
+            }
+        }
+        EventDetails::UsbDevice(usb_event) => {
+            set_event(
+                &mut doc,
+                &format!("usb-device-{}", usb_event.action.to_string().to_lowercase()),
+                &["host"],
+                &["change"],
+            );
+            doc["device"] = json!({ "id": usb_event.device_id });
+            if let Some(name) = &usb_event.friendly_name {
+                doc["device"]["model"] = json!({ "name": name });
+            }
+        }
+        EventDetails::ScreenLock(lock_event) => {
+            let action = if lock_event.locked {
+                "workstation-locked"
+            } else {
+                "workstation-unlocked"
+            };
+            set_event(&mut doc, action, &["session"], &["change"]);
+            doc["user"] = json!({ "name": lock_event.username });
+        }
+        EventDetails::ThreatDetected(threat_event) => {
+            set_event(&mut doc, "malware-detected", &["malware"], &["info"]);
+            doc["threat"] = json!({ "indicator": { "description": threat_event.threat_name } });
+            if let Some(path) = &threat_event.path {
+                doc["file"] = json!({ "path": path });
+            }
+        }
+        EventDetails::AppBlocked(blocked_event) => {
+            set_event(&mut doc, "application-blocked", &["process"], &["denied"]);
+            doc["file"] = json!({ "path": blocked_event.file_path });
+            if let Some(sid) = &blocked_event.user_sid {
+                doc["user"] = json!({ "id": sid });
+            }
+            if let Some(rule_name) = &blocked_event.rule_name {
+                doc["rule"] = json!({ "name": rule_name });
+            }
+        }
+        EventDetails::RemoteExecution(exec_event) => {
+            set_event(&mut doc, "remote-execution", &["process"], &["start"]);
+            if let Some(host) = &exec_event.host {
+                doc["source"] = json!({ "address": host });
+            }
+            if let Some(uri) = &exec_event.resource_uri {
+                doc["url"] = json!({ "original": uri });
+            }
+            if let Some(script) = &exec_event.script {
+                doc["powershell"] = json!({ "file": { "script_block_text": script } });
+            }
+        }
+        EventDetails::Lockout(lockout_event) => {
+            set_event(&mut doc, "account-locked-out", &["iam"], &["change"]);
+            doc["user"] = user(&lockout_event.account);
+            if let Some(computer) = &lockout_event.caller_computer {
+                doc["source"] = json!({ "domain": computer });
+            }
+            if !lockout_event.causes.is_empty() {
+                doc["event"]["count"] = json!(lockout_event.causes.len());
+            }
+        }
+        EventDetails::Heartbeat { seq } => {
+            set_event(&mut doc, "heartbeat", &[], &["info"]);
+            doc["event"]["kind"] = json!("metric");
+            doc["event"]["sequence"] = json!(seq);
+        }
+        EventDetails::QueryStats {
+            channel,
+            duration,
+            event_count,
+            parse_errors,
+        } => {
+            set_event(&mut doc, "query-executed", &[], &["info"]);
+            doc["event"]["kind"] = json!("metric");
+            // ECS durations are in nanoseconds.
+            doc["event"]["duration"] = json!(duration.as_nanos() as u64);
+            doc["winlog"]["channel"] = json!(channel);
+            doc["hosho"]["query"] = json!({
+                "event_count": event_count,
+                "parse_errors": parse_errors,
+            });
+        }
+        EventDetails::SelfTest => {
+            set_event(&mut doc, "self-test", &[], &["info"]);
+        }
+        EventDetails::ListenerPanicked {
+            listener,
+            message,
+            restarts,
+            restarting,
+        } => {
+            let kind = if *restarting { "start" } else { "end" };
+            set_event(
+                &mut doc,
+                "listener-panicked",
+                &["process"],
+                &["error", kind],
+            );
+            doc["error"] = json!({ "message": message });
+            doc["hosho"]["listener"] = json!({ "name": listener, "restarts": restarts });
+        }
+    }
+
+    doc
+}
+
+fn set_event(doc: &mut Value, action: &str, category: &[&str], kind: &[&str]) {
+    doc["event"]["action"] = json!(action);
+    if !category.is_empty() {
+        doc["event"]["category"] = json!(category);
+    }
+    doc["event"]["type"] = json!(kind);
+}
+
+fn set_event_id(doc: &mut Value, event_id: u32) {
+    doc["event"]["code"] = json!(event_id.to_string());
+    doc["winlog"]["event_id"] = json!(event_id.to_string());
+}
+
+/// Maps an account to ECS `user.name`, `user.domain`, and `user.id`.
+fn user(account: &Account) -> Value {
+    let mut user = Map::new();
+    user.insert("name".to_string(), json!(account.user));
+    if let Some(domain) = &account.domain {
+        user.insert("domain".to_string(), json!(domain));
+    }
+    if let Some(sid) = &account.sid {
+        user.insert("id".to_string(), json!(sid.as_str()));
+    }
+    Value::Object(user)
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use windows_sys::Win32::System::Diagnostics::Etw::{
+    EventRegister, EventUnregister, EventWriteString, REGHANDLE,
+};
+use windows_sys::core::GUID;
+
+use crate::enrich::Severity;
+use crate::errors::SentinelError;
+use crate::listener::Event;
+
+use super::{DisplayTz, OutputFormat, Sink};
+
+/// The provider Hosho registers unless told otherwise. Consumers enable it by this GUID, e.g.
+/// `logman start hosho -p {7d3a5c1e-9b42-4f6e-8a1d-3c5e7f9b2a41} -ets`.
+pub const DEFAULT_PROVIDER: GUID = GUID::from_u128(0x7d3a5c1e_9b42_4f6e_8a1d_3c5e7f9b2a41);
+
+// TRACE_LEVEL_* values from evntrace.h.
+const LEVEL_CRITICAL: u8 = 1;
+const LEVEL_ERROR: u8 = 2;
+const LEVEL_WARNING: u8 = 3;
+const LEVEL_INFORMATION: u8 = 4;
+
+/// Writes each event to an ETW provider as a string payload, so agents that already consume ETW
+/// can pick up Hosho's events without a file or network hop. Events are rendered as ECS JSON by
+/// default, and their severity becomes the ETW level.
+pub struct EtwSink {
+    handle: REGHANDLE,
+    format: OutputFormat,
+}
+
+impl EtwSink {
+    /// Registers [`DEFAULT_PROVIDER`].
+    pub fn new() -> Result<Self, SentinelError> {
+        Self::register(DEFAULT_PROVIDER)
+    }
+
+    pub fn register(provider: GUID) -> Result<Self, SentinelError> {
+        let mut handle: REGHANDLE = 0;
+        // SAFETY: the GUID and out-pointer are valid for the duration of the call; the handle is
+        // released in Drop.
+        let status = unsafe { EventRegister(&provider, None, std::ptr::null(), &mut handle) };
+        if status != 0 {
+            return Err(SentinelError::SinkError(format!(
+                "etw: registering provider failed with error {}",
+                status
+            )));
+        }
+        Ok(Self {
+            handle,
+            format: OutputFormat::Ecs,
+        })
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+fn level(severity: Severity) -> u8 {
+    match severity {
+        Severity::Critical => LEVEL_CRITICAL,
+        Severity::High => LEVEL_ERROR,
+        Severity::Medium => LEVEL_WARNING,
+        Severity::Low | Severity::Info => LEVEL_INFORMATION,
+    }
+}
+
+#[async_trait]
+impl Sink for EtwSink {
+    fn name(&self) -> &str {
+        "etw"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        let payload: Vec<u16> = self
+            .format
+            .render(event, DisplayTz::default())
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        // SAFETY: the payload is NUL-terminated and outlives the call. Writing when no session has
+        // enabled the provider is a cheap no-op.
+        let status =
+            unsafe { EventWriteString(self.handle, level(event.severity), 0, payload.as_ptr()) };
+        if status != 0 {
+            return Err(SentinelError::SinkError(format!(
+                "etw: write failed with error {}",
+                status
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EtwSink {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from EventRegister and is unregistered exactly once.
+        unsafe { EventUnregister(self.handle) };
+    }
+}
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE,
+    REPORT_EVENT_TYPE, RegisterEventSourceW, ReportEventW,
+};
+use windows_sys::Win32::System::Registry::{
+    HKEY, HKEY_LOCAL_MACHINE, KEY_SET_VALUE, REG_DWORD, REG_EXPAND_SZ, REG_OPTION_NON_VOLATILE,
+    RegCloseKey, RegCreateKeyExW, RegSetValueExW,
+};
+
+use crate::enrich::Severity;
+use crate::errors::SentinelError;
+use crate::listener::{Event, EventDetails};
+
+use super::{DisplayTz, OutputFormat, Sink};
+
+/// The Application log source Hosho writes as unless told otherwise.
+pub const DEFAULT_SOURCE: &str = "Hosho";
+
+/// The .NET Framework's message file, which renders every event ID as just its message. Using it
+/// spares Hosho shipping a compiled message table, and it's what .NET's own `EventLog` sources
+/// register.
+const MESSAGE_FILE: &str =
+    r"%SystemRoot%\Microsoft.NET\Framework64\v4.0.30319\EventLogMessages.dll";
+
+/// The longest string `ReportEventW` accepts, in UTF-16 units.
+const MAX_MESSAGE_LEN: usize = 31_839;
+
+// Event IDs, one per kind of alert, so event log queries and forwarding rules can select them.
+const EVENT_LOGON: u32 = 1000;
+const EVENT_REPEATED_FAILURES: u32 = 1001;
+const EVENT_LOCKOUT: u32 = 1002;
+const EVENT_THREAT: u32 = 1003;
+const EVENT_APP_BLOCKED: u32 = 1004;
+const EVENT_REMOTE_EXECUTION: u32 = 1005;
+const EVENT_OTHER: u32 = 1099;
+
+/// Writes events to the Application log under a source of their own, so tools that watch the
+/// event log see Hosho's detections alongside native events. Each event is written as text by
+/// default, with an event ID for what it is (see [`event_id`]), its severity as the category
+/// (1 for Info through 5 for Critical), and an entry type of Error for High and Critical events,
+/// Warning for Medium, and Information otherwise.
+///
+/// Meant for alerts rather than every event: register it with `MultiSink::with_sink_where` and
+/// an `AlertThreshold`.
+pub struct EventLogSink {
+    handle: HANDLE,
+    format: OutputFormat,
+}
+
+// SAFETY: an event source handle may be used from any thread, and ReportEventW is thread-safe.
+unsafe impl Send for EventLogSink {}
+unsafe impl Sync for EventLogSink {}
+
+impl EventLogSink {
+    /// Writes as [`DEFAULT_SOURCE`].
+    pub fn new() -> Result<Self, SentinelError> {
+        Self::register(DEFAULT_SOURCE)
+    }
+
+    /// Writes as `source`, first registering it in the Application log if that's allowed. Without
+    /// Administrator privileges an unregistered source still works, but Event Viewer prefixes
+    /// each message with a note that its description can't be found.
+    pub fn register(source: &str) -> Result<Self, SentinelError> {
+        if let Err(e) = install_source(source) {
+            eprintln!("Can't register event log source {}: {}", source, e);
+        }
+
+        let name = wide(source);
+        // SAFETY: a null server is the local machine, and `name` is NUL-terminated and outlives
+        // the call. The handle is released in Drop.
+        let handle = unsafe { RegisterEventSourceW(std::ptr::null(), name.as_ptr()) };
+        if handle.is_null() {
+            return Err(SentinelError::SinkError(format!(
+                "eventlog: opening source {} failed: {}",
+                source,
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(Self {
+            handle,
+            format: OutputFormat::Text,
+        })
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+/// Adds `source` under the Application log's registry key, pointing at [`MESSAGE_FILE`]. Needs
+/// Administrator privileges; registering a source that already exists just rewrites its values.
+fn install_source(source: &str) -> std::io::Result<()> {
+    let subkey = wide(&format!(
+        r"SYSTEM\CurrentControlSet\Services\EventLog\Application\{}",
+        source
+    ));
+    let mut key: HKEY = std::ptr::null_mut();
+    // SAFETY: `subkey` is NUL-terminated and `key` is valid for writes; the key is closed below.
+    let status = unsafe {
+        RegCreateKeyExW(
+            HKEY_LOCAL_MACHINE,
+            subkey.as_ptr(),
+            0,
+            std::ptr::null(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            std::ptr::null(),
+            &mut key,
+            std::ptr::null_mut(),
+        )
+    };
+    if status != 0 {
+        return Err(std::io::Error::from_raw_os_error(status as i32));
+    }
+
+    let message_file: Vec<u8> = wide(MESSAGE_FILE)
+        .iter()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let types_supported =
+        u32::from(EVENTLOG_ERROR_TYPE | EVENTLOG_WARNING_TYPE | EVENTLOG_INFORMATION_TYPE)
+            .to_le_bytes();
+    let values = [
+        ("EventMessageFile", REG_EXPAND_SZ, message_file.as_slice()),
+        ("TypesSupported", REG_DWORD, types_supported.as_slice()),
+    ];
+
+    let mut result = Ok(());
+    for (name, kind, data) in values {
+        let name = wide(name);
+        // SAFETY: `key` is open, `name` is NUL-terminated, and `data` holds `data.len()` bytes.
+        let status = unsafe {
+            RegSetValueExW(
+                key,
+                name.as_ptr(),
+                0,
+                kind,
+                data.as_ptr(),
+                data.len() as u32,
+            )
+        };
+        if status != 0 {
+            result = Err(std::io::Error::from_raw_os_error(status as i32));
+            break;
+        }
+    }
+    // SAFETY: `key` came from RegCreateKeyExW and is closed exactly once.
+    unsafe { RegCloseKey(key) };
+    result
+}
+
+/// The event ID an event is written with:
+///
+/// | ID   | Event                                         |
+/// |------|-----------------------------------------------|
+/// | 1000 | A logon                                       |
+/// | 1001 | Repeated failed logons, collapsed into one    |
+/// | 1002 | An account lockout                            |
+/// | 1003 | Malware detected by Defender                  |
+/// | 1004 | An application blocked by AppLocker           |
+/// | 1005 | Remote execution via PowerShell or WinRM      |
+/// | 1099 | Anything else                                 |
+pub fn event_id(event: &Event) -> u32 {
+    match &event.details {
+        EventDetails::Login(login) if login.attempt_count > 1 => EVENT_REPEATED_FAILURES,
+        EventDetails::Login(_) => EVENT_LOGON,
+        EventDetails::Lockout(_) => EVENT_LOCKOUT,
+        EventDetails::ThreatDetected(_) => EVENT_THREAT,
+        EventDetails::AppBlocked(_) => EVENT_APP_BLOCKED,
+        EventDetails::RemoteExecution(_) => EVENT_REMOTE_EXECUTION,
+        EventDetails::UsbDevice(_)
+        | EventDetails::ScreenLock(_)
+        | EventDetails::Heartbeat { .. }
+        | EventDetails::QueryStats { .. }
+        | EventDetails::SelfTest
+        | EventDetails::ListenerPanicked { .. } => EVENT_OTHER,
+    }
+}
+
+fn entry_type(severity: Severity) -> REPORT_EVENT_TYPE {
+    match severity {
+        Severity::Critical | Severity::High => EVENTLOG_ERROR_TYPE,
+        Severity::Medium => EVENTLOG_WARNING_TYPE,
+        Severity::Low | Severity::Info => EVENTLOG_INFORMATION_TYPE,
+    }
+}
+
+fn category(severity: Severity) -> u16 {
+    severity as u16 + 1
+}
+
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+#[async_trait]
+impl Sink for EventLogSink {
+    fn name(&self) -> &str {
+        "eventlog"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        let mut message: Vec<u16> = self
+            .format
+            .render(event, DisplayTz::default())
+            .encode_utf16()
+            .take(MAX_MESSAGE_LEN)
+            .collect();
+        message.push(0);
+        let strings = [message.as_ptr()];
+        // SAFETY: the handle is open, and the one string is NUL-terminated and outlives the call.
+        let ok = unsafe {
+            ReportEventW(
+                self.handle,
+                entry_type(event.severity),
+                category(event.severity),
+                event_id(event),
+                std::ptr::null_mut(),
+                strings.len() as u16,
+                0,
+                strings.as_ptr(),
+                std::ptr::null(),
+            )
+        };
+        if ok == 0 {
+            return Err(SentinelError::SinkError(format!(
+                "eventlog: write failed: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for EventLogSink {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from RegisterEventSourceW and is deregistered exactly once.
+        unsafe { DeregisterEventSource(self.handle) };
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::Mutex;
+
+use crate::errors::SentinelError;
+use crate::listener::Event;
+
+use super::projection::Projection;
+use super::{DisplayTz, OutputFormat, Sink};
+
+/// Appends each event as a line to a file, human-readable unless another format is chosen.
+pub struct FileSink {
+    path: PathBuf,
+    format: OutputFormat,
+    display_tz: DisplayTz,
+    projection: Projection,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl FileSink {
+    /// Opens `path` for appending, creating it if it doesn't exist.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| SentinelError::SinkError(format!("{}: {}", path.display(), e)))?;
+
+        Ok(Self {
+            path,
+            format: OutputFormat::default(),
+            display_tz: DisplayTz::default(),
+            projection: Projection::default(),
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.display_tz = display_tz;
+        self
+    }
+
+    /// Keeps only the fields `projection` allows in JSON output.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    fn error(&self, e: std::io::Error) -> SentinelError {
+        SentinelError::SinkError(format!("{}: {}", self.path.display(), e))
+    }
+}
+
+#[async_trait]
+impl Sink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        let line = format!(
+            "{}\n",
+            self.format
+                .render_projected(event, self.display_tz, &self.projection)
+        );
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| self.error(e))?;
+        writer.flush().await.map_err(|e| self.error(e))
+    }
+
+    async fn emit_batch(&self, events: &[Event]) -> Result<(), SentinelError> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(
+                &self
+                    .format
+                    .render_projected(event, self.display_tz, &self.projection),
+            );
+            lines.push('\n');
+        }
+
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(lines.as_bytes())
+            .await
+            .map_err(|e| self.error(e))?;
+        writer.flush().await.map_err(|e| self.error(e))
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        self.writer
+            .lock()
+            .await
+            .flush()
+            .await
+            .map_err(|e| self.error(e))
+    }
+}
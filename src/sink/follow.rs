@@ -0,0 +1,59 @@
+use std::io::IsTerminal;
+
+use owo_colors::OwoColorize;
+
+use crate::enrich::BusinessHours;
+use crate::listener::{Event, EventDetails};
+
+use super::{DisplayTz, format_event_in};
+
+/// Renders events for `--follow`: the usual line, colored red for failed logons, yellow for
+/// successful logons outside business hours, and green for other successful logons.
+#[derive(Debug, Clone)]
+pub struct FollowPrinter {
+    color: bool,
+    business_hours: BusinessHours,
+    display_tz: DisplayTz,
+}
+
+impl FollowPrinter {
+    pub fn new(color: bool) -> Self {
+        Self {
+            color,
+            business_hours: BusinessHours::default(),
+            display_tz: DisplayTz::default(),
+        }
+    }
+
+    /// Shows timestamps, and judges business hours, in `display_tz` instead of local time.
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.display_tz = display_tz;
+        self.business_hours = self.business_hours.with_display_tz(display_tz);
+        self
+    }
+
+    /// Colors output only when stdout is a terminal and `NO_COLOR` isn't set.
+    pub fn from_env() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Self::new(!no_color && std::io::stdout().is_terminal())
+    }
+
+    pub fn render(&self, event: &Event) -> String {
+        let line = format_event_in(event, self.display_tz);
+        if !self.color {
+            return line;
+        }
+
+        let EventDetails::Login(login_event) = &event.details else {
+            return line;
+        };
+
+        if !login_event.success {
+            line.red().to_string()
+        } else if !self.business_hours.contains(event.timestamp) {
+            line.yellow().to_string()
+        } else {
+            line.green().to_string()
+        }
+    }
+}
@@ -0,0 +1,421 @@
+pub mod archive;
+pub mod batch;
+pub mod ecs;
+#[cfg(windows)]
+pub mod etw;
+#[cfg(windows)]
+pub mod eventlog;
+pub mod file;
+pub mod follow;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod projection;
+pub mod redact;
+pub mod spec;
+pub mod stdout;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+
+use crate::enrich::Severity;
+use crate::errors::SentinelError;
+use crate::listener::{Event, EventDetails, EventKind};
+use projection::Projection;
+
+/// A destination for events. Sinks are driven uniformly by [`MultiSink`], so each only needs to
+/// know how to write a single event.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// A short name identifying the sink in logs and self-test output.
+    fn name(&self) -> &str;
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError>;
+
+    /// Emits several events at once. Sinks with a cheaper bulk path (one write, one request, one
+    /// transaction) should override this; the default emits each event in turn, carrying on past
+    /// any that fail and reporting how many did.
+    async fn emit_batch(&self, events: &[Event]) -> Result<(), SentinelError> {
+        let mut failed = 0;
+        let mut first_error = None;
+        for event in events {
+            if let Err(e) = self.emit(event).await {
+                failed += 1;
+                first_error.get_or_insert(e);
+            }
+        }
+
+        match first_error {
+            None => Ok(()),
+            Some(e) => Err(SentinelError::SinkError(format!(
+                "{}: {} of {} events failed, first: {}",
+                self.name(),
+                failed,
+                events.len(),
+                e
+            ))),
+        }
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), SentinelError> {
+        self.flush().await
+    }
+}
+
+/// Which events a sink registered with a [`MultiSink`] receives.
+type Interest = Box<dyn Fn(&Event) -> bool + Send + Sync>;
+
+/// Fans each event out to several sinks. Each sink can be registered with an interest filter, so
+/// it only receives the events it cares about; self-test events reach every sink regardless. A
+/// failing sink is reported but never prevents the remaining sinks from receiving the event.
+#[derive(Default)]
+pub struct MultiSink {
+    sinks: Vec<(Box<dyn Sink>, Option<Interest>)>,
+}
+
+impl MultiSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sink that receives every event.
+    pub fn with_sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sinks.push((Box::new(sink), None));
+        self
+    }
+
+    /// Adds a sink that only receives events of the given `kinds`.
+    pub fn with_sink_for(self, sink: impl Sink + 'static, kinds: &[EventKind]) -> Self {
+        let kinds = kinds.to_vec();
+        self.with_sink_where(sink, move |event| kinds.contains(&event.kind()))
+    }
+
+    /// Adds a sink that only receives events matching `interest`, e.g. lockouts and collapsed
+    /// brute force runs for a notification sink.
+    pub fn with_sink_where(
+        mut self,
+        sink: impl Sink + 'static,
+        interest: impl Fn(&Event) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.sinks.push((Box::new(sink), Some(Box::new(interest))));
+        self
+    }
+
+    /// The name of each sink, in the order events reach them.
+    pub fn names(&self) -> Vec<&str> {
+        self.sinks.iter().map(|(sink, _)| sink.name()).collect()
+    }
+
+    /// Emits `event` to every sink interested in it, returning each sink's name alongside its
+    /// result.
+    pub async fn emit_each(&self, event: &Event) -> Vec<(&str, Result<(), SentinelError>)> {
+        let mut results = Vec::with_capacity(self.sinks.len());
+        for (sink, interest) in &self.sinks {
+            if wants(interest.as_ref(), event) {
+                results.push((sink.name(), sink.emit(event).await));
+            }
+        }
+        results
+    }
+}
+
+fn wants(interest: Option<&Interest>, event: &Event) -> bool {
+    matches!(event.details, EventDetails::SelfTest) || interest.is_none_or(|wants| wants(event))
+}
+
+/// Collapses per-sink results, logging each failure, into a single error if any sink failed.
+fn summarize(results: Vec<(&str, Result<(), SentinelError>)>) -> Result<(), SentinelError> {
+    let total = results.len();
+    let mut failed = 0;
+    for (name, result) in results {
+        if let Err(e) = result {
+            eprintln!("Sink {} failed: {}", name, e);
+            failed += 1;
+        }
+    }
+
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(SentinelError::SinkError(format!(
+            "{} of {} sinks failed",
+            failed, total
+        )))
+    }
+}
+
+#[async_trait]
+impl Sink for MultiSink {
+    fn name(&self) -> &str {
+        "multi"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        summarize(self.emit_each(event).await)
+    }
+
+    async fn emit_batch(&self, events: &[Event]) -> Result<(), SentinelError> {
+        let mut results = Vec::with_capacity(self.sinks.len());
+        for (sink, interest) in &self.sinks {
+            let result = match interest {
+                None => sink.emit_batch(events).await,
+                Some(_) => {
+                    let wanted: Vec<_> = events
+                        .iter()
+                        .filter(|event| wants(interest.as_ref(), event))
+                        .cloned()
+                        .collect();
+                    if wanted.is_empty() {
+                        continue;
+                    }
+                    sink.emit_batch(&wanted).await
+                }
+            };
+            results.push((sink.name(), result));
+        }
+        summarize(results)
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        let mut results = Vec::with_capacity(self.sinks.len());
+        for (sink, _) in &self.sinks {
+            results.push((sink.name(), sink.flush().await));
+        }
+        summarize(results)
+    }
+
+    async fn close(&self) -> Result<(), SentinelError> {
+        let mut results = Vec::with_capacity(self.sinks.len());
+        for (sink, _) in &self.sinks {
+            results.push((sink.name(), sink.close().await));
+        }
+        summarize(results)
+    }
+}
+
+/// How line-oriented sinks render each event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// A human-readable sentence.
+    #[default]
+    Text,
+    /// One Elastic Common Schema JSON document per line.
+    Ecs,
+    /// One JSON object per line in Hosho's own event structure, stamped with its
+    /// `schema_version`.
+    Json,
+}
+
+impl OutputFormat {
+    /// Renders `event`, with human-readable timestamps in `display_tz`. ECS timestamps are
+    /// always UTC.
+    pub fn render(self, event: &Event, display_tz: DisplayTz) -> String {
+        self.render_projected(event, display_tz, &Projection::default())
+    }
+
+    /// Like [`render`](Self::render), but JSON formats only keep the fields `projection` allows.
+    /// Text is unaffected.
+    pub fn render_projected(
+        self,
+        event: &Event,
+        display_tz: DisplayTz,
+        projection: &Projection,
+    ) -> String {
+        match self {
+            OutputFormat::Text => format_event_in(event, display_tz),
+            OutputFormat::Ecs => projection.apply(ecs::to_ecs(event)).to_string(),
+            // Going through a `Value` reorders the keys, so only when something is dropped.
+            OutputFormat::Json if projection.is_empty() => {
+                serde_json::to_string(event).expect("Event fields always serialize")
+            }
+            OutputFormat::Json => projection
+                .apply(serde_json::to_value(event).expect("Event fields always serialize"))
+                .to_string(),
+        }
+    }
+}
+
+/// The time zone human-readable output is shown in: the machine's own, or an IANA zone such as
+/// `Europe/Berlin`. Parses from `local` or the zone name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayTz {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl DisplayTz {
+    pub fn format(self, timestamp: DateTime<Utc>, format: &str) -> String {
+        match self {
+            DisplayTz::Local => timestamp
+                .with_timezone(&chrono::Local)
+                .format(format)
+                .to_string(),
+            DisplayTz::Named(tz) => timestamp.with_timezone(&tz).format(format).to_string(),
+        }
+    }
+
+    /// The hour of the day `timestamp` falls in, in this zone.
+    pub fn hour(self, timestamp: DateTime<Utc>) -> u32 {
+        match self {
+            DisplayTz::Local => timestamp.with_timezone(&chrono::Local).hour(),
+            DisplayTz::Named(tz) => timestamp.with_timezone(&tz).hour(),
+        }
+    }
+}
+
+impl FromStr for DisplayTz {
+    type Err = SentinelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(DisplayTz::Local);
+        }
+        s.parse().map(DisplayTz::Named).map_err(|_| {
+            SentinelError::ConfigError(format!(
+                "unknown time zone {:?}, expected \"local\" or an IANA name such as Europe/Berlin",
+                s
+            ))
+        })
+    }
+}
+
+/// Renders an event as a single human-readable line in the local time zone, prefixed with its
+/// severity unless that's `Info`.
+pub fn format_event(event: &Event) -> String {
+    format_event_in(event, DisplayTz::Local)
+}
+
+/// Like [`format_event`], with the timestamp shown in `display_tz`.
+pub fn format_event_in(event: &Event, display_tz: DisplayTz) -> String {
+    match event.severity {
+        Severity::Info => describe_event(event, display_tz),
+        severity => format!("[{}] {}", severity, describe_event(event, display_tz)),
+    }
+}
+
+fn describe_event(event: &Event, display_tz: DisplayTz) -> String {
+    let timestamp = display_tz.format(event.timestamp, "%A, %B %d, %Y at %I:%M:%S %p");
+
+    match &event.details {
+        EventDetails::Login(login_event) if login_event.attempt_count > 1 => format!(
+            r#"Event: {} Failed Logins for {} ({}) starting {} from {}"#,
+            login_event.attempt_count,
+            login_event.target,
+            login_event.variant,
+            timestamp,
+            login_event.source_ip
+        ),
+        EventDetails::Login(login_event) if login_event.success => format!(
+            r#"Event: Successful Login for {} ({}) on {} from {}"#,
+            login_event.target, login_event.variant, timestamp, login_event.source_ip
+        ),
+        EventDetails::Login(login_event) => format!(
+            r#"Event: Failed Login for {} ({}) on {} from {}"#,
+            login_event.target, login_event.variant, timestamp, login_event.source_ip
+        ),
+        EventDetails::UsbDevice(usb_event) => format!(
+            "Event: USB device {} ({}) {} on {}",
+            usb_event
+                .friendly_name
+                .as_deref()
+                .unwrap_or("<unnamed device>"),
+            usb_event.device_id,
+            usb_event.action,
+            timestamp
+        ),
+        EventDetails::ScreenLock(lock_event) => format!(
+            "Event: Workstation {} by {} on {}",
+            if lock_event.locked {
+                "locked"
+            } else {
+                "unlocked"
+            },
+            lock_event.username,
+            timestamp
+        ),
+        EventDetails::ThreatDetected(threat_event) => match &threat_event.action {
+            Some(action) => format!(
+                "Event: Defender took action '{}' against {} ({}) on {}",
+                action,
+                threat_event.threat_name,
+                threat_event.path.as_deref().unwrap_or("unknown path"),
+                timestamp
+            ),
+            None => format!(
+                "Event: Defender detected {} ({}) on {}",
+                threat_event.threat_name,
+                threat_event.path.as_deref().unwrap_or("unknown path"),
+                timestamp
+            ),
+        },
+        EventDetails::AppBlocked(blocked_event) => format!(
+            "Event: AppLocker blocked {} ({} policy) on {}",
+            blocked_event.file_path,
+            blocked_event.policy.as_deref().unwrap_or("unknown"),
+            timestamp
+        ),
+        EventDetails::RemoteExecution(exec_event) => format!(
+            "Event: {} via {} on {}{}",
+            exec_event.kind,
+            exec_event
+                .host
+                .as_deref()
+                .or(exec_event.resource_uri.as_deref())
+                .unwrap_or("unknown host"),
+            timestamp,
+            exec_event
+                .script
+                .as_deref()
+                .map(|script| format!(": {}", script.lines().next().unwrap_or("")))
+                .unwrap_or_default()
+        ),
+        EventDetails::Lockout(lockout_event) => format!(
+            "Event: Account {} locked out on {} after logons from {}{}",
+            lockout_event.account,
+            timestamp,
+            lockout_event
+                .caller_computer
+                .as_deref()
+                .unwrap_or("an unknown computer"),
+            match lockout_event.causes.len() {
+                0 => String::new(),
+                n => format!(" ({} failed logons seen)", n),
+            }
+        ),
+        EventDetails::Heartbeat { seq } => format!("Event: Heartbeat #{} on {}", seq, timestamp),
+        EventDetails::QueryStats {
+            channel,
+            duration,
+            event_count,
+            parse_errors,
+        } => format!(
+            "Event: Query of {} returned {} events ({} unparseable) in {:?} on {}",
+            channel, event_count, parse_errors, duration, timestamp
+        ),
+        EventDetails::SelfTest => format!("Event: [TEST] Self-test alert on {}", timestamp),
+        EventDetails::ListenerPanicked {
+            listener,
+            message,
+            restarts,
+            restarting,
+        } => format!(
+            "Event: Listener {} panicked ({}) on {}, {}",
+            listener,
+            message,
+            timestamp,
+            if *restarting {
+                format!("restart {}", restarts)
+            } else {
+                format!("stopped after {} restarts", restarts)
+            }
+        ),
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, LoggerProvider, Severity as OtelSeverity};
+use opentelemetry::{Key, KeyValue};
+use opentelemetry_otlp::{LogExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::logs::{SdkLogger, SdkLoggerProvider};
+use serde_json::Value;
+
+use crate::enrich::Severity;
+use crate::errors::SentinelError;
+use crate::listener::Event;
+
+use super::ecs::to_ecs;
+use super::{Sink, format_event};
+
+/// Where an OpenTelemetry collector listens for OTLP over HTTP by default.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:4318/v1/logs";
+
+/// ECS fields left out of the attributes because the log record carries them itself.
+const RECORD_FIELDS: [&str; 3] = ["@timestamp", "message", "log.level"];
+
+/// Exports each event as an OpenTelemetry log record over OTLP/HTTP, for organizations that
+/// already run a collector. The resource identifies this machine (`host.name`, `os.type`), the
+/// record's severity comes from the event's, and its attributes are the event's ECS fields under
+/// their dotted names (`user.name`, `source.ip`, ...), which mostly coincide with OTel's own
+/// semantic conventions.
+///
+/// Records are exported in the background in batches; `flush` waits for the pending ones.
+pub struct OtelSink {
+    provider: SdkLoggerProvider,
+    logger: SdkLogger,
+}
+
+impl OtelSink {
+    /// Exports to [`DEFAULT_ENDPOINT`].
+    pub fn new() -> Result<Self, SentinelError> {
+        Self::export_to(DEFAULT_ENDPOINT)
+    }
+
+    /// Exports to the OTLP/HTTP logs `endpoint`, e.g. `http://collector:4318/v1/logs`.
+    pub fn export_to(endpoint: &str) -> Result<Self, SentinelError> {
+        let exporter = LogExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| SentinelError::SinkError(format!("otel: {}", e)))?;
+
+        let mut resource = Resource::builder()
+            .with_service_name("hosho")
+            .with_attribute(KeyValue::new("os.type", "windows"));
+        if let Ok(host) = dns_lookup::get_hostname() {
+            resource = resource.with_attribute(KeyValue::new("host.name", host));
+        }
+
+        let provider = SdkLoggerProvider::builder()
+            .with_resource(resource.build())
+            .with_batch_exporter(exporter)
+            .build();
+        let logger = provider.logger("hosho");
+        Ok(Self { provider, logger })
+    }
+}
+
+/// The OTel severity number for `severity`.
+pub fn severity_number(severity: Severity) -> OtelSeverity {
+    match severity {
+        Severity::Critical => OtelSeverity::Fatal,
+        Severity::High => OtelSeverity::Error,
+        Severity::Medium => OtelSeverity::Warn,
+        Severity::Low => OtelSeverity::Info2,
+        Severity::Info => OtelSeverity::Info,
+    }
+}
+
+/// The event's ECS document flattened into dotted attribute names, leaving out what the log
+/// record carries itself.
+pub fn attributes(event: &Event) -> Vec<(String, AnyValue)> {
+    let mut attributes = Vec::new();
+    flatten(String::new(), to_ecs(event), &mut attributes);
+    attributes.retain(|(key, _)| !RECORD_FIELDS.contains(&key.as_str()));
+    attributes
+}
+
+fn flatten(prefix: String, value: Value, attributes: &mut Vec<(String, AnyValue)>) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let key = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                flatten(key, value, attributes);
+            }
+        }
+        Value::Null => {}
+        value => attributes.push((prefix, any_value(value))),
+    }
+}
+
+fn any_value(value: Value) -> AnyValue {
+    match value {
+        Value::Bool(b) => AnyValue::Boolean(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => AnyValue::Int(i),
+            None => AnyValue::Double(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => AnyValue::String(s.into()),
+        Value::Array(items) => items.into_iter().map(any_value).collect(),
+        // Objects are flattened and nulls dropped before we get here.
+        value => AnyValue::String(value.to_string().into()),
+    }
+}
+
+#[async_trait]
+impl Sink for OtelSink {
+    fn name(&self) -> &str {
+        "otel"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        let mut record = self.logger.create_log_record();
+        record.set_timestamp(SystemTime::from(event.timestamp));
+        if let Some(collected_at) = event.collected_at {
+            record.set_observed_timestamp(SystemTime::from(collected_at));
+        }
+        record.set_severity_number(severity_number(event.severity));
+        record.set_severity_text(severity_text(event.severity));
+        let body = match event.rendering.as_ref().and_then(|r| r.message.as_ref()) {
+            Some(message) => message.clone(),
+            None => format_event(event),
+        };
+        record.set_body(AnyValue::String(body.into()));
+        for (key, value) in attributes(event) {
+            record.add_attribute(Key::from(key), value);
+        }
+        self.logger.emit(record);
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        let provider = self.provider.clone();
+        tokio::task::spawn_blocking(move || provider.force_flush())
+            .await
+            .map_err(|e| SentinelError::SinkError(format!("otel: {}", e)))?
+            .map_err(|e| SentinelError::SinkError(format!("otel: flush failed: {}", e)))
+    }
+
+    async fn close(&self) -> Result<(), SentinelError> {
+        let provider = self.provider.clone();
+        tokio::task::spawn_blocking(move || provider.shutdown())
+            .await
+            .map_err(|e| SentinelError::SinkError(format!("otel: {}", e)))?
+            .map_err(|e| SentinelError::SinkError(format!("otel: shutdown failed: {}", e)))
+    }
+}
+
+fn severity_text(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+        Severity::Info => "info",
+    }
+}
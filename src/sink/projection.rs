@@ -0,0 +1,59 @@
+use serde_json::{Map, Value};
+
+/// An allowlist of the fields JSON output keeps, as dotted paths into the serialized document,
+/// e.g. `timestamp` or `details.Login.source_ip` (`source.ip` for ECS). Naming an object keeps
+/// everything under it. Every other field is dropped rather than redacted, for deployments that
+/// want the smallest footprint. An empty projection keeps everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Projection {
+    fields: Vec<String>,
+}
+
+impl Projection {
+    pub fn new(fields: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Copies the allowed fields of `document` into a new document. Paths that aren't in
+    /// `document` are skipped, so one projection can cover several event kinds.
+    pub fn apply(&self, document: Value) -> Value {
+        if self.is_empty() {
+            return document;
+        }
+        let mut projected = Value::Object(Map::new());
+        for field in &self.fields {
+            let path: Vec<_> = field.split('.').collect();
+            if let Some(value) = lookup(&document, &path) {
+                insert(&mut projected, &path, value.clone());
+            }
+        }
+        projected
+    }
+}
+
+fn lookup<'a>(document: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(document, |value, key| value.as_object()?.get(*key))
+}
+
+fn insert(document: &mut Value, path: &[&str], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = document;
+    for key in parents {
+        let Value::Object(map) = current else {
+            return;
+        };
+        current = map.entry(*key).or_insert_with(|| Value::Object(Map::new()));
+    }
+    if let Value::Object(map) = current {
+        map.insert(last.to_string(), value);
+    }
+}
@@ -0,0 +1,224 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::errors::SentinelError;
+use crate::listener::{Account, Event, EventDetails, LogonEvent};
+
+use super::Sink;
+
+/// A field that may hold personal data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    SourceIp,
+    SourceHostname,
+    /// Every user name an event carries: the target and subject of a logon, the user of a screen
+    /// lock, and the account of a lockout.
+    Username,
+}
+
+/// How a field's value is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    /// Replace the value entirely.
+    Mask,
+    /// Replace the value with a salted hash, so equal values still correlate.
+    Hash,
+    /// Keep only the network part of an address (/24 for IPv4, /48 for IPv6). Values that
+    /// aren't addresses are masked.
+    Network,
+}
+
+const MASK: &str = "[redacted]";
+
+/// A `FIELD=MODE` rule, e.g. `source_ip=network`, as given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedactionRule {
+    pub field: Field,
+    pub redaction: Redaction,
+}
+
+impl FromStr for RedactionRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, redaction) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected FIELD=MODE, got '{}'", s))?;
+
+        let field = match field {
+            "source_ip" => Field::SourceIp,
+            "source_hostname" => Field::SourceHostname,
+            "username" => Field::Username,
+            other => return Err(format!("unknown field '{}'", other)),
+        };
+        let redaction = match redaction {
+            "mask" => Redaction::Mask,
+            "hash" => Redaction::Hash,
+            "network" => Redaction::Network,
+            other => return Err(format!("unknown redaction '{}'", other)),
+        };
+
+        Ok(Self { field, redaction })
+    }
+}
+
+/// Hides configured fields of an event before it reaches a sink.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<RedactionRule>,
+    salt: String,
+}
+
+impl Redactor {
+    /// `salt` is mixed into hashed values, so they can't be reversed by hashing guesses.
+    pub fn new(salt: impl Into<String>) -> Self {
+        Self {
+            rules: Vec::new(),
+            salt: salt.into(),
+        }
+    }
+
+    pub fn with_rule(mut self, field: Field, redaction: Redaction) -> Self {
+        self.rules.push(RedactionRule { field, redaction });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns a copy of `event` with every configured field redacted. With any rule at all, the
+    /// rendered message is dropped too, since Windows writes the same names and addresses into
+    /// it as free text.
+    pub fn apply(&self, event: &Event) -> Event {
+        let mut event = event.clone();
+        for rule in &self.rules {
+            self.redact(&mut event, *rule);
+        }
+        if !self.is_empty()
+            && let Some(rendering) = &mut event.rendering
+        {
+            rendering.message = None;
+        }
+        event
+    }
+
+    fn redact(&self, event: &mut Event, rule: RedactionRule) {
+        match (&mut event.details, rule.field) {
+            (EventDetails::Login(login), _) => self.redact_logon(login, rule),
+            (EventDetails::ScreenLock(lock), Field::Username) => {
+                lock.username = self.redact_value(&lock.username, rule.redaction);
+            }
+            (EventDetails::Lockout(lockout), field) => {
+                match field {
+                    Field::Username => self.redact_account(&mut lockout.account, rule.redaction),
+                    Field::SourceHostname => {
+                        if let Some(caller) = &mut lockout.caller_computer {
+                            *caller = self.redact_value(caller, rule.redaction);
+                        }
+                    }
+                    Field::SourceIp => {}
+                }
+                for cause in &mut lockout.causes {
+                    self.redact_logon(cause, rule);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn redact_logon(&self, login: &mut LogonEvent, rule: RedactionRule) {
+        match rule.field {
+            Field::SourceIp => {
+                login.source_ip = self.redact_value(&login.source_ip, rule.redaction);
+                login.source_ip_raw = login.source_ip.clone();
+            }
+            Field::SourceHostname => {
+                if let Some(hostname) = &mut login.source_hostname {
+                    *hostname = self.redact_value(hostname, rule.redaction);
+                }
+            }
+            Field::Username => {
+                self.redact_account(&mut login.target, rule.redaction);
+                if let Some(subject) = &mut login.subject {
+                    self.redact_account(subject, rule.redaction);
+                }
+            }
+        }
+    }
+
+    fn redact_account(&self, account: &mut Account, redaction: Redaction) {
+        account.user = self.redact_value(&account.upn(), redaction);
+        account.domain = None;
+        account.sid = None;
+    }
+
+    fn redact_value(&self, value: &str, redaction: Redaction) -> String {
+        match redaction {
+            Redaction::Mask => MASK.to_string(),
+            Redaction::Hash => {
+                let digest = Sha256::new()
+                    .chain_update(self.salt.as_bytes())
+                    .chain_update(value.as_bytes())
+                    .finalize();
+                digest[..8]
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect()
+            }
+            Redaction::Network => match value.parse::<IpAddr>() {
+                Ok(IpAddr::V4(ip)) => {
+                    let [a, b, c, _] = ip.octets();
+                    format!("{}.{}.{}.0/24", a, b, c)
+                }
+                Ok(IpAddr::V6(ip)) => {
+                    let s = ip.segments();
+                    format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2])
+                }
+                Err(_) => MASK.to_string(),
+            },
+        }
+    }
+}
+
+/// Wraps a sink so that every event it receives has been through `redactor` first.
+pub struct RedactedSink<S> {
+    inner: S,
+    redactor: Redactor,
+}
+
+impl<S: Sink> RedactedSink<S> {
+    pub fn new(inner: S, redactor: Redactor) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+#[async_trait]
+impl<S: Sink> Sink for RedactedSink<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        self.inner.emit(&self.redactor.apply(event)).await
+    }
+
+    async fn emit_batch(&self, events: &[Event]) -> Result<(), SentinelError> {
+        let redacted: Vec<Event> = events
+            .iter()
+            .map(|event| self.redactor.apply(event))
+            .collect();
+        self.inner.emit_batch(&redacted).await
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        self.inner.flush().await
+    }
+
+    async fn close(&self) -> Result<(), SentinelError> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// A `KIND[:ARG]` sink, e.g. `file:C:\logs\hosho.log`, as given to `--sink` on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkSpec {
+    /// `stdout`
+    Stdout,
+    /// `file:PATH`
+    File(PathBuf),
+    /// `archive:PATH`
+    Archive(PathBuf),
+    /// `etw`
+    #[cfg(windows)]
+    Etw,
+    /// `eventlog`
+    #[cfg(windows)]
+    EventLog,
+    /// `otlp` for a local collector, or `otlp:URL`
+    #[cfg(feature = "otel")]
+    Otlp(Option<String>),
+}
+
+impl FromStr for SinkSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, arg) = match s.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg).filter(|arg| !arg.is_empty())),
+            None => (s, None),
+        };
+        let path = |arg: Option<&str>| {
+            arg.map(PathBuf::from)
+                .ok_or_else(|| format!("expected {}:PATH, got '{}'", kind, s))
+        };
+
+        match kind {
+            "stdout" => Ok(SinkSpec::Stdout),
+            "file" => path(arg).map(SinkSpec::File),
+            "archive" => path(arg).map(SinkSpec::Archive),
+            #[cfg(windows)]
+            "etw" => Ok(SinkSpec::Etw),
+            #[cfg(windows)]
+            "eventlog" => Ok(SinkSpec::EventLog),
+            #[cfg(feature = "otel")]
+            "otlp" => Ok(SinkSpec::Otlp(arg.map(str::to_string))),
+            other => Err(format!("unknown sink '{}'", other)),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::Write;
+
+use async_trait::async_trait;
+
+use crate::errors::SentinelError;
+use crate::listener::Event;
+
+use super::projection::Projection;
+use super::{DisplayTz, OutputFormat, Sink};
+
+/// Prints each event as a line, human-readable unless another format is chosen.
+#[derive(Debug, Default)]
+pub struct StdoutSink {
+    format: OutputFormat,
+    display_tz: DisplayTz,
+    projection: Projection,
+}
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_display_tz(mut self, display_tz: DisplayTz) -> Self {
+        self.display_tz = display_tz;
+        self
+    }
+
+    /// Keeps only the fields `projection` allows in JSON output.
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+}
+
+#[async_trait]
+impl Sink for StdoutSink {
+    fn name(&self) -> &str {
+        "stdout"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        writeln!(
+            std::io::stdout().lock(),
+            "{}",
+            self.format
+                .render_projected(event, self.display_tz, &self.projection)
+        )
+        .map_err(|e| SentinelError::SinkError(format!("stdout: {}", e)))
+    }
+
+    async fn flush(&self) -> Result<(), SentinelError> {
+        std::io::stdout()
+            .flush()
+            .map_err(|e| SentinelError::SinkError(format!("stdout: {}", e)))
+    }
+}
@@ -0,0 +1,175 @@
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::errors::SentinelError;
+use crate::listener::logon::ImpersonationLevel;
+use crate::listener::{Event, EventDetails, Sid};
+use crate::pipeline::Transform;
+
+/// A condition on an event's parsed fields. In JSON, a rule is one of
+/// `{"field": "username", "equals": "svc_backup"}`,
+/// `{"field": "source_ip", "in_network": "10.1.0.0/16"}`, `{"all": [...]}`, or `{"any": [...]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Rule {
+    All { all: Vec<Rule> },
+    Any { any: Vec<Rule> },
+    Field { field: String, equals: String },
+    InNetwork { field: String, in_network: Cidr },
+}
+
+impl Rule {
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Rule::All { all } => all.iter().all(|rule| rule.matches(event)),
+            Rule::Any { any } => any.iter().any(|rule| rule.matches(event)),
+            Rule::Field { field, equals } => {
+                field_value(event, field).is_some_and(|value| value.eq_ignore_ascii_case(equals))
+            }
+            Rule::InNetwork { field, in_network } => field_value(event, field)
+                .and_then(|value| value.parse().ok())
+                .is_some_and(|ip| in_network.contains(ip)),
+        }
+    }
+}
+
+/// An address range in CIDR notation, e.g. `10.1.0.0/16` or `2001:db8::/32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = SentinelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SentinelError::ConfigError(format!("invalid network {:?}", s));
+        let (network, prefix) = s.split_once('/').ok_or_else(invalid)?;
+        let network: IpAddr = network.trim().parse().map_err(|_| invalid())?;
+        let prefix: u8 = prefix.trim().parse().map_err(|_| invalid())?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        if prefix > max_prefix {
+            return Err(invalid());
+        }
+        Ok(Self { network, prefix })
+    }
+}
+
+impl TryFrom<String> for Cidr {
+    type Error = SentinelError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Drops known noise: any event matching one of its rules is never forwarded.
+#[derive(Debug, Clone, Default)]
+pub struct Suppressor {
+    rules: Vec<Rule>,
+}
+
+impl Suppressor {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+
+    /// Parses a JSON array of rules.
+    pub fn from_json(json: &str) -> Result<Self, SentinelError> {
+        serde_json::from_str(json)
+            .map(Self::new)
+            .map_err(|e| SentinelError::ConfigError(format!("suppression rules: {}", e)))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| SentinelError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    pub fn is_suppressed(&self, event: &Event) -> bool {
+        self.rules.iter().any(|rule| rule.matches(event))
+    }
+}
+
+#[async_trait]
+impl Transform for Suppressor {
+    fn name(&self) -> &str {
+        "suppress"
+    }
+
+    async fn transform(&self, event: Event) -> Option<Event> {
+        (!self.is_suppressed(&event)).then_some(event)
+    }
+}
+
+/// The value of a named field, formatted as it would be written in a rule. Fields an event
+/// doesn't have never match. Any event has a `kind`, named as on the command line (e.g.
+/// `lockout`), and most have the Windows `event_id` they were parsed from.
+pub(crate) fn field_value(event: &Event, field: &str) -> Option<String> {
+    match (field, &event.details) {
+        ("kind", _) => event
+            .kind()
+            .to_possible_value()
+            .map(|kind| kind.get_name().to_string()),
+        ("event_id", _) => event.event_id().map(|id| id.to_string()),
+        ("computer", _) => event.computer.clone(),
+        ("record_id", _) => event.record_id().map(|id| id.to_string()),
+        ("username", EventDetails::Login(login)) => Some(login.target.user.clone()),
+        ("sid", EventDetails::Login(login)) => login.target.sid.as_ref().map(Sid::to_string),
+        ("domain", EventDetails::Login(login)) => login.target.domain.clone(),
+        ("username", EventDetails::ScreenLock(lock)) => Some(lock.username.clone()),
+        ("username", EventDetails::Lockout(lockout)) => Some(lockout.account.user.clone()),
+        ("source_ip", EventDetails::Login(login)) => Some(login.source_ip.clone()),
+        ("variant", EventDetails::Login(login)) => Some(login.variant.to_string()),
+        ("success", EventDetails::Login(login)) => Some(login.success.to_string()),
+        ("creator_process", EventDetails::Login(login)) => login.creator_process.clone(),
+        ("subject", EventDetails::Login(login)) => {
+            login.subject.as_ref().map(|subject| subject.user.clone())
+        }
+        ("target_server", EventDetails::Login(login)) => login.target_server.clone(),
+        ("impersonation_level", EventDetails::Login(login)) => login
+            .impersonation_level
+            .as_ref()
+            .map(ImpersonationLevel::to_string),
+        ("self_logon", EventDetails::Login(login)) => Some(login.is_self_logon().to_string()),
+        ("suspicious_account", EventDetails::Login(login)) => {
+            Some(login.suspicious_account.to_string())
+        }
+        ("anonymous", EventDetails::Login(login)) => Some(login.is_anonymous().to_string()),
+        ("device_id", EventDetails::UsbDevice(usb)) => Some(usb.device_id.clone()),
+        ("threat_name", EventDetails::ThreatDetected(threat)) => Some(threat.threat_name.clone()),
+        ("path", EventDetails::ThreatDetected(threat)) => threat.path.clone(),
+        ("file_path", EventDetails::AppBlocked(blocked)) => Some(blocked.file_path.clone()),
+        ("host", EventDetails::RemoteExecution(exec)) => exec.host.clone(),
+        _ => None,
+    }
+}
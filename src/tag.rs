@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::errors::SentinelError;
+use crate::listener::Event;
+use crate::pipeline::Transform;
+use crate::suppress::Rule;
+
+/// Labels events matching `when` with `tag`. In JSON,
+/// `{"tag": "dmz", "when": {"field": "source_ip", "in_network": "10.1.0.0/16"}}`, where `when`
+/// is any suppression rule.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TagRule {
+    pub tag: String,
+    pub when: Rule,
+}
+
+/// Attaches tags for routing and downstream filtering. Every matching rule adds its tag.
+#[derive(Debug, Clone, Default)]
+pub struct Tagger {
+    rules: Vec<TagRule>,
+}
+
+impl Tagger {
+    pub fn new(rules: Vec<TagRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Parses a JSON array of tag rules.
+    pub fn from_json(json: &str) -> Result<Self, SentinelError> {
+        serde_json::from_str(json)
+            .map(Self::new)
+            .map_err(|e| SentinelError::ConfigError(format!("tag rules: {}", e)))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SentinelError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| SentinelError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Appends the tag of each rule `event` matches, skipping tags it already has.
+    pub fn tag(&self, event: &mut Event) {
+        for rule in &self.rules {
+            if rule.when.matches(event) && !event.tags.contains(&rule.tag) {
+                event.tags.push(rule.tag.clone());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transform for Tagger {
+    fn name(&self) -> &str {
+        "tag"
+    }
+
+    async fn transform(&self, mut event: Event) -> Option<Event> {
+        self.tag(&mut event);
+        Some(event)
+    }
+}
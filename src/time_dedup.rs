@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::listener::{Event, EventDetails};
+use crate::pipeline::Transform;
+use crate::suppress::field_value;
+
+/// Drops events identical on a key to one forwarded less than a window earlier, judged by the
+/// events' own timestamps rather than record IDs. Complements the listeners' record ID dedup for
+/// sources without reliable record IDs, and thins out bursts of repeated failures: each key is
+/// forwarded at most once per window, however many events arrive.
+///
+/// The key is the event's kind plus the named fields, using the same field names as suppression
+/// rules. A field an event doesn't have counts as empty.
+///
+/// Operational events (heartbeats, query stats, self-tests, and listener panics) always pass, so
+/// heartbeat sequence gaps still mean lost events. So does a collapsed failure counting more
+/// attempts than the last one forwarded for its key, since it carries news the earlier one
+/// didn't.
+#[derive(Debug)]
+pub struct TimeDedup {
+    fields: Vec<String>,
+    window: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When each key was last forwarded, and how many attempts that event counted.
+    last_forwarded: HashMap<String, (DateTime<Utc>, u32)>,
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+impl TimeDedup {
+    /// Keys on `username`, `source_ip`, `variant`, and `success`.
+    pub const DEFAULT_FIELDS: [&str; 4] = ["username", "source_ip", "variant", "success"];
+
+    pub fn new(window: Duration) -> Self {
+        Self::with_fields(window, Self::DEFAULT_FIELDS.map(str::to_string).to_vec())
+    }
+
+    pub fn with_fields(window: Duration, fields: Vec<String>) -> Self {
+        Self {
+            fields,
+            window,
+            state: Mutex::default(),
+        }
+    }
+
+    fn key(&self, event: &Event) -> String {
+        let mut key = format!("{:?}", event.kind());
+        for field in &self.fields {
+            key.push('\0');
+            key.push_str(&field_value(event, field).unwrap_or_default());
+        }
+        key
+    }
+
+    /// Whether `event` repeats one forwarded within the window. Events that aren't duplicates
+    /// start a new window for their key.
+    pub fn is_duplicate(&self, event: &Event) -> bool {
+        if event.kind().is_operational() {
+            return false;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let at = event.timestamp;
+
+        // Forget keys whose window has passed, at most once per window.
+        if state
+            .last_pruned
+            .is_none_or(|pruned| at - pruned >= self.window)
+        {
+            let window = self.window;
+            state
+                .last_forwarded
+                .retain(|_, (forwarded, _)| at - *forwarded < window);
+            state.last_pruned = Some(at);
+        }
+
+        let key = self.key(event);
+        let attempts = match &event.details {
+            EventDetails::Login(login) => login.attempt_count,
+            _ => 1,
+        };
+        if let Some((forwarded, forwarded_attempts)) = state.last_forwarded.get(&key)
+            && (at - *forwarded).abs() < self.window
+            && attempts <= *forwarded_attempts
+        {
+            return true;
+        }
+        state.last_forwarded.insert(key, (at, attempts));
+        false
+    }
+}
+
+#[async_trait]
+impl Transform for TimeDedup {
+    fn name(&self) -> &str {
+        "time-dedup"
+    }
+
+    async fn transform(&self, event: Event) -> Option<Event> {
+        (!self.is_duplicate(&event)).then_some(event)
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Duration as ChronoDuration;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use tokio_stream::StreamExt;
+
+use crate::clock::SystemClock;
+use crate::leaderboard::Leaderboard;
+use crate::listener::{Event, EventDetails, LogonListener};
+use crate::sink::{DisplayTz, format_event_in};
+
+/// How many events the monitor keeps for scrolling back.
+pub const DEFAULT_CAPACITY: usize = 500;
+
+/// How often the screen is redrawn and the keyboard checked.
+const TICK: Duration = Duration::from_millis(100);
+
+/// How many entries each top-offenders list shows.
+const TOP_N: usize = 5;
+
+/// Which events the event list shows. Counters and top offenders always cover everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub failures_only: bool,
+    /// Shows only logons for this user, compared case-insensitively against the bare username.
+    pub username: Option<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, event: &Event) -> bool {
+        let login = match &event.details {
+            EventDetails::Login(login) => Some(login),
+            _ => None,
+        };
+        if self.failures_only && !login.is_some_and(|login| !login.success) {
+            return false;
+        }
+        self.username.as_ref().is_none_or(|username| {
+            login.is_some_and(|login| login.target.user.eq_ignore_ascii_case(username))
+        })
+    }
+}
+
+/// What the TUI shows: the most recent events, logon counts per variant, and the top offenders
+/// over the last hour. Kept apart from drawing so it can be driven without a terminal.
+pub struct Monitor {
+    recent: VecDeque<Event>,
+    capacity: usize,
+    variant_counts: BTreeMap<String, u64>,
+    leaderboard: Leaderboard,
+    pub filter: Filter,
+}
+
+impl Monitor {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            recent: VecDeque::with_capacity(capacity),
+            capacity,
+            variant_counts: BTreeMap::new(),
+            leaderboard: Leaderboard::new(ChronoDuration::hours(1), Arc::new(SystemClock)),
+            filter: Filter::default(),
+        }
+    }
+
+    pub fn record(&mut self, event: Event) {
+        if let EventDetails::Login(login) = &event.details {
+            *self
+                .variant_counts
+                .entry(login.variant.to_string())
+                .or_default() += u64::from(login.attempt_count.max(1));
+        }
+        self.leaderboard.record(&event);
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(event);
+    }
+
+    /// The recent events the filter lets through, newest first.
+    pub fn visible(&self) -> impl Iterator<Item = &Event> {
+        self.recent
+            .iter()
+            .rev()
+            .filter(|event| self.filter.matches(event))
+    }
+
+    /// Logons seen per variant, counting each attempt of a collapsed failure.
+    pub fn variant_counts(&self) -> &BTreeMap<String, u64> {
+        &self.variant_counts
+    }
+}
+
+/// Whether the keyboard is navigating or typing a username filter.
+enum Mode {
+    Normal,
+    EditingUsername(String),
+}
+
+/// Tails `listener` into a full-screen monitor until `q` is pressed. `f` toggles failures only,
+/// `u` filters by a username (Enter to apply, Esc to cancel), and `c` clears the filters.
+pub async fn run(listener: LogonListener, display_tz: DisplayTz) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = monitor(&mut terminal, listener, display_tz).await;
+    ratatui::restore();
+    result
+}
+
+async fn monitor(
+    terminal: &mut DefaultTerminal,
+    listener: LogonListener,
+    display_tz: DisplayTz,
+) -> io::Result<()> {
+    let mut events = listener.tail();
+    let mut monitor = Monitor::new(DEFAULT_CAPACITY);
+    let mut mode = Mode::Normal;
+    let mut tick = tokio::time::interval(TICK);
+
+    loop {
+        tokio::select! {
+            Some(event) = events.next() => monitor.record(event),
+            _ = tick.tick() => {
+                while event::poll(Duration::ZERO)? {
+                    if let TermEvent::Key(key) = event::read()?
+                        && key.kind == KeyEventKind::Press
+                        && !handle_key(&mut monitor, &mut mode, key.code)
+                    {
+                        return Ok(());
+                    }
+                }
+                terminal.draw(|frame| draw(frame, &mut monitor, &mode, display_tz))?;
+            }
+        }
+    }
+}
+
+/// Applies a key press, returning false when it's time to quit.
+fn handle_key(monitor: &mut Monitor, mode: &mut Mode, key: KeyCode) -> bool {
+    match mode {
+        Mode::Normal => match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('f') => monitor.filter.failures_only = !monitor.filter.failures_only,
+            KeyCode::Char('u') => *mode = Mode::EditingUsername(String::new()),
+            KeyCode::Char('c') => monitor.filter = Filter::default(),
+            _ => {}
+        },
+        Mode::EditingUsername(input) => match key {
+            KeyCode::Enter => {
+                let username = input.trim().to_string();
+                monitor.filter.username = (!username.is_empty()).then_some(username);
+                *mode = Mode::Normal;
+            }
+            KeyCode::Esc => *mode = Mode::Normal,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        },
+    }
+    true
+}
+
+fn draw(frame: &mut Frame, monitor: &mut Monitor, mode: &Mode, display_tz: DisplayTz) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(1)]).areas(frame.area());
+    let [events, sidebar] =
+        Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(main);
+    let [sources, users, variants] = Layout::vertical([Constraint::Ratio(1, 3); 3]).areas(sidebar);
+
+    let items: Vec<_> = monitor
+        .visible()
+        .take(usize::from(events.height))
+        .map(|event| {
+            let failed = matches!(&event.details, EventDetails::Login(login) if !login.success);
+            let style = if failed {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format_event_in(event, display_tz)).style(style)
+        })
+        .collect();
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title("Events")),
+        events,
+    );
+
+    let top_sources = monitor.leaderboard.top_sources(TOP_N);
+    draw_counts(frame, sources, "Top sources (1h)", &top_sources);
+    let top_users = monitor.leaderboard.top_users(TOP_N);
+    draw_counts(frame, users, "Top users (1h)", &top_users);
+    let variant_counts: Vec<_> = monitor
+        .variant_counts()
+        .iter()
+        .map(|(variant, count)| (variant.clone(), *count))
+        .collect();
+    draw_counts(frame, variants, "Logons by type", &variant_counts);
+
+    let status_line = match mode {
+        Mode::EditingUsername(input) => format!("Username: {}_  (Enter apply, Esc cancel)", input),
+        Mode::Normal => format!(
+            "q quit  f failures only [{}]  u username [{}]  c clear",
+            if monitor.filter.failures_only {
+                "on"
+            } else {
+                "off"
+            },
+            monitor.filter.username.as_deref().unwrap_or("any"),
+        ),
+    };
+    frame.render_widget(Paragraph::new(status_line), status);
+}
+
+fn draw_counts<N: std::fmt::Display>(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    counts: &[(String, N)],
+) {
+    let items: Vec<_> = counts
+        .iter()
+        .map(|(name, count)| ListItem::new(format!("{:>6}  {}", count, name)))
+        .collect();
+    frame.render_widget(List::new(items).block(Block::bordered().title(title)), area);
+}
@@ -0,0 +1,95 @@
+use hosho::listener::{Account, LogonId, Sid};
+
+#[test]
+fn test_account_name_forms() {
+    let account = Account::new("alice", Some("CORP"));
+    assert_eq!(account.upn(), "alice@CORP");
+    assert_eq!(account.downlevel(), r"CORP\alice");
+
+    let local = Account::new("alice", None);
+    assert_eq!(local.upn(), "alice");
+    assert_eq!(local.downlevel(), "alice");
+}
+
+#[test]
+fn test_machine_accounts() {
+    assert!(Account::new("ETHER$", Some("WORKGROUP")).is_machine());
+    assert!(!Account::new("alice", Some("CORP")).is_machine());
+}
+
+#[test]
+fn test_wellknown_accounts() {
+    assert!(Account::new("SYSTEM", Some("NT AUTHORITY")).is_wellknown());
+    assert!(Account::new("DWM-3", Some("Window Manager")).is_wellknown());
+    assert!(
+        Account::new("Système", Some("AUTORITE NT"))
+            .with_sid(Sid::parse("S-1-5-18").unwrap())
+            .is_wellknown()
+    );
+    assert!(!Account::new("alice", Some("CORP")).is_wellknown());
+}
+
+#[test]
+fn test_anonymous_accounts() {
+    let localized = Account::new("ANONYME ANMELDUNG", Some("NT-AUTORITÄT"))
+        .with_sid(Sid::parse("S-1-5-7").unwrap());
+    assert!(localized.is_anonymous());
+    assert!(Account::new("anonymous logon", Some("NT AUTHORITY")).is_anonymous());
+    assert!(
+        !Account::new("ANONYMOUS LOGON", None)
+            .with_sid(Sid::parse("S-1-5-21-1-2-3-1001").unwrap())
+            .is_anonymous(),
+        "the SID wins over the name"
+    );
+}
+
+#[test]
+fn test_sid_parsing() {
+    assert_eq!(
+        Sid::parse("S-1-5-21-1004336348-1177238915-682003330-512")
+            .unwrap()
+            .as_str(),
+        "S-1-5-21-1004336348-1177238915-682003330-512"
+    );
+    assert_eq!(Sid::parse("S-1-0-0"), None);
+    assert_eq!(Sid::parse("-"), None);
+    assert_eq!(Sid::parse("S-1-5-x"), None);
+}
+
+#[test]
+fn test_well_known_sids() {
+    let name = |sid: &str| Sid::parse(sid).unwrap().well_known_name();
+
+    assert_eq!(name("S-1-5-18"), Some("SYSTEM"));
+    assert_eq!(name("S-1-5-19"), Some("LOCAL SERVICE"));
+    assert_eq!(name("S-1-5-20"), Some("NETWORK SERVICE"));
+    assert_eq!(name("S-1-5-32-544"), Some("Administrators"));
+    assert_eq!(
+        name("S-1-5-21-1004336348-1177238915-682003330-500"),
+        Some("Administrator")
+    );
+    assert_eq!(name("S-1-5-90-0-3"), Some("Window Manager"));
+    assert_eq!(name("S-1-5-21-1004336348-1177238915-682003330-1001"), None);
+}
+
+#[test]
+fn test_logon_id_parsing() {
+    assert_eq!("0x3e7".parse(), Ok(LogonId(0x3e7)));
+    assert_eq!("0X3E7".parse(), Ok(LogonId(0x3e7)));
+    assert_eq!("0x00000000000003e7".parse(), Ok(LogonId(0x3e7)));
+    assert!("3e7".parse::<LogonId>().is_err());
+    assert!("0x".parse::<LogonId>().is_err());
+    assert!("-".parse::<LogonId>().is_err());
+}
+
+#[test]
+fn test_logon_id_round_trips() {
+    for id in ["0x0", "0x3e7", "0x1a2b3c", "0xffffffffffffffff"] {
+        assert_eq!(id.parse::<LogonId>().unwrap().to_string(), id);
+    }
+    assert_eq!(
+        serde_json::to_string(&LogonId(0x3e7)).unwrap(),
+        "999",
+        "serialized as a number, as before"
+    );
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use hosho::alert::{AlertGate, AlertThreshold};
+use hosho::enrich::Severity;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::pipeline::Transform;
+
+fn logon(username: &str, severity: Severity) -> Event {
+    let mut event = Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(username, Some("CORP")),
+            "10.1.2.3",
+            LogonVariant::Network,
+            true,
+        )),
+        Utc::now(),
+    );
+    event.severity = severity;
+    event
+}
+
+#[tokio::test]
+async fn test_only_alerts_pass_and_routine_events_are_summarized() {
+    let gate = AlertGate::new(AlertThreshold::default());
+
+    assert!(
+        gate.transform(logon("alice", Severity::Low))
+            .await
+            .is_none()
+    );
+    assert!(
+        gate.transform(logon("alice", Severity::Medium))
+            .await
+            .is_none()
+    );
+    let alert = gate.transform(logon("bob", Severity::High)).await;
+    assert!(alert.is_some_and(|event| event.severity == Severity::High));
+
+    let summary = gate.take_summary();
+    assert_eq!(summary.suppressed, 2);
+    assert_eq!(summary.report.users.len(), 1);
+    assert_eq!(summary.report.users["alice@corp"].logons, 2);
+
+    let summary = gate.take_summary();
+    assert_eq!(summary.suppressed, 0, "taking a summary starts a new one");
+    assert!(summary.report.users.is_empty());
+}
+
+#[tokio::test]
+async fn test_operational_events_bypass_the_gate() {
+    let gate = AlertGate::new(AlertThreshold::default());
+    let stats = EventDetails::QueryStats {
+        channel: "Security".to_string(),
+        duration: Duration::from_millis(5),
+        event_count: 0,
+        parse_errors: 0,
+    };
+
+    for details in [
+        EventDetails::Heartbeat { seq: 1 },
+        stats,
+        EventDetails::SelfTest,
+    ] {
+        let event = Event::new(details, Utc::now());
+        assert!(gate.transform(event).await.is_some());
+    }
+    assert_eq!(gate.take_summary().suppressed, 0);
+}
+
+#[tokio::test]
+async fn test_summary_is_written_as_a_json_line() {
+    let gate = AlertGate::new(AlertThreshold::default());
+    gate.transform(logon("alice", Severity::Low)).await;
+
+    let mut out = Vec::new();
+    gate.take_summary().write_json(&mut out).unwrap();
+
+    let line = String::from_utf8(out).unwrap();
+    assert_eq!(line.lines().count(), 1);
+    let summary: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(summary["suppressed"], 1);
+    assert_eq!(summary["users"]["alice@CORP"]["logons"], 1);
+}
+
+#[test]
+fn test_lockouts_and_risky_logons_are_alerts() {
+    let threshold = AlertThreshold::new(Severity::Critical).with_min_risk_score(60);
+
+    let mut lockout = logon("alice", Severity::Low);
+    if let EventDetails::Login(login) = &mut lockout.details {
+        login.success = false;
+        login.failure_reason = Some("%%2307".to_string());
+    }
+    assert!(threshold.is_alert(&lockout));
+
+    let mut risky = logon("alice", Severity::Low);
+    risky.risk_score = Some(60);
+    assert!(threshold.is_alert(&risky));
+
+    risky.risk_score = Some(59);
+    assert!(!threshold.is_alert(&risky));
+}
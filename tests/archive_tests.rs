@@ -0,0 +1,214 @@
+use chrono::{TimeZone, Utc};
+use hosho::enrich::Severity;
+use hosho::enrich::firewall::FirewallAction;
+use hosho::listener::logon::{ImpersonationLevel, LogonVariant};
+use hosho::listener::remote_exec::RemoteExecutionKind;
+use hosho::listener::{
+    Account, AppBlockedEvent, Event, EventDetails, LockoutEvent, LogonEvent, LogonId,
+    RemoteExecutionEvent, RenderingInfo, ScreenLockEvent, Sid, ThreatEvent, UsbAction,
+    UsbDeviceEvent,
+};
+use hosho::sink::Sink;
+use hosho::sink::archive::{ArchiveSink, Compression, read_archive, write_archive};
+
+fn sample_events() -> Vec<Event> {
+    let timestamp = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+        + chrono::Duration::nanoseconds(123_456_789);
+    let mut details = vec![
+        EventDetails::Login(LogonEvent {
+            target: Account::new("alice", Some("CORP"))
+                .with_sid(Sid::parse("S-1-5-21-1-2-3-1001").unwrap()),
+            source_ip: "10.0.0.5".to_string(),
+            source_ip_raw: "::ffff:10.0.0.5".to_string(),
+            source_hostname: Some("ws1.corp.local".to_string()),
+            variant: LogonVariant::Unknown(99),
+            success: false,
+            event_id: Some(4625),
+            event_record_id: Some(4242),
+            keywords: Some(0x8010000000000000),
+            level: Some(0),
+            task: Some(12544),
+            creator_process: Some(r"C:\Windows\System32\services.exe".to_string()),
+            creator_process_id: Some(640),
+            authentication_package: Some("NTLM".to_string()),
+            attempt_count: 3,
+            is_current_user: true,
+            first_seen_ip: true,
+            first_seen_user: true,
+            logon_id: Some(LogonId(0x1a2b3c)),
+            firewall_action: Some(FirewallAction::Drop),
+            linked_logon_id: Some(LogonId(0x3e7)),
+            subject: Some(Account::new("WS1$", Some("CORP"))),
+            subject_logon_id: Some(LogonId(0x3e7)),
+            impersonation_level: Some(ImpersonationLevel::Delegation),
+            target_server: Some("fs01.corp.local".to_string()),
+            failure_reason: Some("%%2313".to_string()),
+            failure_reason_text: Some("Unknown user name or bad password.".to_string()),
+            sub_status: Some(0xC0000064),
+            suspicious_account: true,
+        }),
+        EventDetails::UsbDevice(UsbDeviceEvent {
+            device_id: r"USB\VID_0781&PID_5581".to_string(),
+            friendly_name: Some("SanDisk Ultra".to_string()),
+            action: UsbAction::Other(7),
+            event_record_id: None,
+        }),
+        EventDetails::ScreenLock(ScreenLockEvent {
+            locked: true,
+            username: "alice".to_string(),
+            event_record_id: Some(1),
+        }),
+        EventDetails::ThreatDetected(ThreatEvent {
+            threat_name: "Virus:DOS/EICAR_Test_File".to_string(),
+            path: Some(r"C:\Users\alice\eicar.com".to_string()),
+            severity: Some("Severe".to_string()),
+            action: Some("Quarantine".to_string()),
+            event_record_id: Some(2),
+        }),
+        EventDetails::AppBlocked(AppBlockedEvent {
+            file_path: r"%OSDRIVE%\TEMP\PAYLOAD.EXE".to_string(),
+            user_sid: Some("S-1-5-21-1-2-3-1001".to_string()),
+            policy: Some("EXE".to_string()),
+            rule_name: None,
+            event_record_id: Some(3),
+        }),
+        EventDetails::RemoteExecution(RemoteExecutionEvent {
+            kind: RemoteExecutionKind::ScriptBlock,
+            script: Some("Get-Process | Out-Null".to_string()),
+            script_truncated: true,
+            script_block_id: Some("a1b2".to_string()),
+            host: Some("wsmprovhost.exe".to_string()),
+            resource_uri: None,
+            event_record_id: Some(4),
+        }),
+        EventDetails::Heartbeat { seq: 17 },
+        EventDetails::SelfTest,
+        EventDetails::ListenerPanicked {
+            listener: "logon".to_string(),
+            message: "malformed event".to_string(),
+            restarts: 2,
+            restarting: true,
+        },
+    ];
+    let EventDetails::Login(failure) = &details[0] else {
+        unreachable!()
+    };
+    details.push(EventDetails::Lockout(LockoutEvent {
+        account: Account::new("alice", None).with_sid(Sid::parse("S-1-5-21-1-2-3-1001").unwrap()),
+        caller_computer: Some("WS1".to_string()),
+        causes: vec![failure.clone()],
+        event_record_id: Some(5),
+    }));
+
+    details
+        .into_iter()
+        .map(|details| {
+            let mut event = Event::new(details, timestamp);
+            event.collected_at = Some(timestamp + chrono::Duration::seconds(2));
+            event.computer = Some("ws1.corp.local".to_string());
+            event.severity = Severity::High;
+            event.rendering = Some(RenderingInfo {
+                message: Some("An account failed to log on.".to_string()),
+                level: Some("Information".to_string()),
+                task: None,
+            });
+            event.tags = vec!["dmz".to_string(), "admin".to_string()];
+            event
+        })
+        .collect()
+}
+
+/// `Event` has no `PartialEq`; its `Debug` output covers every field.
+fn debug(events: &[Event]) -> Vec<String> {
+    events.iter().map(|event| format!("{:?}", event)).collect()
+}
+
+#[test]
+fn test_round_trip_preserves_every_field() {
+    for compression in [Compression::None, Compression::Zstd] {
+        let events = sample_events();
+        let mut archive = Vec::new();
+        write_archive(&mut archive, &events, compression).unwrap();
+
+        let replayed = read_archive(archive.as_slice()).unwrap();
+        assert_eq!(debug(&replayed), debug(&events), "{:?}", compression);
+    }
+}
+
+#[test]
+fn test_zstd_archive_is_smaller() {
+    let events: Vec<_> = (0..50).flat_map(|_| sample_events()).collect();
+    let mut plain = Vec::new();
+    let mut compressed = Vec::new();
+    write_archive(&mut plain, &events, Compression::None).unwrap();
+    write_archive(&mut compressed, &events, Compression::Zstd).unwrap();
+
+    assert!(compressed.len() < plain.len() / 4);
+}
+
+#[test]
+fn test_rejects_other_files() {
+    assert!(read_archive(&b"{\"event\": 1}\n"[..]).is_err());
+    assert!(read_archive(&b""[..]).is_err());
+}
+
+#[tokio::test]
+async fn test_sink_appends_across_reopens() {
+    let path = std::env::temp_dir().join(format!("hosho-archive-{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let events = sample_events();
+
+    let sink = ArchiveSink::open(&path, Compression::Zstd).await.unwrap();
+    sink.emit_batch(&events[..3]).await.unwrap();
+    drop(sink);
+    let sink = ArchiveSink::open(&path, Compression::Zstd).await.unwrap();
+    sink.emit_batch(&events[3..]).await.unwrap();
+    sink.emit(&events[0]).await.unwrap();
+    drop(sink);
+    assert!(ArchiveSink::open(&path, Compression::None).await.is_err());
+
+    let replayed = read_archive(std::fs::File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replayed.len(), events.len() + 1);
+    assert_eq!(debug(&replayed[..events.len()]), debug(&events));
+}
+
+/// An archive of `version` holding one `Heartbeat { seq: 7 }` at schema version 5, as written
+/// before `Lockout` was added.
+fn old_archive(version: u8) -> Vec<u8> {
+    let timestamp = b"2025-06-01T12:00:00Z";
+    let mut record = vec![5, 6, 7, timestamp.len() as u8];
+    record.extend_from_slice(timestamp);
+    // collected_at, computer, severity, risk_score, rendering, tags
+    record.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+    let mut archive = b"HSHA".to_vec();
+    archive.extend_from_slice(&[version, 0]);
+    archive.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&record);
+    archive
+}
+
+#[test]
+fn test_rejects_archives_of_older_versions() {
+    for version in [1, 2, 3] {
+        let error = read_archive(old_archive(version).as_slice()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains(&format!("unsupported archive version {}", version)),
+            "{}",
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_sink_refuses_to_append_to_older_archives() {
+    let path = std::env::temp_dir().join(format!("hosho-archive-v1-{}.bin", std::process::id()));
+    std::fs::write(&path, old_archive(1)).unwrap();
+
+    let result = ArchiveSink::open(&path, Compression::None).await;
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
@@ -0,0 +1,163 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hosho::errors::SentinelError;
+use hosho::listener::{Event, EventDetails};
+use hosho::sink::batch::Batcher;
+use hosho::sink::file::FileSink;
+use hosho::sink::{OutputFormat, Sink};
+use tokio::time::Instant;
+
+/// Fails every other event, relying on the default `emit_batch`.
+struct FlakySink {
+    emitted: AtomicUsize,
+}
+
+#[async_trait]
+impl Sink for FlakySink {
+    fn name(&self) -> &str {
+        "flaky"
+    }
+
+    async fn emit(&self, _event: &Event) -> Result<(), SentinelError> {
+        if self.emitted.fetch_add(1, Ordering::SeqCst) % 2 == 1 {
+            return Err(SentinelError::SinkError("dropped".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Simulates a sink with a fixed cost per call (a network round trip or a transaction commit),
+/// which `emit_batch` pays once for the whole batch.
+struct RoundTripSink {
+    calls: AtomicUsize,
+}
+
+const ROUND_TRIP: Duration = Duration::from_millis(5);
+
+#[async_trait]
+impl Sink for RoundTripSink {
+    fn name(&self) -> &str {
+        "round-trip"
+    }
+
+    async fn emit(&self, _event: &Event) -> Result<(), SentinelError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(ROUND_TRIP).await;
+        Ok(())
+    }
+
+    async fn emit_batch(&self, _events: &[Event]) -> Result<(), SentinelError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(ROUND_TRIP).await;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_batcher_fills_at_max_events() {
+    let mut batcher = Batcher::new(3, Duration::from_secs(60));
+
+    assert!(batcher.deadline().is_none());
+    assert!(!batcher.push(Event::self_test()));
+    assert!(batcher.deadline().is_some());
+    assert!(!batcher.push(Event::self_test()));
+    assert!(batcher.push(Event::self_test()));
+
+    assert_eq!(batcher.take().len(), 3);
+    assert!(batcher.deadline().is_none());
+    assert!(batcher.take().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batcher_deadline_tracks_oldest_event() {
+    let mut batcher = Batcher::new(100, Duration::from_millis(250));
+
+    let start = Instant::now();
+    batcher.push(Event::self_test());
+    tokio::time::advance(Duration::from_millis(100)).await;
+    batcher.push(Event::self_test());
+
+    assert_eq!(batcher.deadline(), Some(start + Duration::from_millis(250)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_batched_emission_throughput() {
+    let unbatched = RoundTripSink {
+        calls: AtomicUsize::new(0),
+    };
+    let start = Instant::now();
+    for _ in 0..100 {
+        unbatched.emit(&Event::self_test()).await.unwrap();
+    }
+    let unbatched_elapsed = start.elapsed();
+
+    let batched = RoundTripSink {
+        calls: AtomicUsize::new(0),
+    };
+    let mut batcher = Batcher::new(100, Duration::from_secs(1));
+    let start = Instant::now();
+    for _ in 0..100 {
+        if batcher.push(Event::self_test()) {
+            batched.emit_batch(&batcher.take()).await.unwrap();
+        }
+    }
+    let batched_elapsed = start.elapsed();
+
+    assert_eq!(unbatched.calls.load(Ordering::SeqCst), 100);
+    assert_eq!(unbatched_elapsed, ROUND_TRIP * 100);
+    assert_eq!(
+        batched.calls.load(Ordering::SeqCst),
+        1,
+        "one call per batch"
+    );
+    assert_eq!(batched_elapsed, ROUND_TRIP);
+}
+
+#[tokio::test]
+async fn test_default_emit_batch_continues_past_failures() {
+    let sink = FlakySink {
+        emitted: AtomicUsize::new(0),
+    };
+    let events: Vec<_> = (0..5).map(|_| Event::self_test()).collect();
+
+    let error = sink.emit_batch(&events).await.unwrap_err();
+
+    assert_eq!(sink.emitted.load(Ordering::SeqCst), 5);
+    assert!(
+        error.to_string().contains("2 of 5 events failed"),
+        "unexpected error: {}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_file_sink_writes_a_batch_in_order() {
+    let path = std::env::temp_dir().join(format!("hosho-batch-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let sink = FileSink::open(&path)
+        .await
+        .unwrap()
+        .with_format(OutputFormat::Json);
+    let events: Vec<_> = (1..=3)
+        .map(|seq| Event::new(EventDetails::Heartbeat { seq }, Utc::now()))
+        .collect();
+    sink.emit_batch(&events).await.unwrap();
+    sink.emit_batch(&[]).await.unwrap();
+    sink.close().await.unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let seqs: Vec<_> = contents
+        .lines()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["details"]["Heartbeat"]["seq"].as_u64().unwrap()
+        })
+        .collect();
+    assert_eq!(seqs, vec![1, 2, 3]);
+
+    std::fs::remove_file(&path).unwrap();
+}
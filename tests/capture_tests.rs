@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hosho::listener::capture::{Capture, replay_source, split_events};
+use hosho::listener::{EventDetails, EventListener, LogonListener};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+fn event(event_id: u32, record_id: u32) -> String {
+    format!(
+        r#"<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>{event_id}</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:25:08.8954670Z'/>
+        <EventRecordID>{record_id}</EventRecordID>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='TargetUserName'>alice</Data>
+        <Data Name='TargetDomainName'>ETHER</Data>
+        <Data Name='LogonType'>3</Data>
+        <Data Name='IpAddress'>203.0.113.7</Data>
+    </EventData>
+</Event>"#
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hosho-{}-{}.xml", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_capture_stops_at_its_size_limit() {
+    let path = temp_path("capture-limit");
+    let first = event(4625, 1);
+    let capture = Capture::open(&path, first.len() as u64 + 10).unwrap();
+
+    assert!(capture.record(&first));
+    assert!(!capture.record(&event(4625, 2)));
+    assert!(
+        !capture.record("<Event/>"),
+        "nothing is captured once the limit is hit"
+    );
+
+    let captured = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(split_events(&captured), vec![first]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_capture_writes_each_record_once() {
+    let path = temp_path("capture-dedup");
+    let capture = Capture::open(&path, 1024 * 1024).unwrap();
+
+    assert!(capture.record(&event(4625, 1)));
+    assert!(!capture.record(&event(4625, 1)), "a re-polled record");
+    assert!(capture.record(&event(4625, 2)));
+    let unnumbered = event(4625, 3).replace("<EventRecordID>3</EventRecordID>", "");
+    assert!(capture.record(&unnumbered));
+    assert!(capture.record(&unnumbered));
+
+    let captured = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(split_events(&captured).len(), 4);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_split_events_handles_exports() {
+    let export = format!(
+        "<?xml version=\"1.0\"?><Events>{}{}</Events>",
+        event(4625, 1),
+        event(4624, 2)
+    );
+    assert_eq!(split_events(&export), vec![event(4625, 1), event(4624, 2)]);
+}
+
+#[tokio::test]
+async fn test_replayed_capture_reaches_the_listener() {
+    let path = temp_path("replay");
+    let capture = Capture::open(&path, u64::MAX).unwrap();
+    for (event_id, record_id) in [(4625, 1), (2003, 2), (4625, 3)] {
+        capture.record(&event(event_id, record_id));
+    }
+
+    let source = replay_source(&path, &[4625]).unwrap();
+    let (tx, mut rx) = mpsc::channel(10);
+    LogonListener::new(tx).with_xml_source(source).invoke();
+
+    let mut record_ids = Vec::new();
+    while let Ok(Some(event)) = timeout(Duration::from_millis(500), rx.recv()).await {
+        assert!(matches!(event.details, EventDetails::Login(_)));
+        record_ids.push(event.record_id().unwrap());
+    }
+    assert_eq!(record_ids, vec![1, 3]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,51 @@
+use hosho::listener::channels::ChannelSet;
+use hosho::listener::{DefenderListener, HeartbeatListener, LogonListener, UsbListener};
+use tokio::sync::mpsc;
+
+#[test]
+fn test_nonexistent_channel_is_missing() {
+    let channels = ChannelSet::from_names(["Security", "Microsoft-Windows-PowerShell/Operational"]);
+
+    assert!(channels.contains("security"));
+    assert!(channels.contains("Microsoft-Windows-PowerShell/Operational"));
+    assert!(!channels.contains("Microsoft-Windows-Nonexistent/Operational"));
+}
+
+#[test]
+fn test_listeners_on_missing_channels_are_found() {
+    let channels =
+        ChannelSet::from_names(["Security", "Microsoft-Windows-Windows Defender/Operational"]);
+    let (tx, _rx) = mpsc::channel(1);
+
+    assert_eq!(channels.missing(&LogonListener::new(tx.clone())), None);
+    assert_eq!(channels.missing(&DefenderListener::new(tx.clone())), None);
+    assert_eq!(
+        channels.missing(&UsbListener::new(tx.clone())),
+        Some("Microsoft-Windows-DriverFrameworks-UserMode/Operational")
+    );
+    assert_eq!(
+        channels.missing(&HeartbeatListener::new(tx)),
+        None,
+        "listeners that read no channel always run"
+    );
+}
+
+#[test]
+fn test_configured_logon_channel_is_checked() {
+    let channels = ChannelSet::from_names(["Security"]);
+    let (tx, _rx) = mpsc::channel(1);
+    let listener = LogonListener::new(tx)
+        .with_xpath("Microsoft-Windows-Nonexistent/Operational", "*")
+        .unwrap();
+
+    assert_eq!(
+        channels.missing(&listener),
+        Some("Microsoft-Windows-Nonexistent/Operational")
+    );
+}
+
+#[test]
+fn test_unlisted_channels_are_assumed_present() {
+    let (tx, _rx) = mpsc::channel(1);
+    assert_eq!(ChannelSet::default().missing(&UsbListener::new(tx)), None);
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Duration, Utc};
+use hosho::clock::{Clock, MockClock, SystemClock};
+
+fn start() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2025-07-22T16:25:08Z")
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+#[test]
+fn test_mock_clock_only_moves_when_advanced() {
+    let clock = MockClock::new(start());
+
+    assert_eq!(clock.now(), start());
+    assert_eq!(clock.now(), start());
+
+    clock.advance(Duration::seconds(90));
+    assert_eq!(clock.now(), start() + Duration::seconds(90));
+
+    clock.set(start());
+    assert_eq!(clock.now(), start());
+}
+
+#[test]
+fn test_clock_is_object_safe() {
+    let clocks: Vec<Box<dyn Clock>> =
+        vec![Box::new(SystemClock), Box::new(MockClock::new(start()))];
+
+    assert!(clocks[0].now() > start());
+    assert_eq!(clocks[1].now(), start());
+}
@@ -0,0 +1,231 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use hosho::clock::MockClock;
+use hosho::enrich::severity::{Condition, SeverityRule};
+use hosho::enrich::{Severity, SeverityPolicy};
+use hosho::listener::collapse::{AttemptCollapser, KeyPrefix, collapse_sessions};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent, LogonId};
+
+fn start() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2025-07-22T16:25:00Z")
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn failure(username: &str, source_ip: &str, offset_secs: i64) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new(username, None),
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant: LogonVariant::Network,
+            success: false,
+            event_record_id: Some(0),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        start() + Duration::seconds(offset_secs),
+    )
+}
+
+fn attempt_counts(events: &[Event]) -> Vec<(String, u32)> {
+    events
+        .iter()
+        .filter_map(|event| match &event.details {
+            EventDetails::Login(login) => Some((login.username(), login.attempt_count)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_collapses_run_of_identical_failures() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock.clone());
+
+    let batch: Vec<_> = (0..20)
+        .map(|i| failure("admin", "10.0.0.5", i * 2))
+        .collect();
+    assert!(
+        collapser.process(batch).is_empty(),
+        "the run is still open and should be held"
+    );
+
+    clock.advance(Duration::seconds(61));
+    let released = collapser.process(Vec::new());
+    assert_eq!(attempt_counts(&released), vec![("admin".to_string(), 20)]);
+    assert_eq!(
+        released[0].timestamp,
+        start(),
+        "representative is the first attempt"
+    );
+}
+
+#[test]
+fn test_different_cause_ends_run() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock);
+
+    let released = collapser.process(vec![
+        failure("admin", "10.0.0.5", 0),
+        failure("admin", "10.0.0.5", 1),
+        failure("admin", "10.0.0.9", 2),
+        failure("guest", "10.0.0.9", 3),
+    ]);
+
+    assert_eq!(
+        attempt_counts(&released),
+        vec![("admin".to_string(), 2), ("admin".to_string(), 1)]
+    );
+    assert_eq!(
+        attempt_counts(&collapser.flush().into_iter().collect::<Vec<_>>()),
+        vec![("guest".to_string(), 1)]
+    );
+}
+
+#[test]
+fn test_failures_outside_window_start_new_run() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock);
+
+    let released = collapser.process(vec![
+        failure("admin", "10.0.0.5", 0),
+        failure("admin", "10.0.0.5", 30),
+        failure("admin", "10.0.0.5", 90),
+    ]);
+
+    assert_eq!(attempt_counts(&released), vec![("admin".to_string(), 2)]);
+}
+
+#[test]
+fn test_non_logon_events_pass_through() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock);
+
+    let released = collapser.process(vec![failure("admin", "10.0.0.5", 0), Event::self_test()]);
+
+    assert_eq!(released.len(), 1);
+    assert!(matches!(released[0].details, EventDetails::SelfTest));
+}
+
+#[test]
+fn test_different_failure_reason_ends_run() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock);
+
+    let with_reason = |offset_secs, reason: &str| {
+        let mut event = failure("admin", "10.0.0.5", offset_secs);
+        if let EventDetails::Login(login) = &mut event.details {
+            login.failure_reason = Some(reason.to_string());
+        }
+        event
+    };
+    let released = collapser.process(vec![
+        with_reason(0, "%%2313"),
+        with_reason(1, "%%2313"),
+        with_reason(2, "%%2307"),
+    ]);
+
+    assert_eq!(attempt_counts(&released), vec![("admin".to_string(), 2)]);
+}
+
+#[test]
+fn test_key_prefix_aggregates_sources_in_one_subnet() {
+    let clock = Arc::new(MockClock::new(start()));
+    let mut collapser = AttemptCollapser::new(Duration::seconds(60), clock)
+        .with_key_prefix("24,64".parse().unwrap());
+    let policy = SeverityPolicy::empty().with_rule(SeverityRule::new(
+        vec![Condition::AttemptsAtLeast(2)],
+        Severity::High,
+    ));
+
+    let released = collapser.process(vec![
+        failure("admin", "10.0.0.5", 0),
+        failure("admin", "10.0.0.6", 1),
+        failure("admin", "10.0.1.5", 2),
+    ]);
+
+    assert_eq!(attempt_counts(&released), vec![("admin".to_string(), 2)]);
+    assert_eq!(policy.evaluate(&released[0]), Severity::High);
+}
+
+#[test]
+fn test_key_prefix_truncates_each_family() {
+    let prefix: KeyPrefix = "/24,/64".parse().unwrap();
+
+    assert_eq!(
+        prefix.truncate("10.0.0.5".parse().unwrap()),
+        "10.0.0.0".parse::<std::net::IpAddr>().unwrap()
+    );
+    assert_eq!(
+        prefix.truncate("2001:db8:1:2:3:4:5:6".parse().unwrap()),
+        "2001:db8:1:2::".parse::<std::net::IpAddr>().unwrap()
+    );
+    assert!("33,64".parse::<KeyPrefix>().is_err());
+    assert!("24".parse::<KeyPrefix>().is_err());
+}
+
+fn session_logon(username: &str, variant: LogonVariant, logon_id: u64, offset_secs: i64) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            logon_id: Some(LogonId(logon_id)),
+            ..LogonEvent::new(Account::new(username, None), "10.0.0.5", variant, true)
+        }),
+        start() + Duration::seconds(offset_secs),
+    )
+}
+
+fn variants(events: &[Event]) -> Vec<(String, LogonVariant)> {
+    events
+        .iter()
+        .filter_map(|event| match &event.details {
+            EventDetails::Login(login) => Some((login.username(), login.variant.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_network_then_interactive_logon_collapses_to_interactive() {
+    let mut network = session_logon("alice", LogonVariant::Network, 0x1a2b, 0);
+    if let EventDetails::Login(login) = &mut network.details {
+        login.authentication_package = Some("Kerberos".to_string());
+    }
+    let events = collapse_sessions(vec![
+        network,
+        session_logon("alice", LogonVariant::Interactive, 0x1a2b, 1),
+    ]);
+
+    assert_eq!(
+        variants(&events),
+        [("alice".to_string(), LogonVariant::Interactive)]
+    );
+    assert_eq!(events[0].timestamp, start() + Duration::seconds(1));
+    match &events[0].details {
+        EventDetails::Login(login) => {
+            assert_eq!(login.authentication_package.as_deref(), Some("Kerberos"))
+        }
+        other => panic!("expected a login, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_separate_sessions_and_failures_are_not_collapsed() {
+    let events = collapse_sessions(vec![
+        session_logon("alice", LogonVariant::Network, 0x1a2b, 0),
+        failure("bob", "10.0.0.9", 1),
+        session_logon("carol", LogonVariant::RemoteInteractive, 0x3c4d, 2),
+        session_logon("alice", LogonVariant::Network, 0x1a2b, 3),
+    ]);
+
+    assert_eq!(
+        variants(&events),
+        [
+            ("alice".to_string(), LogonVariant::Network),
+            ("bob".to_string(), LogonVariant::Network),
+            ("carol".to_string(), LogonVariant::RemoteInteractive),
+        ]
+    );
+}
@@ -0,0 +1,312 @@
+use chrono::Utc;
+use hosho::listener::dedup::{DedupKey, Watermark, diff_events};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+
+fn logon_event(event_record_id: u64) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new("TESTUSER", None),
+            source_ip: "10.0.0.5".to_string(),
+            source_ip_raw: "10.0.0.5".to_string(),
+            variant: LogonVariant::Network,
+            success: false,
+            event_record_id: Some(event_record_id),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        Utc::now(),
+    )
+}
+
+fn record_ids(events: &[Event]) -> Vec<u64> {
+    events.iter().filter_map(Event::record_id).collect()
+}
+
+#[test]
+fn test_watermark_suppresses_already_seen_events() {
+    let mut watermark = Watermark::new();
+
+    let first = watermark.filter(vec![logon_event(1), logon_event(2), logon_event(3)]);
+    assert_eq!(record_ids(&first), vec![1, 2, 3]);
+
+    let second = watermark.filter(vec![
+        logon_event(1),
+        logon_event(2),
+        logon_event(3),
+        logon_event(4),
+    ]);
+    assert_eq!(record_ids(&second), vec![4]);
+}
+
+#[test]
+fn test_watermark_resets_after_log_clear() {
+    let mut watermark = Watermark::new();
+    watermark.filter(vec![logon_event(500), logon_event(501)]);
+
+    // The log was cleared, and record IDs restarted from 1.
+    let after_clear = watermark.filter(vec![logon_event(1), logon_event(2)]);
+    assert_eq!(record_ids(&after_clear), vec![1, 2]);
+
+    let next = watermark.filter(vec![logon_event(1), logon_event(2), logon_event(3)]);
+    assert_eq!(record_ids(&next), vec![3]);
+}
+
+#[test]
+fn test_watermark_passes_events_without_record_id() {
+    let mut watermark = Watermark::new();
+    watermark.filter(vec![logon_event(10)]);
+
+    let events = watermark.filter(vec![Event::self_test(), logon_event(10)]);
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0].details, EventDetails::SelfTest));
+}
+
+#[test]
+fn test_watermark_never_drops_logons_without_record_id() {
+    let mut unnumbered = logon_event(0);
+    if let EventDetails::Login(login) = &mut unnumbered.details {
+        login.event_record_id = None;
+    }
+
+    let mut watermark = Watermark::new();
+    watermark.filter(vec![logon_event(10)]);
+    for _ in 0..2 {
+        assert_eq!(watermark.filter(vec![unnumbered.clone()]).len(), 1);
+    }
+}
+
+/// The event the log writes first after being cleared.
+fn log_cleared(event_record_id: u64) -> Event {
+    let mut event = logon_event(event_record_id);
+    if let EventDetails::Login(login) = &mut event.details {
+        login.event_id = Some(1102);
+    }
+    event
+}
+
+#[test]
+fn test_watermark_resets_when_the_log_is_cleared_and_refilled_past_it() {
+    let mut watermark = Watermark::new();
+    watermark.filter((1..=5).map(logon_event).collect());
+
+    // Cleared and refilled past record 5 before the next poll, so neither end of the batch moved
+    // backwards.
+    let refilled: Vec<Event> = std::iter::once(log_cleared(1))
+        .chain((2..=8).map(logon_event))
+        .collect();
+    let after_clear = watermark.filter(refilled.clone());
+    assert_eq!(record_ids(&after_clear), (1..=8).collect::<Vec<_>>());
+
+    assert!(watermark.filter(refilled).is_empty());
+}
+
+#[test]
+fn test_watermark_resets_when_the_oldest_record_goes_backwards() {
+    let mut watermark = Watermark::new();
+    // The log had wrapped, so the oldest record left was 100.
+    watermark.filter((100..=105).map(logon_event).collect());
+
+    // Cleared and refilled past record 105, without the clear itself being queried.
+    let refilled: Vec<Event> = (1..=110).map(logon_event).collect();
+    assert_eq!(watermark.filter(refilled.clone()).len(), 110);
+
+    assert!(watermark.filter(refilled).is_empty());
+}
+
+#[test]
+fn test_watermark_ignores_a_clear_it_has_already_seen() {
+    let mut watermark = Watermark::new();
+    let log: Vec<Event> = std::iter::once(log_cleared(1))
+        .chain((2..=5).map(logon_event))
+        .collect();
+    watermark.filter(log.clone());
+
+    let mut grown = log;
+    grown.push(logon_event(6));
+    assert_eq!(record_ids(&watermark.filter(grown)), vec![6]);
+}
+
+fn forwarded_event(event_record_id: u64, computer: &str) -> Event {
+    let mut event = logon_event(event_record_id);
+    event.computer = Some(computer.to_string());
+    event
+}
+
+#[test]
+fn test_record_id_plus_computer_keeps_colliding_ids_from_different_hosts() {
+    let mut dedup = DedupKey::RecordIdPlusComputer.strategy();
+
+    let first = dedup.filter(vec![
+        forwarded_event(100, "ws1.corp.local"),
+        forwarded_event(100, "ws2.corp.local"),
+    ]);
+    assert_eq!(first.len(), 2);
+
+    let second = dedup.filter(vec![
+        forwarded_event(100, "ws1.corp.local"),
+        forwarded_event(100, "ws2.corp.local"),
+        forwarded_event(101, "ws2.corp.local"),
+    ]);
+    assert_eq!(record_ids(&second), vec![101]);
+    assert_eq!(second[0].computer.as_deref(), Some("ws2.corp.local"));
+}
+
+#[test]
+fn test_record_id_drops_colliding_ids_from_different_hosts() {
+    let mut dedup = DedupKey::RecordId.strategy();
+
+    dedup.filter(vec![forwarded_event(100, "ws1.corp.local")]);
+    let events = dedup.filter(vec![forwarded_event(100, "ws2.corp.local")]);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_content_hash_suppresses_identical_events() {
+    let mut dedup = DedupKey::ContentHash.strategy();
+    let event = forwarded_event(7, "ws1.corp.local");
+
+    assert_eq!(dedup.filter(vec![event.clone()]).len(), 1);
+    assert!(dedup.filter(vec![event.clone()]).is_empty());
+    assert_eq!(
+        dedup
+            .filter(vec![forwarded_event(8, "ws1.corp.local")])
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_content_hash_follows_a_sliding_query_window() {
+    let start = Utc::now();
+    let event = |n: u64| {
+        let mut event = forwarded_event(n, "ws1.corp.local");
+        event.timestamp = start + chrono::Duration::seconds(n as i64);
+        event
+    };
+    let mut dedup = DedupKey::ContentHash.strategy();
+
+    let mut forwarded = Vec::new();
+    for poll in 0..50 {
+        let window = (poll..poll + 20).map(event).collect();
+        forwarded.extend(record_ids(&dedup.filter(window)));
+    }
+    assert_eq!(
+        forwarded,
+        (0..69).collect::<Vec<_>>(),
+        "each event exactly once"
+    );
+}
+
+#[test]
+fn test_dedup_key_parses_from_cli_names() {
+    assert_eq!("record-id".parse(), Ok(DedupKey::RecordId));
+    assert_eq!(
+        "record-id-plus-computer".parse(),
+        Ok(DedupKey::RecordIdPlusComputer)
+    );
+    assert_eq!("content-hash".parse(), Ok(DedupKey::ContentHash));
+    assert!("bogus".parse::<DedupKey>().is_err());
+}
+
+#[test]
+fn test_diff_events_keeps_only_events_past_overlap() {
+    let (fresh, watermark) = diff_events(
+        Watermark::at(3),
+        vec![logon_event(2), logon_event(3), logon_event(4)],
+    );
+    assert_eq!(record_ids(&fresh), vec![4]);
+    assert_eq!(watermark.last_record_id(), Some(4));
+
+    let (fresh, watermark) = diff_events(Watermark::at(4), vec![logon_event(3), logon_event(4)]);
+    assert!(fresh.is_empty());
+    assert_eq!(watermark.last_record_id(), Some(4));
+}
+
+#[test]
+fn test_diff_events_without_overlap_keeps_everything() {
+    let (fresh, watermark) = diff_events(Watermark::at(3), vec![logon_event(7), logon_event(8)]);
+    assert_eq!(record_ids(&fresh), vec![7, 8]);
+    assert_eq!(watermark.last_record_id(), Some(8));
+}
+
+#[test]
+fn test_diff_events_from_empty_previous() {
+    let (fresh, watermark) = diff_events(Watermark::new(), vec![logon_event(5), logon_event(6)]);
+    assert_eq!(record_ids(&fresh), vec![5, 6]);
+    assert_eq!(watermark.last_record_id(), Some(6));
+
+    let (fresh, watermark) = diff_events(Watermark::new(), Vec::new());
+    assert!(fresh.is_empty());
+    assert_eq!(watermark.last_record_id(), None);
+
+    let (fresh, watermark) = diff_events(Watermark::at(9), vec![Event::self_test()]);
+    assert_eq!(fresh.len(), 1);
+    assert_eq!(watermark.last_record_id(), Some(9));
+}
+
+#[test]
+fn test_capped_filter_returns_the_rest_on_later_calls() {
+    for key in [
+        DedupKey::RecordId,
+        DedupKey::RecordIdPlusComputer,
+        DedupKey::ContentHash,
+    ] {
+        let mut dedup = key.strategy();
+        let log: Vec<_> = (1..=5).map(logon_event).collect();
+
+        let mut batches = Vec::new();
+        loop {
+            let batch = record_ids(&dedup.filter_at_most(log.clone(), 2));
+            if batch.is_empty() {
+                break;
+            }
+            assert!(batch.len() <= 2, "{:?} returned {:?}", key, batch);
+            batches.push(batch);
+        }
+        assert_eq!(
+            batches,
+            vec![vec![1, 2], vec![3, 4], vec![5]],
+            "{:?} should page through the log oldest first",
+            key
+        );
+    }
+}
+
+#[test]
+fn test_capped_filter_caps_the_whole_batch_across_computers() {
+    let start = Utc::now();
+    let log: Vec<_> = ["ws1", "ws2", "ws3"]
+        .into_iter()
+        .flat_map(|computer| {
+            (1..=3).map(move |id| {
+                let mut event = logon_event(id);
+                event.computer = Some(computer.to_string());
+                event.timestamp = start + chrono::Duration::seconds(id as i64);
+                event
+            })
+        })
+        .collect();
+    let mut dedup = DedupKey::RecordIdPlusComputer.strategy();
+
+    let mut seen = Vec::new();
+    loop {
+        let batch = dedup.filter_at_most(log.clone(), 4);
+        if batch.is_empty() {
+            break;
+        }
+        assert!(batch.len() <= 4, "returned {} events", batch.len());
+        seen.extend(
+            batch
+                .iter()
+                .map(|event| (event.computer.clone().unwrap(), event.record_id().unwrap())),
+        );
+    }
+
+    seen.sort();
+    let expected: Vec<_> = ["ws1", "ws2", "ws3"]
+        .into_iter()
+        .flat_map(|computer| (1..=3).map(move |id| (computer.to_string(), id)))
+        .collect();
+    assert_eq!(seen, expected, "every event exactly once");
+}
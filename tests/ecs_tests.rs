@@ -0,0 +1,112 @@
+use chrono::{TimeZone, Utc};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent, RenderingInfo};
+use hosho::sink::ecs::to_ecs;
+
+fn sample_logon() -> Event {
+    let mut event = Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new("TESTUSER", Some("WORKGROUP")),
+            source_ip: "192.168.1.50".to_string(),
+            source_ip_raw: "192.168.1.50".to_string(),
+            variant: LogonVariant::RemoteInteractive,
+            success: false,
+            event_record_id: Some(4242),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    );
+    event.computer = Some("ws1.corp.local".to_string());
+    event
+}
+
+#[test]
+fn test_logon_maps_to_ecs_field_names() {
+    let doc = to_ecs(&sample_logon());
+
+    assert_eq!(doc["event"]["action"], "logon-failed");
+    assert_eq!(doc["event"]["outcome"], "failure");
+    assert_eq!(doc["source"]["ip"], "192.168.1.50");
+    assert_eq!(doc["user"]["name"], "TESTUSER");
+    assert_eq!(doc["user"]["domain"], "WORKGROUP");
+    assert_eq!(doc["winlog"]["event_id"], "4625");
+    assert_eq!(doc["winlog"]["record_id"], 4242);
+    assert_eq!(doc["winlog"]["logon"]["type"], "RemoteInteractive");
+    assert_eq!(doc["host"]["name"], "ws1.corp.local");
+    assert_eq!(doc["@timestamp"], "2025-06-01T12:00:00+00:00");
+}
+
+#[test]
+fn test_logon_keeps_its_own_event_id() {
+    let mut event = sample_logon();
+    if let EventDetails::Login(login_event) = &mut event.details {
+        login_event.success = true;
+        login_event.event_id = Some(4648);
+    }
+
+    let doc = to_ecs(&event);
+    assert_eq!(doc["event"]["code"], "4648");
+    assert_eq!(doc["winlog"]["event_id"], "4648");
+}
+
+#[test]
+fn test_placeholder_source_ip_is_omitted() {
+    let mut event = sample_logon();
+    if let EventDetails::Login(login_event) = &mut event.details {
+        login_event.source_ip = "-".to_string();
+    }
+
+    let doc = to_ecs(&event);
+    assert!(doc.get("source").is_none());
+}
+
+#[test]
+fn test_successful_logon_maps_to_success_outcome() {
+    let mut event = sample_logon();
+    if let EventDetails::Login(login_event) = &mut event.details {
+        login_event.success = true;
+    }
+
+    let doc = to_ecs(&event);
+    assert_eq!(doc["event"]["outcome"], "success");
+    assert_eq!(doc["winlog"]["event_id"], "4624");
+}
+
+#[test]
+fn test_message_prefers_rendered_description() {
+    let mut event = sample_logon();
+    assert!(doc_message(&event).starts_with("Event: Failed Login"));
+
+    event.rendering = Some(RenderingInfo {
+        message: Some("An account failed to log on.".to_string()),
+        level: None,
+        task: None,
+    });
+    assert_eq!(doc_message(&event), "An account failed to log on.");
+}
+
+fn doc_message(event: &Event) -> String {
+    to_ecs(event)["message"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_query_stats_map_to_a_metric() {
+    let event = Event::new(
+        EventDetails::QueryStats {
+            channel: "Security".to_string(),
+            duration: std::time::Duration::from_millis(42),
+            event_count: 17,
+            parse_errors: 1,
+        },
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    );
+    let doc = to_ecs(&event);
+
+    assert_eq!(doc["event"]["action"], "query-executed");
+    assert_eq!(doc["event"]["kind"], "metric");
+    assert_eq!(doc["event"]["duration"], 42_000_000);
+    assert_eq!(doc["winlog"]["channel"], "Security");
+    assert_eq!(doc["hosho"]["query"]["event_count"], 17);
+    assert_eq!(doc["hosho"]["query"]["parse_errors"], 1);
+}
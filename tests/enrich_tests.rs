@@ -0,0 +1,239 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use hosho::enrich::reverse_dns::MAX_PENDING_LOOKUPS;
+use hosho::enrich::{CurrentUserEnricher, FirstSeenEnricher, ReverseDnsEnricher};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::pipeline::Transform;
+
+fn logon(username: &str) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new(username, None),
+            source_ip: "127.0.0.1".to_string(),
+            source_ip_raw: "127.0.0.1".to_string(),
+            variant: LogonVariant::Interactive,
+            success: false,
+            event_record_id: Some(1),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        Utc::now(),
+    )
+}
+
+fn is_current_user(event: &Event) -> bool {
+    match &event.details {
+        EventDetails::Login(login) => login.is_current_user,
+        _ => panic!("expected a logon event"),
+    }
+}
+
+#[test]
+fn test_current_user_matches_case_insensitively() {
+    let enricher = CurrentUserEnricher::fixed(Some("xevion@ETHER".to_string()));
+
+    let mut mine = logon("Xevion@ether");
+    let mut theirs = logon("guest@ETHER");
+    enricher.enrich(&mut mine);
+    enricher.enrich(&mut theirs);
+
+    assert!(is_current_user(&mine));
+    assert!(!is_current_user(&theirs));
+}
+
+#[test]
+fn test_no_console_session_is_never_current_user() {
+    let enricher = CurrentUserEnricher::fixed(None);
+
+    let mut event = logon("xevion@ETHER");
+    enricher.enrich(&mut event);
+
+    assert!(!is_current_user(&event));
+}
+
+fn logon_from(source_ip: &str) -> Event {
+    let mut event = logon("admin");
+    if let EventDetails::Login(login) = &mut event.details {
+        login.source_ip = source_ip.to_string();
+    }
+    event
+}
+
+fn source_hostname(event: &Event) -> Option<&str> {
+    match &event.details {
+        EventDetails::Login(login) => login.source_hostname.as_deref(),
+        _ => panic!("expected a logon event"),
+    }
+}
+
+#[tokio::test]
+async fn test_reverse_dns_caches_lookups() {
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&lookups);
+    let enricher = ReverseDnsEnricher::with_resolver(
+        Duration::from_secs(1),
+        Arc::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Some("scanner.example.net".to_string())
+        }),
+    );
+
+    for _ in 0..3 {
+        let mut event = logon_from("203.0.113.9");
+        enricher.enrich(&mut event).await;
+        assert_eq!(source_hostname(&event), Some("scanner.example.net"));
+    }
+    assert_eq!(lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_reverse_dns_skips_private_and_placeholder_addresses() {
+    let enricher = ReverseDnsEnricher::with_resolver(
+        Duration::from_secs(1),
+        Arc::new(|_| panic!("should not resolve")),
+    );
+
+    for source_ip in ["10.0.0.5", "127.0.0.1", "-", "N/A"] {
+        let mut event = logon_from(source_ip);
+        enricher.enrich(&mut event).await;
+        assert_eq!(source_hostname(&event), None);
+    }
+}
+
+#[tokio::test]
+async fn test_reverse_dns_failure_leaves_hostname_unset() {
+    let enricher = ReverseDnsEnricher::with_resolver(Duration::from_secs(1), Arc::new(|_| None));
+
+    let mut event = logon_from("198.51.100.7");
+    enricher.enrich(&mut event).await;
+    assert_eq!(source_hostname(&event), None);
+}
+
+#[tokio::test]
+async fn test_reverse_dns_misses_expire() {
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&lookups);
+    let enricher = ReverseDnsEnricher::with_resolver(
+        Duration::from_secs(1),
+        Arc::new(move |_| {
+            // The resolver is down for the first lookup only.
+            (counter.fetch_add(1, Ordering::SeqCst) > 0).then(|| "scanner.example.net".to_string())
+        }),
+    )
+    .with_miss_ttl(Duration::from_millis(50));
+
+    for _ in 0..2 {
+        let mut event = logon_from("203.0.113.9");
+        enricher.enrich(&mut event).await;
+        assert_eq!(source_hostname(&event), None);
+    }
+    assert_eq!(lookups.load(Ordering::SeqCst), 1, "the miss is cached");
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let mut event = logon_from("203.0.113.9");
+    enricher.enrich(&mut event).await;
+    assert_eq!(source_hostname(&event), Some("scanner.example.net"));
+    assert_eq!(lookups.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_reverse_dns_bounds_pending_lookups() {
+    // Every lookup hangs until the gate opens.
+    let gate = Arc::new((Mutex::new(false), Condvar::new()));
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let enricher = {
+        let gate = Arc::clone(&gate);
+        let lookups = Arc::clone(&lookups);
+        ReverseDnsEnricher::with_resolver(
+            Duration::from_millis(10),
+            Arc::new(move |ip| {
+                lookups.fetch_add(1, Ordering::SeqCst);
+                let (open, opened) = &*gate;
+                let _open = opened
+                    .wait_while(open.lock().unwrap(), |open| !*open)
+                    .unwrap();
+                Some(format!("host-{}.example.net", ip))
+            }),
+        )
+    };
+
+    for i in 0..MAX_PENDING_LOOKUPS * 3 {
+        let mut event = logon_from(&format!("203.0.113.{}", i + 1));
+        enricher.enrich(&mut event).await;
+        assert_eq!(source_hostname(&event), None);
+    }
+    assert!(lookups.load(Ordering::SeqCst) <= MAX_PENDING_LOOKUPS);
+
+    *gate.0.lock().unwrap() = true;
+    gate.1.notify_all();
+}
+
+fn first_seen(event: &Event) -> (bool, bool) {
+    match &event.details {
+        EventDetails::Login(login) => (login.first_seen_ip, login.first_seen_user),
+        _ => panic!("expected a logon event"),
+    }
+}
+
+#[test]
+fn test_first_seen_only_on_first_occurrence() {
+    let mut enricher = FirstSeenEnricher::in_memory();
+
+    let mut event = logon_from("203.0.113.9");
+    enricher.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (true, true));
+
+    let mut event = logon_from("203.0.113.9");
+    enricher.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (false, false));
+
+    let mut event = logon_from("198.51.100.7");
+    enricher.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (true, false));
+}
+
+#[test]
+fn test_first_seen_persists_across_restarts() {
+    let path = std::env::temp_dir().join(format!("hosho-first-seen-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut enricher = FirstSeenEnricher::open(&path).unwrap();
+    let mut event = logon_from("203.0.113.9");
+    enricher.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (true, true));
+
+    let mut reopened = FirstSeenEnricher::open(&path).unwrap();
+    let mut event = logon_from("203.0.113.9");
+    reopened.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (false, false));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_first_seen_appends_only_new_entries() {
+    let path = std::env::temp_dir().join(format!(
+        "hosho-first-seen-append-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let enricher = Mutex::new(FirstSeenEnricher::open(&path).unwrap());
+    for source_ip in ["203.0.113.9", "203.0.113.9", "198.51.100.7"] {
+        enricher.transform(logon_from(source_ip)).await.unwrap();
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 3, "two addresses and one user");
+
+    let mut reopened = FirstSeenEnricher::open(&path).unwrap();
+    let mut event = logon_from("198.51.100.7");
+    reopened.enrich(&mut event).unwrap();
+    assert_eq!(first_seen(&event), (false, false));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,45 @@
+use hosho::errors::SentinelError;
+
+#[test]
+fn test_query_failures_map_to_distinct_variants() {
+    let classify = |os_error| SentinelError::from_query_failure("Security", os_error, "failed");
+
+    assert!(matches!(classify(Some(5)), SentinelError::AccessDenied(_)));
+    assert!(matches!(
+        classify(Some(15007)),
+        SentinelError::ChannelNotFound(_)
+    ));
+    assert!(matches!(
+        classify(Some(15001)),
+        SentinelError::QueryMalformed(_)
+    ));
+    assert!(matches!(classify(Some(1722)), SentinelError::Rpc(_)));
+    assert!(matches!(classify(None), SentinelError::EventQueryError(_)));
+}
+
+#[test]
+fn test_access_denied_recognized_from_message() {
+    let error = SentinelError::from_query_failure("Security", None, "Access is denied.");
+    assert!(matches!(error, SentinelError::AccessDenied(channel) if channel == "Security"));
+}
+
+#[test]
+fn test_access_denied_names_the_channel_queried() {
+    let error =
+        SentinelError::from_query_failure("Microsoft-Windows-Sysmon/Operational", Some(5), "");
+    let message = error.to_string();
+    assert!(
+        message.contains("Microsoft-Windows-Sysmon/Operational"),
+        "{}",
+        message
+    );
+    assert!(!message.contains("Security"), "{}", message);
+}
+
+#[test]
+fn test_only_rpc_and_generic_failures_are_transient() {
+    assert!(SentinelError::Rpc("unavailable".to_string()).is_transient());
+    assert!(!SentinelError::AccessDenied("Security".to_string()).is_transient());
+    assert!(!SentinelError::ChannelNotFound("Foo".to_string()).is_transient());
+    assert!(!SentinelError::QueryMalformed("Security".to_string()).is_transient());
+}
@@ -0,0 +1,28 @@
+use hosho::listener::event_ids::parse_event_ids;
+
+#[test]
+fn test_expands_lists_and_ranges() {
+    assert_eq!(
+        parse_event_ids("4624-4625,4634;4648").unwrap(),
+        vec![4624, 4625, 4634, 4648]
+    );
+    assert_eq!(parse_event_ids(" 4625 ").unwrap(), vec![4625]);
+}
+
+#[test]
+fn test_sorts_and_deduplicates() {
+    assert_eq!(
+        parse_event_ids("4648,4624-4626,4625,4648").unwrap(),
+        vec![4624, 4625, 4626, 4648]
+    );
+}
+
+#[test]
+fn test_rejects_invalid_specs() {
+    assert!(parse_event_ids("").is_err());
+    assert!(parse_event_ids("0").is_err());
+    assert!(parse_event_ids("65536").is_err());
+    assert!(parse_event_ids("4625-4624").is_err());
+    assert!(parse_event_ids("46x5").is_err());
+    assert!(parse_event_ids("4624-").is_err());
+}
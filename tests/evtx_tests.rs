@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+use chrono::{TimeZone, Utc};
+use hosho::listener::EvtxDirListener;
+use hosho::listener::evtx::chronological;
+use hosho::listener::xpath::XPathQuery;
+use tokio::sync::mpsc;
+
+#[test]
+fn test_files_lists_only_evtx_archives() {
+    let dir = std::env::temp_dir().join(format!("hosho-evtx-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("nested.evtx")).unwrap();
+    for name in ["b.evtx", "A.EVTX", "notes.txt"] {
+        std::fs::write(dir.join(name), b"").unwrap();
+    }
+
+    let (tx, _rx) = mpsc::channel(1);
+    let files = EvtxDirListener::new(tx, &dir).files().unwrap();
+    assert_eq!(files, vec![dir.join("A.EVTX"), dir.join("b.evtx")]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_archives_are_ordered_by_oldest_event() {
+    let at = |hour| Some(Utc.with_ymd_and_hms(2025, 6, 1, hour, 0, 0).unwrap());
+    let files = chronological(vec![
+        (PathBuf::from("Archive-Security-2.evtx"), at(12)),
+        (PathBuf::from("empty.evtx"), None),
+        (PathBuf::from("Archive-Security-1.evtx"), at(6)),
+        (PathBuf::from("Archive-Security-10.evtx"), at(18)),
+    ]);
+    assert_eq!(
+        files,
+        [
+            "Archive-Security-1.evtx",
+            "Archive-Security-2.evtx",
+            "Archive-Security-10.evtx",
+            "empty.evtx",
+        ]
+        .map(PathBuf::from)
+    );
+}
+
+#[test]
+fn test_file_query_points_event_viewer_at_the_file() {
+    let query = XPathQuery::events_in_file(&PathBuf::from("archive.evtx"), &[4625]);
+    assert!(
+        query
+            .to_string()
+            .contains("<Select Path=\"file://archive.evtx\">*[System[(EventID=4625)]]</Select>")
+    );
+}
@@ -0,0 +1,140 @@
+use std::io::Write;
+
+use chrono::{Duration, FixedOffset, TimeZone, Utc};
+use hosho::enrich::FirewallCorrelator;
+use hosho::enrich::firewall::{FirewallAction, parse_firewall_log};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::pipeline::Transform;
+
+const LOG: &str = "\
+#Version: 1.5
+#Software: Microsoft Windows Firewall
+#Time Format: Local
+#Fields: date time action protocol src-ip dst-ip src-port dst-port size tcpflags tcpsyn tcpack tcpwin icmptype icmpcode info path pid
+2025-06-02 12:00:01 DROP TCP 203.0.113.7 10.0.0.5 51234 3389 0 - 0 0 0 - - - RECEIVE 4
+2025-06-02 12:00:03 ALLOW TCP 198.51.100.9 10.0.0.5 50000 445 0 - 0 0 0 - - - RECEIVE 4
+2025-06-02 12:00:04 ALLOW TCP 10.0.0.5 203.0.113.7 443 51235 0 - 0 0 0 - - - SEND 4
+2025-06-02 12:00:05 INFO-EVENTS-LOST - - - - - - - - - - - - 12 - -
+";
+
+fn utc() -> FixedOffset {
+    FixedOffset::east_opt(0).unwrap()
+}
+
+fn logon(source_ip: &str, second: u32) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new("admin", None),
+            source_ip,
+            LogonVariant::RemoteInteractive,
+            false,
+        )),
+        Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, second).unwrap(),
+    )
+}
+
+fn firewall_action(event: &Event) -> Option<FirewallAction> {
+    match &event.details {
+        EventDetails::Login(login) => login.firewall_action,
+        _ => None,
+    }
+}
+
+fn log_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("hosho-{}-{}.log", name, std::process::id()))
+}
+
+#[test]
+fn test_parse_keeps_inbound_allow_and_drop() {
+    let entries = parse_firewall_log(LOG, &FixedOffset::east_opt(2 * 3600).unwrap());
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].action, FirewallAction::Drop);
+    assert_eq!(
+        entries[0].source,
+        "203.0.113.7".parse::<std::net::IpAddr>().unwrap()
+    );
+    assert_eq!(
+        entries[0].timestamp,
+        Utc.with_ymd_and_hms(2025, 6, 2, 10, 0, 1).unwrap()
+    );
+    assert_eq!(entries[1].action, FirewallAction::Allow);
+}
+
+#[test]
+fn test_logon_is_tagged_within_window() {
+    let path = log_path("firewall");
+    std::fs::write(&path, LOG).unwrap();
+    let correlator = FirewallCorrelator::new(&path, Duration::seconds(5)).with_utc_offset(utc());
+
+    let mut dropped = logon("203.0.113.7", 3);
+    let mut late = logon("198.51.100.9", 30);
+    let mut unseen = logon("192.0.2.1", 3);
+    correlator.enrich(&mut dropped);
+    correlator.enrich(&mut late);
+    correlator.enrich(&mut unseen);
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(firewall_action(&dropped), Some(FirewallAction::Drop));
+    assert_eq!(firewall_action(&late), None);
+    assert_eq!(firewall_action(&unseen), None);
+}
+
+#[test]
+fn test_missing_log_leaves_logons_untagged() {
+    let correlator = FirewallCorrelator::new(log_path("firewall-missing"), Duration::seconds(5));
+
+    let mut event = logon("203.0.113.7", 1);
+    correlator.enrich(&mut event);
+    assert_eq!(firewall_action(&event), None);
+}
+
+#[test]
+fn test_rotated_log_is_followed() {
+    let path = log_path("firewall-rotated");
+    let rotated = path.with_extension("log.old");
+    let (header, lines) = LOG.split_at(LOG.find("2025").unwrap());
+    let mut lines = lines.lines();
+    std::fs::write(&path, format!("{}{}\n", header, lines.next().unwrap())).unwrap();
+    let correlator = FirewallCorrelator::new(&path, Duration::seconds(5)).with_utc_offset(utc());
+
+    let mut before = logon("203.0.113.7", 1);
+    correlator.enrich(&mut before);
+    assert_eq!(firewall_action(&before), Some(FirewallAction::Drop));
+
+    // The firewall logs one more connection, then Windows renames the full log and starts a new
+    // one.
+    let mut old = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap();
+    writeln!(old, "{}", lines.next().unwrap()).unwrap();
+    drop(old);
+    std::fs::rename(&path, &rotated).unwrap();
+    std::fs::write(&path, header).unwrap();
+
+    let mut after = logon("198.51.100.9", 3);
+    correlator.enrich(&mut after);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&rotated);
+
+    assert_eq!(firewall_action(&after), Some(FirewallAction::Allow));
+}
+
+#[tokio::test]
+async fn test_logon_read_out_of_order_still_matches() {
+    let path = log_path("firewall-out-of-order");
+    std::fs::write(&path, LOG).unwrap();
+    let correlator = FirewallCorrelator::new(&path, Duration::seconds(5)).with_utc_offset(utc());
+
+    // A later logon is looked up first, as when listeners deliver out of order.
+    let mut newer = logon("192.0.2.1", 0);
+    newer.timestamp += Duration::minutes(10);
+    let newer = correlator.transform(newer).await.unwrap();
+    let older = correlator.transform(logon("203.0.113.7", 2)).await.unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(firewall_action(&newer), None);
+    assert_eq!(firewall_action(&older), Some(FirewallAction::Drop));
+}
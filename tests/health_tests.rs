@@ -0,0 +1,31 @@
+use chrono::{TimeZone, Utc};
+use hosho::listener::health::HealthTracker;
+
+#[test]
+fn test_failures_accumulate_until_a_success() {
+    let tracker = HealthTracker::new();
+    assert!(tracker.snapshot().is_healthy());
+
+    tracker.record_failure("Access denied");
+    tracker.record_failure("RPC server unavailable");
+    let health = tracker.snapshot();
+    assert!(!health.is_healthy());
+    assert_eq!(health.consecutive_errors, 2);
+    assert_eq!(health.last_error.as_deref(), Some("RPC server unavailable"));
+    assert_eq!(health.last_success, None);
+
+    let at = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+    tracker.record_success(at);
+    let health = tracker.snapshot();
+    assert!(health.is_healthy());
+    assert_eq!(health.last_success, Some(at));
+}
+
+#[test]
+fn test_clones_share_health() {
+    let tracker = HealthTracker::new();
+    let clone = tracker.clone();
+
+    clone.record_failure("boom");
+    assert_eq!(tracker.snapshot().consecutive_errors, 1);
+}
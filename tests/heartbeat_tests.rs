@@ -0,0 +1,20 @@
+use hosho::listener::{EventDetails, EventListener, HeartbeatListener};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn test_heartbeats_are_numbered_sequentially() {
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = HeartbeatListener::new(tx);
+
+    let mut seqs = Vec::new();
+    for _ in 0..3 {
+        listener.clone().invoke();
+        let event = rx.recv().await.expect("heartbeat should be sent");
+        match event.details {
+            EventDetails::Heartbeat { seq } => seqs.push(seq),
+            other => panic!("expected a heartbeat, got {:?}", other),
+        }
+        assert_eq!(event.record_id(), None);
+    }
+    assert_eq!(seqs, vec![1, 2, 3]);
+}
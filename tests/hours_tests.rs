@@ -0,0 +1,30 @@
+use chrono::{TimeZone, Utc};
+use hosho::enrich::BusinessHours;
+
+#[test]
+fn test_business_hours_follow_daylight_saving() {
+    let hours = BusinessHours::new(8, 18).with_display_tz("Europe/Berlin".parse().unwrap());
+
+    // 08:30 in Berlin both times, at UTC+1 in winter and UTC+2 in summer.
+    let winter = Utc.with_ymd_and_hms(2025, 1, 15, 7, 30, 0).unwrap();
+    let summer = Utc.with_ymd_and_hms(2025, 7, 15, 6, 30, 0).unwrap();
+    assert!(hours.contains(winter));
+    assert!(hours.contains(summer));
+
+    let summer_early = Utc.with_ymd_and_hms(2025, 7, 15, 5, 30, 0).unwrap();
+    assert!(!hours.contains(summer_early));
+}
+
+#[test]
+fn test_business_hours_wrap_past_midnight() {
+    let night_shift = BusinessHours::new(22, 6).with_display_tz("UTC".parse().unwrap());
+
+    for hour in [22, 23, 0, 5] {
+        let timestamp = Utc.with_ymd_and_hms(2025, 6, 2, hour, 0, 0).unwrap();
+        assert!(night_shift.contains(timestamp), "{}:00", hour);
+    }
+    for hour in [6, 12, 21] {
+        let timestamp = Utc.with_ymd_and_hms(2025, 6, 2, hour, 0, 0).unwrap();
+        assert!(!night_shift.contains(timestamp), "{}:00", hour);
+    }
+}
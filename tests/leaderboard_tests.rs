@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use hosho::clock::{Clock, MockClock};
+use hosho::leaderboard::Leaderboard;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+
+fn failure(username: &str, source_ip: &str, at: DateTime<Utc>) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new(username, None),
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant: LogonVariant::Network,
+            success: false,
+            event_record_id: Some(1),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        at,
+    )
+}
+
+#[test]
+fn test_leaderboard_ranks_by_failures() {
+    let now = Utc::now();
+    let mut leaderboard = Leaderboard::new(Duration::hours(1), Arc::new(MockClock::new(now)));
+
+    for _ in 0..3 {
+        leaderboard.record(&failure("admin", "203.0.113.7", now));
+    }
+    leaderboard.record(&failure("guest", "198.51.100.2", now));
+    leaderboard.record(&failure("admin", "-", now));
+
+    assert_eq!(
+        leaderboard.top_sources(10),
+        vec![
+            ("203.0.113.7".to_string(), 3),
+            ("198.51.100.2".to_string(), 1)
+        ]
+    );
+    assert_eq!(leaderboard.top_users(1), vec![("admin".to_string(), 4)]);
+
+    let json = leaderboard.to_json(1);
+    assert_eq!(json["sources"][0]["source_ip"], "203.0.113.7");
+    assert_eq!(json["users"][0]["failures"], 4);
+}
+
+#[test]
+fn test_leaderboard_ages_out_old_failures() {
+    let clock = Arc::new(MockClock::new(Utc::now()));
+    let mut leaderboard = Leaderboard::new(Duration::hours(1), clock.clone());
+
+    leaderboard.record(&failure("admin", "203.0.113.7", clock.now()));
+    clock.advance(Duration::minutes(45));
+    leaderboard.record(&failure("guest", "198.51.100.2", clock.now()));
+    clock.advance(Duration::minutes(30));
+
+    assert_eq!(
+        leaderboard.top_sources(10),
+        vec![("198.51.100.2".to_string(), 1)]
+    );
+    assert_eq!(leaderboard.top_users(10), vec![("guest".to_string(), 1)]);
+}
+
+#[test]
+fn test_leaderboard_windows_by_when_failures_happened() {
+    let now = Utc::now();
+    let mut leaderboard = Leaderboard::new(Duration::hours(1), Arc::new(MockClock::new(now)));
+
+    // A backlog arriving late, and out of order.
+    leaderboard.record(&failure(
+        "guest",
+        "198.51.100.2",
+        now - Duration::minutes(10),
+    ));
+    leaderboard.record(&failure("admin", "203.0.113.7", now - Duration::hours(2)));
+    leaderboard.record(&failure(
+        "guest",
+        "198.51.100.2",
+        now - Duration::minutes(50),
+    ));
+
+    assert_eq!(
+        leaderboard.top_sources(10),
+        vec![("198.51.100.2".to_string(), 2)]
+    );
+    assert_eq!(leaderboard.top_users(10), vec![("guest".to_string(), 2)]);
+}
@@ -0,0 +1,407 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use hosho::clock::MockClock;
+use hosho::listener::bookmark::Bookmark;
+use hosho::listener::logon::parse_login_event;
+use hosho::listener::xpath::QueryDirection;
+use hosho::listener::{
+    DeliveryMode, Event, EventDetails, EventListener, LogonListener, Subscriber, XmlSource,
+};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tokio_stream::StreamExt;
+
+fn failed_logon(record_id: u32, user: &str) -> String {
+    format!(
+        r#"<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>4625</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:25:08.8954670Z'/>
+        <EventRecordID>{record_id}</EventRecordID>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='TargetUserName'>{user}</Data>
+        <Data Name='TargetDomainName'>ETHER</Data>
+        <Data Name='LogonType'>3</Data>
+        <Data Name='IpAddress'>203.0.113.7</Data>
+    </EventData>
+</Event>"#
+    )
+}
+
+async fn next_event(rx: &mut mpsc::Receiver<Event>) -> Option<Event> {
+    timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+#[tokio::test]
+async fn test_listener_delivers_new_events_to_channel() {
+    // Each poll sees one more event than the last, as a live log would.
+    let polls = Arc::new(AtomicU32::new(0));
+    let source: XmlSource = Arc::new(move || {
+        let latest = polls.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok((1..=latest)
+            .map(|record_id| failed_logon(record_id, &format!("user{}", record_id)))
+            .collect())
+    });
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx).with_xml_source(source);
+
+    for expected in 1..=3 {
+        listener.clone().invoke();
+        let event = next_event(&mut rx)
+            .await
+            .expect("event should be delivered");
+        assert_eq!(event.record_id(), Some(expected));
+        assert_eq!(event.computer.as_deref(), Some("Ether"));
+        match event.details {
+            EventDetails::Login(login) => {
+                assert_eq!(login.username(), format!("user{}@ETHER", expected));
+                assert!(!login.success);
+            }
+            other => panic!("expected a login, got {:?}", other),
+        }
+    }
+
+    assert!(listener.health().is_healthy());
+}
+
+#[tokio::test]
+async fn test_listener_records_source_failures() {
+    let source: XmlSource = Arc::new(|| Err(anyhow::anyhow!("log unavailable")));
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx).with_xml_source(source);
+
+    listener.clone().invoke();
+    assert!(
+        timeout(Duration::from_millis(200), rx.recv())
+            .await
+            .is_err()
+    );
+    assert_eq!(listener.health().consecutive_errors, 1);
+}
+
+#[tokio::test]
+async fn test_run_polls_until_aborted() {
+    let source: XmlSource = Arc::new(|| Ok(vec![failed_logon(1, "alice")]));
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_poll_interval(Duration::from_millis(10));
+
+    let handle = tokio::spawn(listener.run());
+    let event = next_event(&mut rx)
+        .await
+        .expect("run should deliver events");
+    assert_eq!(event.record_id(), Some(1));
+
+    // Later polls see the same record, which dedup holds back.
+    assert!(
+        timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err()
+    );
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_paused_listener_delivers_nothing_then_catches_up() {
+    // The log holds however many records the test has written so far.
+    let written = Arc::new(AtomicU32::new(1));
+    let log = Arc::clone(&written);
+    let source: XmlSource = Arc::new(move || {
+        Ok((1..=log.load(Ordering::SeqCst))
+            .map(|record_id| failed_logon(record_id, "svc_backup"))
+            .collect())
+    });
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_poll_interval(Duration::from_millis(10));
+    let pause = listener.pause_handle();
+
+    let handle = tokio::spawn(listener.run());
+    let event = next_event(&mut rx).await.expect("event before pausing");
+    assert_eq!(event.record_id(), Some(1));
+
+    pause.pause();
+    written.store(3, Ordering::SeqCst);
+    assert!(
+        timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err(),
+        "a paused listener should deliver nothing"
+    );
+
+    pause.resume();
+    for expected in 2..=3 {
+        let event = next_event(&mut rx).await.expect("events after resuming");
+        assert_eq!(event.record_id(), Some(expected));
+    }
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_max_age_drops_stale_events() {
+    let stale = failed_logon(1, "olduser").replace("2025-07-22", "2025-06-01");
+    let source: XmlSource = Arc::new(move || Ok(vec![stale.clone(), failed_logon(2, "newuser")]));
+    let clock = MockClock::new(
+        DateTime::parse_from_rfc3339("2025-07-25T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc),
+    );
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_max_age(chrono::Duration::days(7), Arc::new(clock));
+
+    listener.clone().invoke();
+    let event = next_event(&mut rx)
+        .await
+        .expect("fresh event should be delivered");
+    assert_eq!(event.record_id(), Some(2));
+    assert!(
+        timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_run_falls_back_to_polling_when_subscribing_fails() {
+    let source: XmlSource = Arc::new(|| Ok(vec![failed_logon(1, "alice")]));
+    let subscriber: Subscriber = Arc::new(|_| Err(anyhow::anyhow!("channel can't be subscribed")));
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_subscriber(subscriber)
+        .with_poll_interval(Duration::from_millis(10));
+
+    let handle = tokio::spawn(listener.clone().run());
+    let event = next_event(&mut rx)
+        .await
+        .expect("polling should deliver events");
+    assert_eq!(event.record_id(), Some(1));
+    assert_eq!(listener.health().mode, Some(DeliveryMode::Poll));
+    handle.abort();
+}
+
+#[tokio::test]
+async fn test_run_prefers_subscription() {
+    let source: XmlSource = Arc::new(|| panic!("a subscribed listener shouldn't poll"));
+    // Hold each subscription's sender so the subscription stays open.
+    let senders = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let subscriber: Subscriber = {
+        let senders = Arc::clone(&senders);
+        Arc::new(move |tx: mpsc::Sender<String>| {
+            tx.try_send(failed_logon(1, "alice"))?;
+            tx.try_send(failed_logon(2, "bob"))?;
+            senders.lock().unwrap().push(tx);
+            Ok(())
+        })
+    };
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_subscriber(subscriber);
+
+    let handle = tokio::spawn(listener.clone().run());
+    for expected in 1..=2 {
+        let event = next_event(&mut rx)
+            .await
+            .expect("subscription should deliver events");
+        assert_eq!(event.record_id(), Some(expected));
+    }
+    assert_eq!(listener.health().mode, Some(DeliveryMode::Subscribe));
+    handle.abort();
+}
+
+#[test]
+fn test_last_returns_most_recent_events_first() {
+    let at = |record_id, user: &str, second: u32| {
+        failed_logon(record_id, user).replace("16:25:08", &format!("16:25:{:02}", second))
+    };
+    let source: XmlSource =
+        Arc::new(move || Ok(vec![at(1, "alice", 1), at(2, "bob", 5), at(3, "carol", 3)]));
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx).with_xml_source(source);
+
+    let events = listener.last(2).unwrap();
+    let records: Vec<_> = events.iter().map(Event::record_id).collect();
+    assert_eq!(records, vec![Some(2), Some(3)]);
+    assert!(rx.try_recv().is_err(), "nothing goes through the channel");
+}
+
+#[tokio::test]
+async fn test_reverse_direction_delivers_newest_first_and_still_dedups() {
+    let source: XmlSource = Arc::new(|| {
+        Ok((1..=3)
+            .map(|record_id| failed_logon(record_id, "alice"))
+            .collect())
+    });
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_xml_source(source)
+        .with_direction(QueryDirection::Reverse);
+
+    listener.clone().invoke();
+    let mut records = Vec::new();
+    for _ in 0..3 {
+        let event = next_event(&mut rx)
+            .await
+            .expect("event should be delivered");
+        records.push(event.record_id());
+    }
+    assert_eq!(records, vec![Some(3), Some(2), Some(1)]);
+
+    listener.clone().invoke();
+    assert!(
+        timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn test_count_since_counts_only_recent_events() {
+    let ago = |record_id, minutes| {
+        let timestamp = (Utc::now() - chrono::Duration::minutes(minutes))
+            .to_rfc3339_opts(SecondsFormat::Micros, true);
+        failed_logon(record_id, "alice").replace("2025-07-22T16:25:08.8954670Z", &timestamp)
+    };
+    let success = ago(4, 5).replace("<EventID>4625</EventID>", "<EventID>4624</EventID>");
+    let source: XmlSource = Arc::new(move || {
+        Ok(vec![
+            ago(1, 120),
+            ago(2, 30),
+            ago(3, 5),
+            ago(3, 5),
+            success.clone(),
+        ])
+    });
+    let (tx, mut rx) = mpsc::channel(10);
+    let listener = LogonListener::new(tx)
+        .with_event_ids(vec![4625])
+        .with_xml_source(source);
+
+    // The duplicate record is counted twice: counting doesn't dedup. The 4624 isn't asked for.
+    assert_eq!(listener.count_since(chrono::Duration::hours(1)).unwrap(), 3);
+    assert!(rx.try_recv().is_err(), "nothing goes through the channel");
+}
+
+/// A log that holds records 1 through however many the test has written so far.
+fn growing_log(written: &Arc<AtomicU32>) -> XmlSource {
+    let log = Arc::clone(written);
+    Arc::new(move || {
+        Ok((1..=log.load(Ordering::SeqCst))
+            .map(|record_id| failed_logon(record_id, "alice"))
+            .collect())
+    })
+}
+
+async fn next_tailed(tail: &mut hosho::listener::tail::Tail) -> Option<Event> {
+    timeout(Duration::from_secs(1), tail.next())
+        .await
+        .ok()
+        .flatten()
+}
+
+#[tokio::test]
+async fn test_tail_yields_each_new_event_once() {
+    let written = Arc::new(AtomicU32::new(2));
+    let (tx, _rx) = mpsc::channel(1);
+    let mut tail = LogonListener::new(tx)
+        .with_xml_source(growing_log(&written))
+        .with_poll_interval(Duration::from_millis(10))
+        .tail();
+
+    for expected in 1..=2 {
+        let event = next_tailed(&mut tail).await.expect("tailed event");
+        assert_eq!(event.record_id(), Some(expected));
+    }
+    written.store(3, Ordering::SeqCst);
+    let event = next_tailed(&mut tail).await.expect("tailed event");
+    assert_eq!(event.record_id(), Some(3));
+    assert!(
+        timeout(Duration::from_millis(100), tail.next())
+            .await
+            .is_err(),
+        "already yielded events shouldn't come again"
+    );
+}
+
+#[tokio::test]
+async fn test_tail_resumes_from_bookmark() {
+    let path = std::env::temp_dir().join(format!("hosho-bookmark-{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let written = Arc::new(AtomicU32::new(3));
+    let tail = |written: &Arc<AtomicU32>| {
+        let (tx, _rx) = mpsc::channel(1);
+        LogonListener::new(tx)
+            .with_xml_source(growing_log(written))
+            .with_poll_interval(Duration::from_millis(10))
+            .with_bookmark(&path)
+            .unwrap()
+            .tail()
+    };
+
+    let mut first = tail(&written);
+    for expected in 1..=3 {
+        let event = next_tailed(&mut first).await.expect("tailed event");
+        assert_eq!(event.record_id(), Some(expected));
+    }
+    drop(first);
+    // The bookmark is saved just after the events are sent.
+    for _ in 0..100 {
+        if Bookmark::open(&path).unwrap().record_id() == Some(3) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(Bookmark::open(&path).unwrap().record_id(), Some(3));
+
+    // Records written while nothing was tailing are picked up, and nothing is yielded twice.
+    written.store(5, Ordering::SeqCst);
+    let mut second = tail(&written);
+    for expected in 4..=5 {
+        let event = next_tailed(&mut second).await.expect("tailed event");
+        assert_eq!(event.record_id(), Some(expected));
+    }
+    drop(second);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_bookmark_skips_delivered_events_until_the_log_is_cleared() {
+    let path =
+        std::env::temp_dir().join(format!("hosho-bookmark-cleared-{}.txt", std::process::id()));
+    std::fs::write(&path, "40").unwrap();
+    let bookmark = Bookmark::open(&path).unwrap();
+    let logons = |record_ids: &[u32]| -> Vec<Event> {
+        record_ids
+            .iter()
+            .map(|&record_id| {
+                let (timestamp, login) =
+                    parse_login_event(&failed_logon(record_id, "alice")).unwrap();
+                Event::new(EventDetails::Login(login), timestamp)
+            })
+            .collect()
+    };
+
+    let fresh: Vec<_> = bookmark
+        .skip_delivered(logons(&[39, 40, 41]))
+        .iter()
+        .map(Event::record_id)
+        .collect();
+    assert_eq!(fresh, vec![Some(41)]);
+    // Record IDs starting over below the bookmark mean the log was cleared.
+    assert_eq!(bookmark.skip_delivered(logons(&[1, 2])).len(), 2);
+    let _ = std::fs::remove_file(&path);
+}
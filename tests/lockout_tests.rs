@@ -0,0 +1,118 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hosho::enrich::LockoutCorrelator;
+use hosho::listener::lockout::parse_lockout_event;
+use hosho::listener::{Account, Event, EventDetails, LockoutEvent, LogonEvent};
+use hosho::pipeline::Transform;
+
+const LOCKOUT_XML: &str = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-Security-Auditing' Guid='{54849625-5478-4994-a5ba-3e3b0328c30d}'/>
+        <EventID>4740</EventID>
+        <TimeCreated SystemTime='2025-07-22T18:02:11.1234560Z'/>
+        <EventRecordID>8486120</EventRecordID>
+        <Channel>Security</Channel>
+        <Computer>DC01.corp.local</Computer>
+    </System>
+    <EventData>
+        <Data Name='TargetUserName'>alice</Data>
+        <Data Name='TargetDomainName'>WKS042</Data>
+        <Data Name='TargetSid'>S-1-5-21-1004336348-1177238915-682003330-1104</Data>
+        <Data Name='SubjectUserSid'>S-1-5-18</Data>
+        <Data Name='SubjectUserName'>DC01$</Data>
+        <Data Name='SubjectDomainName'>CORP</Data>
+        <Data Name='SubjectLogonId'>0x3e7</Data>
+    </EventData>
+</Event>
+"#;
+
+fn at(secs: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 7, 22, 18, 0, 0).unwrap() + Duration::seconds(secs)
+}
+
+fn failure(user: &str, secs: i64) -> Event {
+    let login = LogonEvent {
+        target: Account::new(user, Some("CORP")),
+        source_ip: "10.0.0.42".to_string(),
+        attempt_count: 1,
+        ..Default::default()
+    };
+    Event::new(EventDetails::Login(login), at(secs))
+}
+
+fn lockout(user: &str, secs: i64) -> Event {
+    let lockout = LockoutEvent {
+        account: Account::new(user, None),
+        caller_computer: Some("WKS042".to_string()),
+        causes: Vec::new(),
+        event_record_id: None,
+    };
+    Event::new(EventDetails::Lockout(lockout), at(secs))
+}
+
+fn causes(event: Event) -> Vec<LogonEvent> {
+    let EventDetails::Lockout(lockout) = event.details else {
+        panic!("expected a lockout");
+    };
+    assert!(
+        lockout
+            .causes
+            .iter()
+            .all(|cause| cause.target.user == "alice" && !cause.success)
+    );
+    lockout.causes
+}
+
+#[test]
+fn test_parse_lockout_event() {
+    let (timestamp, lockout) = parse_lockout_event(LOCKOUT_XML).expect("4740 should parse");
+
+    assert_eq!(timestamp, at(131) + Duration::nanoseconds(123_456_000));
+    assert_eq!(lockout.account.user, "alice");
+    assert_eq!(lockout.account.domain, None);
+    assert_eq!(
+        lockout.account.sid.unwrap().as_str(),
+        "S-1-5-21-1004336348-1177238915-682003330-1104"
+    );
+    assert_eq!(lockout.caller_computer.as_deref(), Some("WKS042"));
+    assert!(lockout.causes.is_empty());
+    assert_eq!(lockout.event_record_id, Some(8486120));
+}
+
+#[test]
+fn test_parse_lockout_event_rejects_other_ids() {
+    let xml = LOCKOUT_XML.replace("<EventID>4740</EventID>", "<EventID>4625</EventID>");
+    assert!(parse_lockout_event(&xml).is_err());
+}
+
+#[tokio::test]
+async fn test_failures_are_attached_to_the_lockout() {
+    let correlator = LockoutCorrelator::new(Duration::minutes(30));
+
+    for secs in 0..15 {
+        correlator.transform(failure("alice", secs)).await.unwrap();
+    }
+    correlator.transform(failure("bob", 10)).await.unwrap();
+    let event = correlator.transform(lockout("ALICE", 20)).await.unwrap();
+
+    assert_eq!(causes(event).len(), 15);
+
+    let again = correlator.transform(lockout("alice", 30)).await.unwrap();
+    assert!(
+        causes(again).is_empty(),
+        "a lockout takes the failures that caused it"
+    );
+}
+
+#[tokio::test]
+async fn test_look_back_is_bounded() {
+    let correlator = LockoutCorrelator::new(Duration::minutes(5)).with_max_per_user(3);
+
+    correlator.transform(failure("alice", 0)).await.unwrap();
+    for secs in 400..405 {
+        correlator.transform(failure("alice", secs)).await.unwrap();
+    }
+    let event = correlator.transform(lockout("alice", 410)).await.unwrap();
+
+    assert_eq!(causes(event).len(), 3);
+}
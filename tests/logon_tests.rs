@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
-use sentinel::listener::logon::{LogonVariant, parse_login_event};
+use hosho::listener::logon::{
+    ImpersonationLevel, LogonEvent, LogonVariant, STATUS_ACCOUNT_DISABLED, STATUS_NO_SUCH_USER,
+    parse_login_event,
+};
+use hosho::listener::{Account, LogonId};
 
-#[test]
-fn test_parse_login_event() {
-    let xml = r#"
+const SAMPLE_LOGON: &str = r#"
 <Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
     <System>
         <Provider Name='Microsoft-Windows-Security-Auditing' Guid='{54849625-5478-4994-a5ba-3e3b0328c30d}'/>
@@ -51,7 +53,11 @@ fn test_parse_login_event() {
         <Data Name='ElevatedToken'>%%1842</Data>
     </EventData>
 </Event>
-        "#;
+"#;
+
+#[test]
+fn test_parse_login_event() {
+    let xml = SAMPLE_LOGON;
 
     let result = parse_login_event(xml);
 
@@ -64,24 +70,93 @@ fn test_parse_login_event() {
         .with_timezone(&Utc);
     assert_eq!(timestamp, expected_timestamp);
 
-    assert_eq!(logon_event.username, "SYSTEM@NT AUTHORITY");
+    assert_eq!(logon_event.username(), "SYSTEM@NT AUTHORITY");
+    assert!(logon_event.target.is_wellknown());
+    assert_eq!(
+        logon_event
+            .target
+            .sid
+            .as_ref()
+            .and_then(|sid| sid.well_known_name()),
+        Some("SYSTEM")
+    );
     assert_eq!(logon_event.source_ip, "-");
     assert!(matches!(logon_event.variant, LogonVariant::Service));
+    assert!(logon_event.success);
+    assert_eq!(
+        logon_event.creator_process.as_deref(),
+        Some(r"C:\Windows\System32\services.exe")
+    );
+    assert_eq!(logon_event.creator_process_id, Some(0x560));
+
+    let subject = logon_event.subject.expect("subject should be parsed");
+    assert_eq!(subject.user, "ETHER$");
+    assert_eq!(subject.domain.as_deref(), Some("WORKGROUP"));
+    assert!(subject.is_machine());
+    assert_eq!(logon_event.subject_logon_id, Some(LogonId(0x3e7)));
+    assert_eq!(logon_event.logon_id, Some(LogonId(0x3e7)));
+
+    // An all-zero linked logon ID means the logon has no linked (split-token) partner.
+    assert_eq!(logon_event.linked_logon_id, None);
 
     println!("Successfully tested parse_login_event:");
     println!("Timestamp: {}", timestamp);
-    println!("Username: {}", logon_event.username);
+    println!("Username: {}", logon_event.username());
     println!("Source IP: {}", logon_event.source_ip);
     println!("Logon Type: {}", logon_event.variant);
 }
 
+#[test]
+fn test_parse_linked_logon_id() {
+    let xml = SAMPLE_LOGON.replace(
+        "<Data Name='TargetLinkedLogonId'>0x0</Data>",
+        "<Data Name='TargetLinkedLogonId'>0x1a2b3c</Data>",
+    );
+
+    let (_, logon_event) = parse_login_event(&xml).expect("parse_login_event should succeed");
+    assert_eq!(logon_event.linked_logon_id, Some(LogonId(0x1a2b3c)));
+}
+
+fn logon_from(ip_address: &str) -> LogonEvent {
+    let xml = SAMPLE_LOGON.replace(
+        "<Data Name='IpAddress'>-</Data>",
+        &format!("<Data Name='IpAddress'>{}</Data>", ip_address),
+    );
+    parse_login_event(&xml)
+        .expect("parse_login_event should succeed")
+        .1
+}
+
+#[test]
+fn test_source_ip_unwraps_mapped_ipv6() {
+    let logon_event = logon_from("::ffff:10.0.0.1");
+    assert_eq!(logon_event.source_ip, "10.0.0.1");
+    assert_eq!(logon_event.source_ip_raw, "::ffff:10.0.0.1");
+    assert_eq!(logon_event.source_addr(), Some("10.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn test_source_ip_strips_brackets_ports_and_zones() {
+    assert_eq!(logon_from("[::ffff:10.0.0.1]").source_ip, "10.0.0.1");
+    assert_eq!(logon_from("[2001:db8::1]:445").source_ip, "2001:db8::1");
+    assert_eq!(logon_from("10.0.0.1:445").source_ip, "10.0.0.1");
+    assert_eq!(logon_from("fe80::1%12").source_ip, "fe80::1");
+    assert_eq!(logon_from("2001:DB8::1").source_ip, "2001:db8::1");
+}
+
+#[test]
+fn test_source_ip_keeps_placeholders() {
+    let logon_event = logon_from("-");
+    assert_eq!(logon_event.source_ip, "-");
+    assert_eq!(logon_event.source_addr(), None);
+}
+
 #[test]
 fn test_parse_login_event_with_missing_data() {
     let xml = r#"
 <Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
     <System>
         <TimeCreated SystemTime='2025-07-22T16:25:08.8954670Z'/>
-        <EventRecordID>0</EventRecordID>
     </System>
     <EventData>
         <Data Name='TargetUserName'>TESTUSER</Data>
@@ -103,12 +178,13 @@ fn test_parse_login_event_with_missing_data() {
         .with_timezone(&Utc);
     assert_eq!(timestamp, expected_timestamp);
 
-    assert_eq!(logon_event.username, "TESTUSER");
+    assert_eq!(logon_event.username(), "TESTUSER");
     assert_eq!(logon_event.source_ip, "N/A");
     assert!(matches!(logon_event.variant, LogonVariant::Invalid(_)));
+    assert_eq!(logon_event.event_record_id, None);
 
     println!("Successfully tested parse_login_event with missing data:");
-    println!("Username: {}", logon_event.username);
+    println!("Username: {}", logon_event.username());
     println!("Source IP: {}", logon_event.source_ip);
     println!("Logon Type: {}", logon_event.variant);
 }
@@ -134,3 +210,358 @@ fn test_parse_login_event_invalid_xml() {
         "Error should mention XML parsing failure"
     );
 }
+
+#[test]
+fn test_parse_login_event_rejects_malformed_input() {
+    let inputs = [
+        String::new(),
+        "<Event>".to_string(),
+        SAMPLE_LOGON[..SAMPLE_LOGON.len() / 2].to_string(),
+        SAMPLE_LOGON.replace("2025-07-22T16:25:08.8954670Z", "2025-07-22T"),
+        SAMPLE_LOGON.replace(
+            "<Data Name='SubjectUserName'>",
+            "<![CDATA[<Data Name='SubjectUserName'>",
+        ),
+    ];
+    for xml in &inputs {
+        assert!(parse_login_event(xml).is_err(), "should reject {:?}", xml);
+    }
+
+    let xml = SAMPLE_LOGON
+        .replace(
+            "<Data Name='ProcessId'>0x560</Data>",
+            "<Data Name='ProcessId'>0xffffffffffffffffff</Data>",
+        )
+        .replace(
+            "<Data Name='SubjectLogonId'>0x3e7</Data>",
+            "<Data Name='SubjectLogonId'>0x</Data>",
+        );
+    let (_, logon_event) = parse_login_event(&xml).expect("bad IDs should not fail the event");
+    assert_eq!(logon_event.creator_process_id, None);
+    assert_eq!(logon_event.subject_logon_id, None);
+}
+
+#[test]
+fn test_default_logon_event_is_unset() {
+    let logon_event = LogonEvent::default();
+    assert_eq!(logon_event.target.user, "");
+    assert_eq!(logon_event.variant, LogonVariant::Invalid(String::new()));
+    assert!(!logon_event.success);
+}
+
+#[test]
+fn test_logon_event_into_json() {
+    let (_, logon_event) =
+        parse_login_event(SAMPLE_LOGON).expect("parse_login_event should succeed");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&logon_event.into_json()).expect("into_json should produce JSON");
+    assert_eq!(json["target"]["user"], "SYSTEM");
+    assert_eq!(json["target"]["sid"], "S-1-5-18");
+    assert_eq!(json["variant"], "Service");
+    assert_eq!(json["event_record_id"], 8485950);
+}
+
+fn failed_logon_with_reason(reason: &str) -> LogonEvent {
+    let xml = SAMPLE_LOGON
+        .replace("<EventID>4624</EventID>", "<EventID>4625</EventID>")
+        .replace(
+            "</EventData>",
+            &format!("<Data Name='FailureReason'>{}</Data></EventData>", reason),
+        );
+    parse_login_event(&xml)
+        .expect("parse_login_event should succeed")
+        .1
+}
+
+#[test]
+fn test_failure_reason_is_resolved() {
+    let logon_event = failed_logon_with_reason("%%2313");
+    assert!(!logon_event.success);
+    assert_eq!(logon_event.failure_reason.as_deref(), Some("%%2313"));
+    assert_eq!(
+        logon_event.failure_reason_text.as_deref(),
+        Some("Unknown user name or bad password.")
+    );
+}
+
+#[test]
+fn test_unknown_failure_reason_keeps_raw_value() {
+    let logon_event = failed_logon_with_reason("%%9999");
+    assert_eq!(logon_event.failure_reason.as_deref(), Some("%%9999"));
+    assert_eq!(logon_event.failure_reason_text, None);
+
+    let logon_event = failed_logon_with_reason("Account locked out.");
+    assert_eq!(
+        logon_event.failure_reason_text.as_deref(),
+        Some("Account locked out.")
+    );
+
+    let (_, logon_event) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert_eq!(logon_event.failure_reason, None);
+}
+
+#[test]
+fn test_audit_keywords_match_event_id() {
+    let (_, logon_event) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert_eq!(logon_event.keywords, Some(0x8020000000000000));
+    assert_eq!(logon_event.task, Some(12544));
+    assert_eq!(logon_event.level, Some(0));
+    assert_eq!(logon_event.audit_success(), Some(logon_event.success));
+
+    let failure = SAMPLE_LOGON
+        .replace("<EventID>4624</EventID>", "<EventID>4625</EventID>")
+        .replace("0x8020000000000000", "0x8010000000000000");
+    let (_, logon_event) = parse_login_event(&failure).unwrap();
+    assert_eq!(logon_event.audit_success(), Some(false));
+    assert!(!logon_event.success);
+    assert!(!logon_event.audit_keyword_mismatch());
+}
+
+#[test]
+fn test_audit_keywords_contradicting_event_id_are_flagged() {
+    let forged = SAMPLE_LOGON.replace("0x8020000000000000", "0x8010000000000000");
+    let (_, logon_event) = parse_login_event(&forged).unwrap();
+
+    // The event ID decides the outcome; the contradiction is left for the caller to judge.
+    assert!(logon_event.success);
+    assert!(logon_event.audit_keyword_mismatch());
+}
+
+#[test]
+fn test_success_of_other_event_ids_comes_from_keywords() {
+    let explicit_credentials =
+        SAMPLE_LOGON.replace("<EventID>4624</EventID>", "<EventID>4648</EventID>");
+    let (_, logon_event) = parse_login_event(&explicit_credentials).unwrap();
+    assert!(logon_event.success);
+    assert_eq!(logon_event.event_id, Some(4648));
+}
+
+#[test]
+fn test_system_logging_on_system_is_self_logon() {
+    let (_, logon_event) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert!(
+        logon_event.is_self_logon(),
+        "ETHER$ and SYSTEM share the S-1-5-18 SID"
+    );
+
+    let other_target = SAMPLE_LOGON
+        .replace(
+            "<Data Name='TargetUserSid'>S-1-5-18",
+            "<Data Name='TargetUserSid'>S-1-5-21-1-2-3-1001",
+        )
+        .replace(">SYSTEM</Data>", ">alice</Data>");
+    let (_, logon_event) = parse_login_event(&other_target).unwrap();
+    assert!(!logon_event.is_self_logon());
+
+    let no_subject = LogonEvent::default();
+    assert!(!no_subject.is_self_logon());
+}
+
+#[test]
+fn test_merge_prefers_the_richer_fields() {
+    let partial = LogonEvent {
+        logon_id: Some(LogonId(0x1a2b)),
+        ..LogonEvent::new(
+            Account::new("alice", Some("CORP")),
+            "-",
+            LogonVariant::Network,
+            true,
+        )
+    };
+    let full = LogonEvent {
+        source_hostname: Some("ws01.corp.example".to_string()),
+        authentication_package: Some("Kerberos".to_string()),
+        logon_id: Some(LogonId(0x1a2b)),
+        event_record_id: Some(42),
+        ..LogonEvent::new(
+            Account::new("alice", Some("CORP")),
+            "10.0.0.5",
+            LogonVariant::RemoteInteractive,
+            true,
+        )
+    };
+
+    let merged = partial.merge(full);
+    assert_eq!(merged.source_ip, "10.0.0.5");
+    assert_eq!(merged.variant, LogonVariant::RemoteInteractive);
+    assert_eq!(merged.source_hostname.as_deref(), Some("ws01.corp.example"));
+    assert_eq!(merged.authentication_package.as_deref(), Some("Kerberos"));
+    assert_eq!(merged.event_record_id, Some(42));
+    assert_eq!(merged.username(), "alice@CORP");
+}
+
+#[test]
+fn test_null_session_is_anonymous() {
+    let null_session = SAMPLE_LOGON
+        .replace(
+            "<Data Name='TargetUserSid'>S-1-5-18",
+            "<Data Name='TargetUserSid'>S-1-5-7",
+        )
+        .replace(">SYSTEM</Data>", ">ANONYMOUS LOGON</Data>")
+        .replace("<Data Name='LogonType'>5", "<Data Name='LogonType'>3");
+    let (_, logon_event) = parse_login_event(&null_session).unwrap();
+    assert!(logon_event.is_anonymous());
+    assert_eq!(logon_event.variant, LogonVariant::Network);
+
+    let (_, system) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert!(!system.is_anonymous());
+}
+
+#[test]
+fn test_german_locale_event_parses_from_codes_not_text() {
+    let german = SAMPLE_LOGON
+        .replace("<EventID>4624</EventID>", "<EventID>4625</EventID>")
+        .replace(
+            "<Keywords>0x8020000000000000</Keywords>",
+            "<Keywords>0x8010000000000000</Keywords>",
+        )
+        .replace("NT AUTHORITY", "NT-AUTORITÄT")
+        .replace(
+            ">SYSTEM</Data>",
+            ">SYSTEM</Data>\n        <Data Name='FailureReason'>%%2313</Data>",
+        )
+        .replace(
+            "</EventData>",
+            r#"</EventData>
+    <RenderingInfo Culture='de-DE'>
+        <Message>Fehler beim Anmelden eines Kontos.</Message>
+        <Level>Informationen</Level>
+        <Task>Anmelden</Task>
+        <Keywords>
+            <Keyword>Überwachung gescheitert</Keyword>
+        </Keywords>
+    </RenderingInfo>"#,
+        );
+
+    let (_, logon_event) = parse_login_event(&german).unwrap();
+
+    assert!(matches!(logon_event.variant, LogonVariant::Service));
+    assert!(!logon_event.success);
+    assert_eq!(logon_event.audit_success(), Some(false));
+    assert_eq!(logon_event.target.domain.as_deref(), Some("NT-AUTORITÄT"));
+    assert!(logon_event.target.is_wellknown(), "recognized by SID");
+    assert!(logon_event.is_self_logon());
+    assert_eq!(
+        logon_event.failure_reason_text.as_deref(),
+        Some("Unknown user name or bad password.")
+    );
+}
+
+#[test]
+fn test_explicit_credentials_target_server() {
+    let explicit_credentials = |server: &str, info: &str| {
+        SAMPLE_LOGON
+            .replace("<EventID>4624</EventID>", "<EventID>4648</EventID>")
+            .replace(
+                "<Data Name='IpAddress'>",
+                &format!(
+                    "<Data Name='TargetServerName'>{}</Data>\n        <Data Name='TargetInfo'>{}</Data>\n        <Data Name='IpAddress'>",
+                    server, info
+                ),
+            )
+    };
+
+    let (_, logon_event) = parse_login_event(&explicit_credentials(
+        "fs01.corp.local",
+        "cifs/fs01.corp.local",
+    ))
+    .unwrap();
+    assert_eq!(
+        logon_event.target_server.as_deref(),
+        Some("fs01.corp.local")
+    );
+
+    let (_, logon_event) =
+        parse_login_event(&explicit_credentials("-", "HTTP/web01.corp.local")).unwrap();
+    assert_eq!(
+        logon_event.target_server.as_deref(),
+        Some("web01.corp.local")
+    );
+
+    let (_, logon_event) =
+        parse_login_event(&explicit_credentials("localhost", "localhost")).unwrap();
+    assert_eq!(logon_event.target_server, None);
+
+    let (_, logon_event) = parse_login_event(&explicit_credentials("-", "-")).unwrap();
+    assert_eq!(logon_event.target_server, None);
+
+    let (_, logon_event) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert_eq!(logon_event.target_server, None);
+}
+
+#[test]
+fn test_all_logon_variants_round_trip() {
+    let all = LogonVariant::all();
+    assert_eq!(all.len(), LogonVariant::KNOWN.len());
+    assert_eq!(all[7], (10, "RemoteInteractive"));
+
+    for (number, name) in all {
+        let variant = LogonVariant::from_string(&number.to_string());
+        assert_eq!(variant.as_number(), Some(number));
+        assert_eq!(variant.to_string(), name);
+        assert!(
+            !matches!(variant, LogonVariant::Unknown(_)),
+            "{} is known",
+            number
+        );
+    }
+
+    assert_eq!(LogonVariant::from_string("6"), LogonVariant::Unknown(6));
+    assert_eq!(LogonVariant::Unknown(6).as_number(), Some(6));
+    assert_eq!(LogonVariant::from_string("x").as_number(), None);
+}
+
+#[test]
+fn test_impersonation_level_resolves_placeholders() {
+    let (_, logon_event) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert_eq!(
+        logon_event.impersonation_level,
+        Some(ImpersonationLevel::Impersonation)
+    );
+
+    let delegation = SAMPLE_LOGON.replace("%%1833", "%%1840");
+    let (_, logon_event) = parse_login_event(&delegation).unwrap();
+    assert_eq!(
+        logon_event.impersonation_level,
+        Some(ImpersonationLevel::Delegation)
+    );
+
+    assert_eq!(
+        ImpersonationLevel::from_string("%%1899"),
+        ImpersonationLevel::Other("%%1899".to_string())
+    );
+}
+
+fn failed_logon_with_sub_status(sub_status: &str) -> LogonEvent {
+    failed_logon_with_reason(&format!(
+        "%%2313</Data><Data Name='SubStatus'>{}",
+        sub_status
+    ))
+}
+
+#[test]
+fn test_disabled_or_missing_account_is_suspicious() {
+    let missing = failed_logon_with_sub_status("0xc0000064");
+    assert_eq!(missing.sub_status, Some(STATUS_NO_SUCH_USER));
+    assert!(missing.suspicious_account);
+
+    let disabled = failed_logon_with_sub_status("0xC0000072");
+    assert_eq!(disabled.sub_status, Some(STATUS_ACCOUNT_DISABLED));
+    assert!(disabled.suspicious_account);
+}
+
+#[test]
+fn test_other_sub_statuses_are_not_suspicious() {
+    // Bad password for an existing account.
+    let bad_password = failed_logon_with_sub_status("0xc000006a");
+    assert_eq!(bad_password.sub_status, Some(0xC000006A));
+    assert!(!bad_password.suspicious_account);
+
+    let unset = failed_logon_with_sub_status("0x0");
+    assert_eq!(unset.sub_status, None);
+    assert!(!unset.suspicious_account);
+
+    let (_, success) = parse_login_event(SAMPLE_LOGON).unwrap();
+    assert!(!success.suspicious_account);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "otel")]
+
+use chrono::{TimeZone, Utc};
+use hosho::enrich::Severity;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::sink::otel::{attributes, severity_number};
+use opentelemetry::logs::{AnyValue, Severity as OtelSeverity};
+
+fn sample_logon() -> Event {
+    let mut event = Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new("TESTUSER", Some("WORKGROUP")),
+            "192.168.1.50",
+            LogonVariant::RemoteInteractive,
+            false,
+        )),
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    );
+    event.computer = Some("ws1.corp.local".to_string());
+    event.tags = vec!["rdp".to_string()];
+    event
+}
+
+fn attribute(event: &Event, key: &str) -> Option<AnyValue> {
+    attributes(event)
+        .into_iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value)
+}
+
+#[test]
+fn test_attributes_use_dotted_ecs_names() {
+    let event = sample_logon();
+
+    assert_eq!(
+        attribute(&event, "user.name"),
+        Some(AnyValue::String("TESTUSER".into()))
+    );
+    assert_eq!(
+        attribute(&event, "source.ip"),
+        Some(AnyValue::String("192.168.1.50".into()))
+    );
+    assert_eq!(
+        attribute(&event, "host.name"),
+        Some(AnyValue::String("ws1.corp.local".into()))
+    );
+    assert_eq!(
+        attribute(&event, "tags"),
+        Some(AnyValue::ListAny(Box::new(vec![AnyValue::String(
+            "rdp".into()
+        )])))
+    );
+}
+
+#[test]
+fn test_record_fields_are_not_repeated_as_attributes() {
+    let event = sample_logon();
+
+    assert_eq!(attribute(&event, "@timestamp"), None);
+    assert_eq!(attribute(&event, "message"), None);
+    assert_eq!(attribute(&event, "log.level"), None);
+}
+
+#[test]
+fn test_severity_maps_to_otel_severity_number() {
+    assert_eq!(severity_number(Severity::Info), OtelSeverity::Info);
+    assert_eq!(severity_number(Severity::Medium), OtelSeverity::Warn);
+    assert_eq!(severity_number(Severity::Critical), OtelSeverity::Fatal);
+}
@@ -0,0 +1,105 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hosho::enrich::severity::{Condition, SeverityRule};
+use hosho::enrich::{Severity, SeverityPolicy};
+use hosho::errors::SentinelError;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::pipeline::{Pipeline, Tap};
+use hosho::sink::Sink;
+use hosho::suppress::Suppressor;
+use hosho::tag::Tagger;
+
+fn logon(username: &str) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(username, None),
+            "10.1.2.3",
+            LogonVariant::Network,
+            false,
+        )),
+        Utc::now(),
+    )
+}
+
+fn pipeline() -> Pipeline {
+    with_rules(Pipeline::new())
+}
+
+/// Adds a suppression rule, a tag rule, and a severity rule keyed on that tag.
+fn with_rules(pipeline: Pipeline) -> Pipeline {
+    pipeline
+        .with_transform(
+            Suppressor::from_json(r#"[{"field": "username", "equals": "svc_backup"}]"#).unwrap(),
+        )
+        .with_transform(
+            Tagger::from_json(
+                r#"[{"tag": "admin", "when": {"field": "username", "equals": "administrator"}}]"#,
+            )
+            .unwrap(),
+        )
+        .with_transform(SeverityPolicy::empty().with_rule(SeverityRule::new(
+            vec![Condition::Tagged("admin".to_string())],
+            Severity::Critical,
+        )))
+}
+
+#[test]
+fn test_names_follow_order_added() {
+    assert_eq!(pipeline().names(), vec!["suppress", "tag", "severity"]);
+}
+
+#[tokio::test]
+async fn test_later_transforms_see_earlier_changes() {
+    let event = pipeline().run(logon("Administrator")).await.unwrap();
+
+    assert_eq!(event.tags, vec!["admin"]);
+    assert_eq!(event.severity, Severity::Critical);
+}
+
+#[tokio::test]
+async fn test_dropped_events_stop_the_pipeline() {
+    assert!(pipeline().run(logon("svc_backup")).await.is_none());
+    assert!(pipeline().run(logon("alice")).await.is_some());
+}
+
+#[tokio::test]
+async fn test_empty_pipeline_passes_events_through() {
+    let event = Pipeline::new().run(logon("alice")).await.unwrap();
+
+    assert!(event.tags.is_empty());
+    assert_eq!(event.severity, Severity::Info);
+}
+
+/// Records every event it receives.
+#[derive(Clone, Default)]
+struct RecordingSink(Arc<Mutex<Vec<Event>>>);
+
+#[async_trait]
+impl Sink for RecordingSink {
+    fn name(&self) -> &str {
+        "recording"
+    }
+
+    async fn emit(&self, event: &Event) -> Result<(), SentinelError> {
+        self.0.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_tap_sees_events_before_later_transforms() {
+    let raw = RecordingSink::default();
+    let transforms = with_rules(Pipeline::new().with_transform(Tap::new(raw.clone())));
+
+    let enriched = transforms.run(logon("Administrator")).await.unwrap();
+    assert!(transforms.run(logon("svc_backup")).await.is_none());
+
+    assert_eq!(enriched.severity, Severity::Critical);
+    let raw = raw.0.lock().unwrap();
+    assert_eq!(raw.len(), 2, "suppressed events are still tapped");
+    assert!(raw[0].tags.is_empty());
+    assert_eq!(raw[0].severity, Severity::Info);
+}
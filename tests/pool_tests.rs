@@ -0,0 +1,130 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use hosho::listener::pool::QueryPool;
+use tokio::task::JoinSet;
+
+/// Counts simulated queries in flight and the most ever in flight at once.
+#[derive(Default)]
+struct Gauge {
+    /// In flight now, and the most seen.
+    counts: Mutex<(usize, usize)>,
+    changed: Condvar,
+}
+
+/// Runs more simulated blocking queries through `pool` than it has slots, returning the most
+/// that ran at once. Each query holds its slot until one more than `bound` are in flight, which
+/// a working bound never allows, so each gives up after a short timeout instead.
+async fn max_in_flight(pool: QueryPool, bound: usize) -> usize {
+    let gauge = Arc::new(Gauge::default());
+    let mut queries = JoinSet::new();
+    for _ in 0..bound + 2 {
+        let pool = pool.clone();
+        let gauge = Arc::clone(&gauge);
+        queries.spawn(async move {
+            pool.run(move || {
+                let mut counts = gauge.counts.lock().unwrap();
+                counts.0 += 1;
+                counts.1 = counts.1.max(counts.0);
+                gauge.changed.notify_all();
+                let (mut counts, _) = gauge
+                    .changed
+                    .wait_timeout_while(counts, Duration::from_millis(100), |counts| {
+                        counts.0 < bound + 1
+                    })
+                    .unwrap();
+                counts.0 -= 1;
+            })
+            .await
+        });
+    }
+    while let Some(result) = queries.join_next().await {
+        result.unwrap().unwrap();
+    }
+    gauge.counts.lock().unwrap().1
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_queries_run_concurrently() {
+    assert_eq!(max_in_flight(QueryPool::new(4), 4).await, 4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pool_bounds_concurrency() {
+    assert_eq!(max_in_flight(QueryPool::new(1), 1).await, 1);
+    assert_eq!(max_in_flight(QueryPool::new(2), 2).await, 2);
+}
+
+/// Queries that each hold their slot until the test releases them, in the order they started.
+#[derive(Default)]
+struct Lockstep {
+    /// How many queries have started, been released, and finished.
+    steps: Mutex<(usize, usize, usize)>,
+    changed: Condvar,
+}
+
+/// Runs `queries` simulated blocking queries through `pool`, returning how many rounds they took:
+/// each round waits for every slot that can be filled to be filled, then releases them all. The
+/// round count stands in for latency, in units of one query's duration, without timing anything.
+async fn rounds(pool: QueryPool, bound: usize, queries: usize) -> usize {
+    let lockstep = Arc::new(Lockstep::default());
+    let mut running = JoinSet::new();
+    for _ in 0..queries {
+        let pool = pool.clone();
+        let lockstep = Arc::clone(&lockstep);
+        running.spawn(async move {
+            pool.run(move || {
+                let mut steps = lockstep.steps.lock().unwrap();
+                let ticket = steps.0;
+                steps.0 += 1;
+                lockstep.changed.notify_all();
+                let mut steps = lockstep
+                    .changed
+                    .wait_while(steps, |steps| steps.1 <= ticket)
+                    .unwrap();
+                steps.2 += 1;
+                lockstep.changed.notify_all();
+            })
+            .await
+        });
+    }
+
+    let driver = Arc::clone(&lockstep);
+    let rounds = tokio::task::spawn_blocking(move || {
+        let mut rounds = 0;
+        let mut steps = driver.steps.lock().unwrap();
+        while steps.2 < queries {
+            let fillable = bound.min(queries - steps.2);
+            (steps, _) = driver
+                .changed
+                .wait_timeout_while(steps, Duration::from_secs(5), |steps| {
+                    steps.0 - steps.2 < fillable
+                })
+                .unwrap();
+            rounds += 1;
+            steps.1 = steps.0;
+            driver.changed.notify_all();
+            steps = driver
+                .changed
+                .wait_while(steps, |steps| steps.2 < steps.1)
+                .unwrap();
+        }
+        rounds
+    })
+    .await
+    .unwrap();
+
+    while let Some(result) = running.join_next().await {
+        result.unwrap().unwrap();
+    }
+    rounds
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_concurrent_queries_beat_sequential() {
+    let sequential = rounds(QueryPool::new(1), 1, 8).await;
+    let concurrent = rounds(QueryPool::new(4), 4, 8).await;
+
+    assert_eq!(sequential, 8);
+    assert_eq!(concurrent, 2);
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LockoutEvent, LogonEvent, RenderingInfo};
+use hosho::sink::redact::{Field, Redaction, RedactionRule, Redactor};
+
+fn logon(username: &str, source_ip: &str) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new(username, Some("CORP")),
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant: LogonVariant::Network,
+            success: false,
+            event_record_id: Some(1),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        Utc::now(),
+    )
+}
+
+fn login(event: &Event) -> &LogonEvent {
+    match &event.details {
+        EventDetails::Login(login) => login,
+        _ => panic!("expected a logon event"),
+    }
+}
+
+#[test]
+fn test_network_redaction_keeps_prefix() {
+    let redactor = Redactor::new("salt").with_rule(Field::SourceIp, Redaction::Network);
+
+    let redacted = redactor.apply(&logon("alice", "203.0.113.77"));
+    assert_eq!(login(&redacted).source_ip, "203.0.113.0/24");
+
+    let redacted = redactor.apply(&logon("alice", "2001:db8:85a3::8a2e:370:7334"));
+    assert_eq!(login(&redacted).source_ip, "2001:db8:85a3::/48");
+
+    let redacted = redactor.apply(&logon("alice", "-"));
+    assert_eq!(login(&redacted).source_ip, "[redacted]");
+}
+
+#[test]
+fn test_hash_redaction_correlates_within_a_salt() {
+    let redactor = Redactor::new("salt").with_rule(Field::Username, Redaction::Hash);
+
+    let first = redactor.apply(&logon("alice", "10.0.0.5"));
+    let second = redactor.apply(&logon("alice", "10.0.0.6"));
+    let other = redactor.apply(&logon("bob", "10.0.0.5"));
+    assert_eq!(login(&first).target, login(&second).target);
+    assert_ne!(login(&first).target, login(&other).target);
+    assert!(!login(&first).username().contains("alice"));
+
+    let resalted = Redactor::new("pepper").with_rule(Field::Username, Redaction::Hash);
+    let third = resalted.apply(&logon("alice", "10.0.0.5"));
+    assert_ne!(login(&first).target, login(&third).target);
+}
+
+#[test]
+fn test_redaction_leaves_original_untouched() {
+    let redactor = Redactor::new("salt")
+        .with_rule(Field::Username, Redaction::Mask)
+        .with_rule(Field::SourceIp, Redaction::Mask);
+    let event = logon("alice", "203.0.113.77");
+
+    let redacted = redactor.apply(&event);
+    assert_eq!(login(&redacted).username(), "[redacted]");
+    assert_eq!(login(&redacted).source_ip, "[redacted]");
+    assert_eq!(login(&event).username(), "alice@CORP");
+}
+
+#[test]
+fn test_redaction_drops_the_rendered_message() {
+    let mut event = logon("alice", "203.0.113.77");
+    event.rendering = Some(RenderingInfo {
+        message: Some("An account failed to log on. Account Name: alice".to_string()),
+        level: Some("Information".to_string()),
+        task: Some("Logon".to_string()),
+    });
+
+    let redacted = Redactor::new("salt")
+        .with_rule(Field::SourceIp, Redaction::Network)
+        .apply(&event);
+    let rendering = redacted.rendering.unwrap();
+    assert_eq!(rendering.message, None);
+    assert_eq!(rendering.task.as_deref(), Some("Logon"));
+
+    let untouched = Redactor::new("salt").apply(&event);
+    assert!(untouched.rendering.unwrap().message.is_some());
+}
+
+#[test]
+fn test_lockout_causes_are_redacted() {
+    let mut cause = login(&logon("alice", "203.0.113.77")).clone();
+    cause.source_hostname = Some("attacker.example".to_string());
+    let event = Event::new(
+        EventDetails::Lockout(LockoutEvent {
+            account: Account::new("alice", None),
+            caller_computer: Some("WS1".to_string()),
+            causes: vec![cause],
+            event_record_id: Some(2),
+        }),
+        Utc::now(),
+    );
+
+    let redacted = Redactor::new("salt")
+        .with_rule(Field::SourceIp, Redaction::Network)
+        .with_rule(Field::SourceHostname, Redaction::Mask)
+        .with_rule(Field::Username, Redaction::Mask)
+        .apply(&event);
+    let EventDetails::Lockout(lockout) = &redacted.details else {
+        panic!("expected a lockout event");
+    };
+    assert_eq!(lockout.account.user, "[redacted]");
+    assert_eq!(lockout.caller_computer.as_deref(), Some("[redacted]"));
+    let cause = &lockout.causes[0];
+    assert_eq!(cause.source_ip, "203.0.113.0/24");
+    assert_eq!(cause.source_ip_raw, "203.0.113.0/24");
+    assert_eq!(cause.source_hostname.as_deref(), Some("[redacted]"));
+    assert_eq!(cause.username(), "[redacted]");
+}
+
+#[test]
+fn test_redaction_rules_parse_from_cli() {
+    assert_eq!(
+        "source_ip=network".parse(),
+        Ok(RedactionRule {
+            field: Field::SourceIp,
+            redaction: Redaction::Network
+        })
+    );
+    assert!("source_ip".parse::<RedactionRule>().is_err());
+    assert!("password=mask".parse::<RedactionRule>().is_err());
+}
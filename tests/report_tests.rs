@@ -0,0 +1,87 @@
+use chrono::{DateTime, Duration, Utc};
+use hosho::listener::lockout::LockoutEvent;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::report::PerUserReport;
+
+fn start() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2025-07-22T08:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn logon(user: &str, source_ip: &str, success: bool, offset_mins: i64) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(user, Some("CORP")),
+            source_ip,
+            LogonVariant::Network,
+            success,
+        )),
+        start() + Duration::minutes(offset_mins),
+    )
+}
+
+#[test]
+fn test_report_groups_activity_by_user() {
+    let mut locked_out = logon("bob", "10.0.0.9", false, 30);
+    if let EventDetails::Login(login) = &mut locked_out.details {
+        login.failure_reason = Some("%%2307".to_string());
+        login.attempt_count = 3;
+    }
+    let events = vec![
+        logon("alice", "10.0.0.5", false, 0),
+        logon("alice", "10.0.0.5", true, 5),
+        logon("bob", "-", false, 10),
+        locked_out,
+        logon("WKS01$", "-", true, 20),
+        Event::new(
+            EventDetails::Lockout(LockoutEvent {
+                account: Account::new("BOB", None),
+                caller_computer: None,
+                causes: Vec::new(),
+                event_record_id: Some(9),
+            }),
+            start() + Duration::minutes(31),
+        ),
+        logon("Alice", "10.0.0.6", true, 60),
+        Event::self_test(),
+    ];
+
+    let report = PerUserReport::new().build(&events);
+    assert_eq!(report.start, Some(start()));
+    assert_eq!(report.end, Some(start() + Duration::minutes(60)));
+    assert_eq!(report.users.len(), 3);
+
+    let alice = &report.users["alice@corp"];
+    assert_eq!((alice.logons, alice.failures, alice.lockouts), (2, 1, 0));
+    assert_eq!(alice.last_seen, start() + Duration::minutes(60));
+    assert_eq!(
+        alice.sources.iter().collect::<Vec<_>>(),
+        vec!["10.0.0.5", "10.0.0.6"]
+    );
+
+    // Only failures, no successful logon.
+    let bob = &report.users["bob@corp"];
+    assert_eq!((bob.logons, bob.failures, bob.lockouts), (0, 4, 1));
+    assert_eq!(bob.locked_out_failures, 3);
+    assert_eq!(bob.last_seen, start() + Duration::minutes(31));
+    assert_eq!(bob.first_seen, start() + Duration::minutes(10));
+}
+
+#[test]
+fn test_report_can_exclude_machine_accounts() {
+    let events = vec![
+        logon("alice", "10.0.0.5", true, 0),
+        logon("WKS01$", "-", true, 1),
+    ];
+
+    let report = PerUserReport::new()
+        .with_machine_accounts_excluded(true)
+        .build(&events);
+    assert_eq!(report.users.keys().collect::<Vec<_>>(), vec!["alice@corp"]);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["users"]["alice@corp"]["logons"], 1);
+    assert_eq!(json["users"]["alice@corp"]["account"]["user"], "alice");
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use hosho::errors::SentinelError;
+use hosho::listener::pool::QueryPool;
+use hosho::listener::retry::RetryPolicy;
+
+/// Fails with each error in turn, then succeeds, counting the attempts.
+fn flaky(
+    errors: Vec<SentinelError>,
+) -> (
+    impl FnMut() -> anyhow::Result<u32> + Send + 'static,
+    Arc<AtomicU32>,
+) {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&attempts);
+    let mut errors = errors.into_iter();
+    let query = move || {
+        let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        match errors.next() {
+            Some(e) => Err(e.into()),
+            None => Ok(attempt),
+        }
+    };
+    (query, attempts)
+}
+
+fn policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy::new(max_retries, Duration::ZERO)
+}
+
+async fn run(
+    policy: RetryPolicy,
+    query: impl FnMut() -> anyhow::Result<u32> + Send + 'static,
+) -> anyhow::Result<u32> {
+    policy.run(&QueryPool::new(1), query).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_transient_errors_are_retried_until_success() {
+    let (query, attempts) = flaky(vec![
+        SentinelError::Rpc("The RPC server is unavailable.".to_string()),
+        SentinelError::EventQueryError("timed out".to_string()),
+    ]);
+
+    assert_eq!(run(policy(2), query).await.unwrap(), 3);
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_retries_are_capped() {
+    let (query, attempts) = flaky(vec![
+        SentinelError::Rpc("unavailable".to_string()),
+        SentinelError::Rpc("unavailable".to_string()),
+        SentinelError::Rpc("unavailable".to_string()),
+    ]);
+
+    let error = run(policy(2), query).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<SentinelError>(),
+        Some(SentinelError::Rpc(_))
+    ));
+    assert_eq!(
+        attempts.load(Ordering::SeqCst),
+        3,
+        "first attempt plus two retries"
+    );
+}
+
+#[tokio::test]
+async fn test_permanent_errors_are_not_retried() {
+    for error in [
+        SentinelError::QueryMalformed("Security".to_string()),
+        SentinelError::AccessDenied("Security".to_string()),
+        SentinelError::ChannelNotFound("Microsoft-Windows-Foo/Operational".to_string()),
+    ] {
+        let (query, attempts) = flaky(vec![error]);
+        assert!(run(policy(5), query).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    let (mut query, attempts) = flaky(Vec::new());
+    let parse_failure = move || -> anyhow::Result<u32> {
+        query()?;
+        Err(anyhow::anyhow!("missing EventData"))
+    };
+    assert!(run(policy(5), parse_failure).await.is_err());
+    assert_eq!(
+        attempts.load(Ordering::SeqCst),
+        1,
+        "errors from outside the event log aren't retried"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff_releases_the_pool_slot() {
+    let pool = QueryPool::new(1);
+    let (query, attempts) = flaky(vec![SentinelError::Rpc("unavailable".to_string())]);
+    let retrying = {
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            RetryPolicy::new(1, Duration::from_secs(5))
+                .run(&pool, query)
+                .await
+                .unwrap()
+        })
+    };
+    // Let the first attempt fail and the backoff begin.
+    while attempts.load(Ordering::SeqCst) == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    // Another listener's query gets the only slot while the first waits out its backoff.
+    let other = pool.run(|| 7).await.unwrap();
+    assert_eq!(other, 7);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+    assert_eq!(retrying.await.unwrap().unwrap(), 2);
+}
@@ -0,0 +1,76 @@
+use chrono::{TimeZone, Utc};
+use hosho::enrich::RiskScorer;
+use hosho::enrich::risk::{MAX_RISK_SCORE, RiskWeights};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::sink::DisplayTz;
+
+fn logon(source_ip: &str, variant: LogonVariant, success: bool, hour: u32) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new("admin", None),
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant,
+            success,
+            event_record_id: Some(1),
+            ..Default::default()
+        }),
+        Utc.with_ymd_and_hms(2025, 6, 2, hour, 30, 0).unwrap(),
+    )
+}
+
+fn utc() -> DisplayTz {
+    "UTC".parse().unwrap()
+}
+
+fn utc_scorer(weights: RiskWeights) -> RiskScorer {
+    RiskScorer::new(weights).with_display_tz(utc())
+}
+
+#[test]
+fn test_public_rdp_failure_outscores_local_service_logon() {
+    let scorer = utc_scorer(RiskWeights::default());
+
+    let mut rdp = logon("203.0.113.7", LogonVariant::RemoteInteractive, false, 12);
+    let mut service = logon("127.0.0.1", LogonVariant::Service, true, 12);
+    scorer.enrich(&mut rdp);
+    scorer.enrich(&mut service);
+
+    assert_eq!(rdp.risk_score, Some(75));
+    assert_eq!(service.risk_score, Some(0));
+}
+
+#[test]
+fn test_score_is_capped() {
+    let weights = RiskWeights {
+        remote_interactive: 200,
+        public_source: 200,
+        ..Default::default()
+    };
+    let scorer = utc_scorer(weights);
+
+    let mut event = logon("203.0.113.7", LogonVariant::RemoteInteractive, false, 2);
+    scorer.enrich(&mut event);
+    assert_eq!(event.risk_score, Some(MAX_RISK_SCORE));
+}
+
+#[test]
+fn test_off_hours_adds_weight() {
+    let scorer = utc_scorer(RiskWeights::default()).with_business_hours(9, 17);
+
+    let mut day = logon("10.0.0.5", LogonVariant::Interactive, true, 9);
+    let mut night = logon("10.0.0.5", LogonVariant::Interactive, true, 17);
+    scorer.enrich(&mut day);
+    scorer.enrich(&mut night);
+
+    assert_eq!(day.risk_score, Some(10));
+    assert_eq!(night.risk_score, Some(25));
+}
+
+#[test]
+fn test_non_logon_events_are_not_scored() {
+    let mut event = Event::new(EventDetails::Heartbeat { seq: 1 }, Utc::now());
+    RiskScorer::default().enrich(&mut event);
+    assert_eq!(event.risk_score, None);
+}
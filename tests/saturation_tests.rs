@@ -0,0 +1,46 @@
+use hosho::listener::saturation::{ChannelSaturation, channel};
+
+#[tokio::test]
+async fn test_gauge_tracks_high_water_mark() {
+    let (tx, mut rx) = channel("logon", 4);
+    let gauge = rx.gauge();
+
+    for i in 0..3 {
+        tx.send(i).await.unwrap();
+    }
+    for _ in 0..3 {
+        rx.recv().await.unwrap();
+    }
+    tx.send(3).await.unwrap();
+    rx.recv().await.unwrap();
+
+    assert_eq!(
+        gauge.take(),
+        ChannelSaturation {
+            name: "logon",
+            capacity: 4,
+            high_water: 3,
+            times_full: 0,
+        }
+    );
+    assert_eq!(gauge.take().high_water, 0, "taking resets the gauge");
+}
+
+#[tokio::test]
+async fn test_gauge_counts_full_channel() {
+    let (tx, mut rx) = channel("usb", 2);
+    let gauge = rx.gauge();
+
+    tx.send(1).await.unwrap();
+    tx.send(2).await.unwrap();
+    rx.recv().await.unwrap();
+    rx.recv().await.unwrap();
+
+    let saturation = gauge.take();
+    assert_eq!(saturation.high_water, 2);
+    assert_eq!(saturation.times_full, 1);
+    assert_eq!(
+        saturation.to_string(),
+        "usb: peaked at 2/2 queued, full 1 times"
+    );
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use hosho::listener::schedule::{PollSchedule, listener_seed};
+
+#[test]
+fn test_poll_schedule_without_jitter_is_fixed() {
+    let mut schedule = PollSchedule::new(Duration::from_secs(1), Duration::ZERO, Some(7));
+
+    for _ in 0..10 {
+        assert_eq!(schedule.next_delay(), Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn test_poll_schedule_jitter_stays_in_bounds() {
+    let interval = Duration::from_secs(5);
+    let jitter = Duration::from_millis(500);
+    let mut schedule = PollSchedule::new(interval, jitter, Some(42));
+
+    for _ in 0..1000 {
+        let delay = schedule.next_delay();
+        assert!(delay >= interval - jitter, "delay {:?} below bound", delay);
+        assert!(delay <= interval + jitter, "delay {:?} above bound", delay);
+    }
+}
+
+#[test]
+fn test_poll_schedule_same_seed_is_reproducible() {
+    let mut a = PollSchedule::new(
+        Duration::from_secs(1),
+        Duration::from_millis(250),
+        Some(1234),
+    );
+    let mut b = PollSchedule::new(
+        Duration::from_secs(1),
+        Duration::from_millis(250),
+        Some(1234),
+    );
+
+    let delays_a: Vec<_> = (0..20).map(|_| a.next_delay()).collect();
+    let delays_b: Vec<_> = (0..20).map(|_| b.next_delay()).collect();
+    assert_eq!(delays_a, delays_b);
+
+    assert!(
+        delays_a.iter().any(|d| *d != Duration::from_secs(1)),
+        "jitter should vary the delay"
+    );
+}
+
+#[test]
+fn test_poll_schedule_jitter_is_clamped_below_interval() {
+    let interval = Duration::from_millis(100);
+    let mut schedule = PollSchedule::new(interval, Duration::from_secs(1), Some(9));
+
+    for _ in 0..100 {
+        let delay = schedule.next_delay();
+        assert!(!delay.is_zero(), "a poll should never follow immediately");
+        assert!(delay < interval * 2, "delay {:?} above bound", delay);
+    }
+}
+
+#[test]
+fn test_listener_seeds_differ_but_are_reproducible() {
+    assert_eq!(listener_seed(None, "usb"), None);
+    assert_eq!(listener_seed(Some(7), "usb"), listener_seed(Some(7), "usb"));
+    assert_ne!(
+        listener_seed(Some(7), "usb"),
+        listener_seed(Some(7), "lockout")
+    );
+
+    let interval = Duration::from_secs(1);
+    let jitter = Duration::from_millis(250);
+    let mut usb = PollSchedule::new(interval, jitter, listener_seed(Some(7), "usb"));
+    let mut lockout = PollSchedule::new(interval, jitter, listener_seed(Some(7), "lockout"));
+    let usb_delays: Vec<_> = (0..20).map(|_| usb.next_delay()).collect();
+    let lockout_delays: Vec<_> = (0..20).map(|_| lockout.next_delay()).collect();
+    assert_ne!(usb_delays, lockout_delays);
+}
@@ -0,0 +1,66 @@
+use chrono::{TimeZone, Utc};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::schema::{MIN_SCHEMA_VERSION, SCHEMA_VERSION};
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::sink::{DisplayTz, OutputFormat};
+use serde_json::{Value, json};
+
+fn sample_logon() -> Event {
+    let mut event = Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new("alice", Some("CORP")),
+            "10.0.0.5",
+            LogonVariant::Network,
+            true,
+        )),
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    );
+    event.tags = vec!["vpn".to_string()];
+    event
+}
+
+fn to_value(event: &Event) -> Value {
+    serde_json::from_str(&OutputFormat::Json.render(event, DisplayTz::default())).unwrap()
+}
+
+#[test]
+fn test_json_output_is_stamped_with_schema_version() {
+    let doc = to_value(&sample_logon());
+    assert_eq!(doc["schema_version"], json!(SCHEMA_VERSION));
+}
+
+#[test]
+fn test_json_output_round_trips() {
+    let doc = to_value(&sample_logon());
+    let event: Event = serde_json::from_value(doc.clone()).unwrap();
+    assert_eq!(to_value(&event), doc);
+}
+
+#[test]
+fn test_previous_version_without_newer_fields_deserializes() {
+    let mut doc = to_value(&sample_logon());
+    doc["schema_version"] = json!(MIN_SCHEMA_VERSION);
+    let object = doc.as_object_mut().unwrap();
+    object.remove("tags");
+    object.remove("severity");
+    let login = doc["details"]["Login"].as_object_mut().unwrap();
+    login.remove("impersonation_level");
+    login.remove("target_server");
+    login.remove("firewall_action");
+    login.remove("event_id");
+
+    let event: Event = serde_json::from_value(doc).unwrap();
+    assert!(event.tags.is_empty());
+    assert_eq!(to_value(&event)["schema_version"], json!(SCHEMA_VERSION));
+}
+
+#[test]
+fn test_unsupported_versions_are_rejected() {
+    for version in [MIN_SCHEMA_VERSION - 1, SCHEMA_VERSION + 1] {
+        let mut doc = to_value(&sample_logon());
+        doc["schema_version"] = json!(version);
+
+        let error = serde_json::from_value::<Event>(doc).unwrap_err();
+        assert!(error.to_string().contains("unsupported schema version"));
+    }
+}
@@ -0,0 +1,48 @@
+use hosho::listener::screen_lock::parse_screen_lock_event;
+
+fn lock_event_xml(event_id: u32) -> String {
+    format!(
+        r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-Security-Auditing' Guid='{{54849625-5478-4994-a5ba-3e3b0328c30d}}'/>
+        <EventID>{}</EventID>
+        <TimeCreated SystemTime='2025-07-22T18:02:11.1234560Z'/>
+        <EventRecordID>8486001</EventRecordID>
+        <Channel>Security</Channel>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='TargetUserSid'>S-1-5-21-1004336348-1177238915-682003330-1001</Data>
+        <Data Name='TargetUserName'>xevion</Data>
+        <Data Name='TargetDomainName'>ETHER</Data>
+        <Data Name='TargetLogonId'>0x4b2f1a</Data>
+        <Data Name='SessionId'>1</Data>
+    </EventData>
+</Event>
+        "#,
+        event_id
+    )
+}
+
+#[test]
+fn test_parse_screen_lock_event() {
+    let (_, lock_event) =
+        parse_screen_lock_event(&lock_event_xml(4800)).expect("4800 should parse");
+
+    assert!(lock_event.locked);
+    assert_eq!(lock_event.username, "xevion@ETHER");
+}
+
+#[test]
+fn test_parse_screen_unlock_event() {
+    let (_, lock_event) =
+        parse_screen_lock_event(&lock_event_xml(4801)).expect("4801 should parse");
+
+    assert!(!lock_event.locked);
+}
+
+#[test]
+fn test_parse_screen_lock_event_rejects_other_ids() {
+    assert!(parse_screen_lock_event(&lock_event_xml(4624)).is_err());
+}
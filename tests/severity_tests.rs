@@ -0,0 +1,133 @@
+use chrono::{TimeZone, Utc};
+use hosho::enrich::severity::{Condition, SeverityRule};
+use hosho::enrich::{Severity, SeverityPolicy};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::sink::DisplayTz;
+
+fn failure(source_ip: &str, variant: LogonVariant, attempt_count: u32, hour: u32) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new("admin", None),
+            source_ip: source_ip.to_string(),
+            source_ip_raw: source_ip.to_string(),
+            variant,
+            success: false,
+            event_record_id: Some(1),
+            attempt_count,
+            ..Default::default()
+        }),
+        Utc.with_ymd_and_hms(2025, 6, 2, hour, 30, 0).unwrap(),
+    )
+}
+
+fn utc() -> DisplayTz {
+    "UTC".parse().unwrap()
+}
+
+fn utc_policy() -> SeverityPolicy {
+    SeverityPolicy::default().with_display_tz(utc())
+}
+
+#[test]
+fn test_default_policy_grades_logon_failures() {
+    let policy = utc_policy();
+
+    let lan = failure("10.0.0.5", LogonVariant::Network, 1, 10);
+    assert_eq!(policy.evaluate(&lan), Severity::Low);
+
+    let public = failure("203.0.113.9", LogonVariant::Network, 1, 10);
+    assert_eq!(policy.evaluate(&public), Severity::Medium);
+
+    let public_rdp = failure("203.0.113.9", LogonVariant::RemoteInteractive, 1, 10);
+    assert_eq!(policy.evaluate(&public_rdp), Severity::High);
+
+    let public_rdp_burst = failure("203.0.113.9", LogonVariant::RemoteInteractive, 25, 10);
+    assert_eq!(policy.evaluate(&public_rdp_burst), Severity::Critical);
+}
+
+#[test]
+fn test_suspicious_account_raises_severity() {
+    let mut event = failure("10.0.0.5", LogonVariant::Network, 1, 10);
+    if let EventDetails::Login(login) = &mut event.details {
+        login.suspicious_account = true;
+    }
+    assert_eq!(utc_policy().evaluate(&event), Severity::High);
+}
+
+#[test]
+fn test_anonymous_network_logon_raises_severity() {
+    let mut event = failure("10.0.0.5", LogonVariant::Network, 1, 10);
+    if let EventDetails::Login(login) = &mut event.details {
+        login.target = Account::new("ANONYMOUS LOGON", Some("NT AUTHORITY"));
+    }
+    assert_eq!(utc_policy().evaluate(&event), Severity::High);
+}
+
+#[test]
+fn test_off_hours_raises_severity() {
+    let policy = utc_policy();
+
+    let night = failure("10.0.0.5", LogonVariant::Network, 1, 3);
+    assert_eq!(policy.evaluate(&night), Severity::Medium);
+}
+
+#[test]
+fn test_off_hours_only_raises_failed_logons() {
+    let policy = utc_policy();
+
+    let mut success = failure("203.0.113.9", LogonVariant::Network, 1, 3);
+    if let EventDetails::Login(login) = &mut success.details {
+        login.success = true;
+    }
+    assert_eq!(policy.evaluate(&success), Severity::Low);
+
+    let mut self_test = Event::self_test();
+    self_test.timestamp = Utc.with_ymd_and_hms(2025, 6, 2, 3, 30, 0).unwrap();
+    assert_eq!(policy.evaluate(&self_test), Severity::Info);
+}
+
+#[test]
+fn test_off_hours_wraps_past_midnight() {
+    let policy = SeverityPolicy::empty()
+        .with_display_tz(utc())
+        .with_rule(SeverityRule::new(
+            vec![Condition::OffHours {
+                start_hour: 22,
+                end_hour: 6,
+            }],
+            Severity::High,
+        ));
+
+    let night_shift = failure("10.0.0.5", LogonVariant::Network, 1, 23);
+    assert_eq!(policy.evaluate(&night_shift), Severity::Low);
+    let early = failure("10.0.0.5", LogonVariant::Network, 1, 2);
+    assert_eq!(policy.evaluate(&early), Severity::Low);
+    let midday = failure("10.0.0.5", LogonVariant::Network, 1, 12);
+    assert_eq!(policy.evaluate(&midday), Severity::High);
+}
+
+#[test]
+fn test_custom_rules_override_defaults() {
+    let policy = SeverityPolicy::empty().with_rule(SeverityRule::new(
+        vec![Condition::LogonType(LogonVariant::Network)],
+        Severity::High,
+    ));
+
+    let mut event = failure("10.0.0.5", LogonVariant::Network, 1, 10);
+    policy.enrich(&mut event);
+    assert_eq!(event.severity, Severity::High);
+
+    let self_test = Event::self_test();
+    assert_eq!(policy.evaluate(&self_test), Severity::Info);
+}
+
+#[test]
+fn test_default_policy_raises_audit_keyword_mismatch() {
+    let mut forged = failure("10.0.0.5", LogonVariant::Network, 1, 10);
+    if let EventDetails::Login(login) = &mut forged.details {
+        login.event_id = Some(4625);
+        login.keywords = Some(0x8020000000000000);
+    }
+    assert_eq!(utc_policy().evaluate(&forged), Severity::High);
+}
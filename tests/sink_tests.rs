@@ -0,0 +1,205 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use hosho::errors::SentinelError;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, EventKind, LogonEvent};
+use hosho::sink::file::FileSink;
+use hosho::sink::follow::FollowPrinter;
+use hosho::sink::projection::Projection;
+use hosho::sink::spec::SinkSpec;
+use hosho::sink::{DisplayTz, MultiSink, OutputFormat, Sink, format_event, format_event_in};
+
+struct FailingSink;
+
+#[async_trait]
+impl Sink for FailingSink {
+    fn name(&self) -> &str {
+        "failing"
+    }
+
+    async fn emit(&self, _event: &Event) -> Result<(), SentinelError> {
+        Err(SentinelError::SinkError("always fails".to_string()))
+    }
+}
+
+struct CountingSink(Arc<AtomicUsize>);
+
+#[async_trait]
+impl Sink for CountingSink {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    async fn emit(&self, _event: &Event) -> Result<(), SentinelError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_multi_sink_isolates_failures() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let sinks = MultiSink::new()
+        .with_sink(FailingSink)
+        .with_sink(CountingSink(Arc::clone(&count)));
+
+    let result = sinks.emit(&Event::self_test()).await;
+
+    assert!(result.is_err(), "a failing sink should be reported");
+    assert_eq!(
+        count.load(Ordering::SeqCst),
+        1,
+        "sinks after a failing one should still receive the event"
+    );
+
+    let results = sinks.emit_each(&Event::self_test()).await;
+    let names: Vec<_> = results.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, vec!["failing", "counting"]);
+    assert!(results[0].1.is_err());
+    assert!(results[1].1.is_ok());
+}
+
+#[tokio::test]
+async fn test_file_sink_appends_lines() {
+    let path = std::env::temp_dir().join(format!("hosho-file-sink-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let sink = FileSink::open(&path).await.expect("file sink should open");
+    sink.emit(&Event::self_test()).await.unwrap();
+    sink.emit(&Event::self_test()).await.unwrap();
+    sink.close().await.unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<_> = contents.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.contains("[TEST]")));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_follow_printer_colors_only_when_enabled() {
+    let event = Event::self_test();
+    assert_eq!(
+        FollowPrinter::new(false).render(&event),
+        format_event(&event)
+    );
+    // Only logon events are colored.
+    assert_eq!(
+        FollowPrinter::new(true).render(&event),
+        format_event(&event)
+    );
+}
+
+#[test]
+fn test_display_tz_renders_in_named_zone() {
+    let display_tz: DisplayTz = "Asia/Tokyo".parse().unwrap();
+    let event = Event::new(
+        EventDetails::Heartbeat { seq: 1 },
+        Utc.with_ymd_and_hms(2025, 6, 2, 23, 30, 0).unwrap(),
+    );
+
+    assert_eq!(
+        format_event_in(&event, display_tz),
+        "Event: Heartbeat #1 on Tuesday, June 03, 2025 at 08:30:00 AM"
+    );
+    assert_eq!("LOCAL".parse::<DisplayTz>().unwrap(), DisplayTz::Local);
+}
+
+#[test]
+fn test_unknown_display_tz_is_a_config_error() {
+    assert!(matches!(
+        "Mars/Olympus_Mons".parse::<DisplayTz>(),
+        Err(SentinelError::ConfigError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sinks_only_receive_events_they_are_interested_in() {
+    let logins = Arc::new(AtomicUsize::new(0));
+    let lockouts = Arc::new(AtomicUsize::new(0));
+    let everything = Arc::new(AtomicUsize::new(0));
+    let sinks = MultiSink::new()
+        .with_sink_for(CountingSink(Arc::clone(&logins)), &[EventKind::Login])
+        .with_sink_where(CountingSink(Arc::clone(&lockouts)), |event| {
+            matches!(&event.details, EventDetails::Login(login)
+                if login.failure_reason.as_deref() == Some("%%2307"))
+        })
+        .with_sink(CountingSink(Arc::clone(&everything)));
+
+    let login = Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new("alice", None),
+            "10.0.0.5",
+            LogonVariant::Network,
+            true,
+        )),
+        Utc::now(),
+    );
+    let heartbeat = Event::new(EventDetails::Heartbeat { seq: 1 }, Utc::now());
+    sinks.emit(&login).await.unwrap();
+    sinks
+        .emit_batch(&[heartbeat.clone(), login, heartbeat])
+        .await
+        .unwrap();
+
+    assert_eq!(logins.load(Ordering::SeqCst), 2);
+    assert_eq!(lockouts.load(Ordering::SeqCst), 0);
+    assert_eq!(everything.load(Ordering::SeqCst), 4);
+
+    // Self-tests check every sink, whatever it's interested in.
+    let results = sinks.emit_each(&Event::self_test()).await;
+    assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn test_sink_specs_parse_kind_and_argument() {
+    assert_eq!("stdout".parse::<SinkSpec>().unwrap(), SinkSpec::Stdout);
+    assert_eq!(
+        r"file:C:\logs\hosho.log".parse::<SinkSpec>().unwrap(),
+        SinkSpec::File(r"C:\logs\hosho.log".into())
+    );
+    assert_eq!(
+        "archive:events.bin".parse::<SinkSpec>().unwrap(),
+        SinkSpec::Archive("events.bin".into())
+    );
+    assert!("file".parse::<SinkSpec>().is_err());
+    assert!("file:".parse::<SinkSpec>().is_err());
+    assert!("webhook:https://example.com".parse::<SinkSpec>().is_err());
+}
+
+#[test]
+fn test_projection_drops_fields_not_allowed() {
+    let login = Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new("alice", Some("CORP")),
+            "10.0.0.5",
+            LogonVariant::Network,
+            true,
+        )),
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    );
+    let projection = Projection::new(["timestamp", "details.Login.variant", "details.Missing"]);
+
+    let line = OutputFormat::Json.render_projected(&login, DisplayTz::default(), &projection);
+    let doc: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(
+        doc,
+        serde_json::json!({
+            "timestamp": "2025-06-01T12:00:00Z",
+            "details": { "Login": { "variant": "Network" } },
+        })
+    );
+    assert!(!line.contains("alice"));
+
+    let ecs: serde_json::Value = serde_json::from_str(&OutputFormat::Ecs.render_projected(
+        &login,
+        DisplayTz::default(),
+        &Projection::new(["source.ip"]),
+    ))
+    .unwrap();
+    assert_eq!(ecs, serde_json::json!({ "source": { "ip": "10.0.0.5" } }));
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use hosho::listener::schedule::PollSchedule;
+use hosho::listener::supervise::{self, RestartPolicy, supervise};
+use hosho::listener::{EventDetails, EventListener, HeartbeatListener, LogonListener, poll};
+use tokio::sync::mpsc;
+
+/// A listener with a parse bug: every poll panics.
+#[derive(Clone, Default)]
+struct PanickingListener {
+    polls: Arc<AtomicU32>,
+}
+
+impl EventListener for PanickingListener {
+    fn invoke(&self) {
+        self.polls.fetch_add(1, Ordering::SeqCst);
+        panic!("malformed event");
+    }
+}
+
+fn every_second() -> PollSchedule {
+    PollSchedule::new(Duration::from_secs(1), Duration::ZERO, None)
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_panicking_listener_does_not_stop_others() {
+    let policy = RestartPolicy::new(2, Duration::from_secs(1));
+
+    let panicking = PanickingListener::default();
+    let polls = Arc::clone(&panicking.polls);
+    let supervisor = tokio::spawn(supervise("panicking", policy, move || {
+        poll(panicking.clone(), every_second())
+    }));
+
+    let (tx, mut rx) = mpsc::channel(100);
+    let heartbeat = HeartbeatListener::new(tx);
+    tokio::spawn(supervise("heartbeat", policy, move || {
+        poll(heartbeat.clone(), every_second())
+    }));
+
+    supervisor.await.unwrap();
+    assert_eq!(
+        polls.load(Ordering::SeqCst),
+        3,
+        "first run plus two restarts"
+    );
+
+    let mut seqs = Vec::new();
+    while seqs.len() < 10 {
+        match rx
+            .recv()
+            .await
+            .expect("heartbeats should keep arriving")
+            .details
+        {
+            EventDetails::Heartbeat { seq } => seqs.push(seq),
+            other => panic!("expected a heartbeat, got {:?}", other),
+        }
+    }
+    assert_eq!(seqs, (1..=10).collect::<Vec<_>>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backoff_doubles_up_to_cap() {
+    let policy =
+        RestartPolicy::new(4, Duration::from_secs(1)).with_max_backoff(Duration::from_secs(3));
+    let starts = Arc::new(AtomicU32::new(0));
+    let started = tokio::time::Instant::now();
+
+    let counter = Arc::clone(&starts);
+    supervise("panicking", policy, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { panic!("malformed event") }
+    })
+    .await;
+
+    assert_eq!(starts.load(Ordering::SeqCst), 5);
+    // 1s + 2s + 3s + 3s between the five runs.
+    assert_eq!(started.elapsed(), Duration::from_secs(9));
+}
+
+#[tokio::test]
+async fn test_finished_task_is_not_restarted() {
+    let starts = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&starts);
+    supervise("oneshot", RestartPolicy::default(), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async {}
+    })
+    .await;
+
+    assert_eq!(starts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_panic_in_a_query_restarts_the_listener_and_is_reported() {
+    // Other tests' supervisors report here too, so only this one's reports are checked.
+    let (events_tx, mut events_rx) = mpsc::channel(100);
+    assert!(supervise::configure(events_tx));
+
+    let queries = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&queries);
+    let (tx, _rx) = mpsc::channel(10);
+    let listener =
+        LogonListener::new(tx).with_xml_source(Arc::new(move || -> anyhow::Result<Vec<String>> {
+            counter.fetch_add(1, Ordering::SeqCst);
+            panic!("malformed event")
+        }));
+    let schedule = every_second();
+    let policy = RestartPolicy::new(1, Duration::from_millis(10));
+
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        supervise("logon", policy, move || {
+            poll(listener.clone(), schedule.clone())
+        }),
+    )
+    .await
+    .expect("the query's panic should reach the supervisor");
+    assert_eq!(
+        queries.load(Ordering::SeqCst),
+        2,
+        "first run plus one restart"
+    );
+
+    let mut reports = Vec::new();
+    while let Ok(event) = events_rx.try_recv() {
+        if let EventDetails::ListenerPanicked {
+            listener,
+            message,
+            restarts,
+            restarting,
+        } = event.details
+            && listener == "logon"
+        {
+            assert_eq!(message, "malformed event");
+            reports.push((restarts, restarting));
+        }
+    }
+    assert_eq!(reports, vec![(1, true), (1, false)]);
+}
@@ -0,0 +1,107 @@
+use chrono::Utc;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::suppress::Suppressor;
+
+fn logon(username: &str, variant: LogonVariant) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent {
+            target: Account::new(username, None),
+            source_ip: "-".to_string(),
+            source_ip_raw: "-".to_string(),
+            variant,
+            success: false,
+            event_record_id: Some(1),
+            attempt_count: 1,
+            ..Default::default()
+        }),
+        Utc::now(),
+    )
+}
+
+#[test]
+fn test_all_rule_requires_every_condition() {
+    let suppressor = Suppressor::from_json(
+        r#"[{"all": [
+            {"field": "username", "equals": "svc_backup"},
+            {"field": "variant", "equals": "Service"}
+        ]}]"#,
+    )
+    .unwrap();
+
+    assert!(suppressor.is_suppressed(&logon("svc_backup", LogonVariant::Service)));
+    assert!(suppressor.is_suppressed(&logon("SVC_BACKUP", LogonVariant::Service)));
+    assert!(!suppressor.is_suppressed(&logon("svc_backup", LogonVariant::Network)));
+    assert!(!suppressor.is_suppressed(&logon("admin", LogonVariant::Service)));
+}
+
+#[test]
+fn test_any_rule_nested_in_all() {
+    let suppressor = Suppressor::from_json(
+        r#"[{"all": [
+            {"field": "variant", "equals": "Service"},
+            {"any": [
+                {"field": "username", "equals": "svc_backup"},
+                {"field": "username", "equals": "svc_sql"}
+            ]}
+        ]}]"#,
+    )
+    .unwrap();
+
+    assert!(suppressor.is_suppressed(&logon("svc_sql", LogonVariant::Service)));
+    assert!(!suppressor.is_suppressed(&logon("svc_web", LogonVariant::Service)));
+}
+
+#[test]
+fn test_unknown_fields_never_match() {
+    let suppressor =
+        Suppressor::from_json(r#"[{"field": "threat_name", "equals": "EICAR"}]"#).unwrap();
+
+    assert!(!suppressor.is_suppressed(&logon("admin", LogonVariant::Network)));
+    assert!(!suppressor.is_suppressed(&Event::self_test()));
+}
+
+#[test]
+fn test_malformed_rules_are_rejected() {
+    assert!(Suppressor::from_json(r#"[{"field": "username"}]"#).is_err());
+}
+
+#[test]
+fn test_in_network_rule_matches_addresses_in_range() {
+    let suppressor = Suppressor::from_json(
+        r#"[{"any": [
+            {"field": "source_ip", "in_network": "192.168.0.0/16"},
+            {"field": "source_ip", "in_network": "fd00::/8"}
+        ]}]"#,
+    )
+    .unwrap();
+    let from = |source_ip: &str| {
+        let mut event = logon("admin", LogonVariant::Network);
+        if let EventDetails::Login(login) = &mut event.details {
+            login.source_ip = source_ip.to_string();
+        }
+        event
+    };
+
+    assert!(suppressor.is_suppressed(&from("192.168.10.4")));
+    assert!(suppressor.is_suppressed(&from("fd12:3456::1")));
+    assert!(!suppressor.is_suppressed(&from("10.0.0.1")));
+    assert!(!suppressor.is_suppressed(&from("-")));
+}
+
+#[test]
+fn test_self_logon_field_filters_self_logons() {
+    let suppressor =
+        Suppressor::from_json(r#"[{"field": "self_logon", "equals": "true"}]"#).unwrap();
+    let with_subject = |subject: &str| {
+        let mut event = logon("SYSTEM", LogonVariant::Service);
+        if let EventDetails::Login(login) = &mut event.details {
+            login.subject = Some(Account::new(subject, None));
+        }
+        event
+    };
+
+    assert!(suppressor.is_suppressed(&with_subject("system")));
+    assert!(!suppressor.is_suppressed(&with_subject("svc_backup")));
+    assert!(!suppressor.is_suppressed(&logon("SYSTEM", LogonVariant::Service)));
+}
@@ -0,0 +1,96 @@
+use chrono::Utc;
+use hosho::enrich::severity::{Condition, SeverityRule};
+use hosho::enrich::{Severity, SeverityPolicy};
+use hosho::listener::lockout::LockoutEvent;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::sink::ecs::to_ecs;
+use hosho::tag::Tagger;
+
+fn logon_from(username: &str, source_ip: &str) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(username, None),
+            source_ip,
+            LogonVariant::Network,
+            false,
+        )),
+        Utc::now(),
+    )
+}
+
+const RULES: &str = r#"[
+    {"tag": "dmz", "when": {"field": "source_ip", "in_network": "10.1.0.0/16"}},
+    {"tag": "admin", "when": {"field": "username", "equals": "administrator"}},
+    {"tag": "dmz", "when": {"field": "source_ip", "in_network": "10.1.2.0/24"}}
+]"#;
+
+#[test]
+fn test_matching_rules_accumulate_tags() {
+    let tagger = Tagger::from_json(RULES).unwrap();
+
+    let mut event = logon_from("Administrator", "10.1.2.3");
+    tagger.tag(&mut event);
+    assert_eq!(event.tags, vec!["dmz", "admin"]);
+
+    let mut event = logon_from("alice", "10.2.0.1");
+    tagger.tag(&mut event);
+    assert!(event.tags.is_empty());
+}
+
+#[test]
+fn test_invalid_networks_are_rejected() {
+    for network in ["10.1.0.0", "10.1.0.0/33", "dmz/16"] {
+        let json = format!(
+            r#"[{{"tag": "dmz", "when": {{"field": "source_ip", "in_network": "{}"}}}}]"#,
+            network
+        );
+        assert!(
+            Tagger::from_json(&json).is_err(),
+            "{} should be rejected",
+            network
+        );
+    }
+}
+
+#[test]
+fn test_tags_reach_severity_and_ecs() {
+    let tagger = Tagger::from_json(RULES).unwrap();
+    let policy = SeverityPolicy::empty().with_rule(SeverityRule::new(
+        vec![Condition::Tagged("dmz".to_string())],
+        Severity::High,
+    ));
+
+    let mut event = logon_from("alice", "10.1.0.9");
+    tagger.tag(&mut event);
+    policy.enrich(&mut event);
+    assert_eq!(event.severity, Severity::High);
+    assert_eq!(to_ecs(&event)["tags"][0], "dmz");
+}
+
+#[test]
+fn test_rules_match_kind_and_event_id() {
+    let tagger = Tagger::from_json(
+        r#"[
+            {"tag": "escalate", "when": {"field": "event_id", "equals": "4740"}},
+            {"tag": "lockout", "when": {"field": "kind", "equals": "lockout"}}
+        ]"#,
+    )
+    .unwrap();
+
+    let mut lockout = Event::new(
+        EventDetails::Lockout(LockoutEvent {
+            account: Account::new("alice", None),
+            caller_computer: Some("WS01".to_string()),
+            causes: Vec::new(),
+            event_record_id: Some(7),
+        }),
+        Utc::now(),
+    );
+    tagger.tag(&mut lockout);
+    assert_eq!(lockout.tags, vec!["escalate", "lockout"]);
+
+    let mut failure = logon_from("alice", "10.2.0.1");
+    tagger.tag(&mut failure);
+    assert!(failure.tags.is_empty());
+}
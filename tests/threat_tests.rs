@@ -0,0 +1,93 @@
+use hosho::listener::applocker::parse_app_blocked_event;
+use hosho::listener::defender::parse_threat_event;
+
+fn defender_event_xml(event_id: u32, action_name: &str) -> String {
+    format!(
+        r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-Windows Defender' Guid='{{11cd958a-c507-4ef3-b3f2-5fd9dfbd2c78}}'/>
+        <EventID>{}</EventID>
+        <TimeCreated SystemTime='2025-07-22T19:14:03.5120000Z'/>
+        <EventRecordID>2210</EventRecordID>
+        <Channel>Microsoft-Windows-Windows Defender/Operational</Channel>
+        <Computer>Ether</Computer>
+    </System>
+    <EventData>
+        <Data Name='Product Name'>Microsoft Defender Antivirus</Data>
+        <Data Name='Threat Name'>Virus:DOS/EICAR_Test_File</Data>
+        <Data Name='Severity Name'>Severe</Data>
+        <Data Name='Category Name'>Virus</Data>
+        <Data Name='Detection User'>ETHER\xevion</Data>
+        <Data Name='Path'>file:_C:\Users\xevion\Downloads\eicar.com</Data>
+        <Data Name='Action Name'>{}</Data>
+        <Data Name='Remediation User'></Data>
+    </EventData>
+</Event>
+        "#,
+        event_id, action_name
+    )
+}
+
+#[test]
+fn test_parse_defender_detection() {
+    let (_, threat) =
+        parse_threat_event(&defender_event_xml(1116, "Not Applicable")).expect("1116 should parse");
+
+    assert_eq!(threat.threat_name, "Virus:DOS/EICAR_Test_File");
+    assert_eq!(
+        threat.path.as_deref(),
+        Some(r"C:\Users\xevion\Downloads\eicar.com")
+    );
+    assert_eq!(threat.severity.as_deref(), Some("Severe"));
+    assert_eq!(threat.action, None);
+}
+
+#[test]
+fn test_parse_defender_action_taken() {
+    let (_, threat) =
+        parse_threat_event(&defender_event_xml(1117, "Quarantine")).expect("1117 should parse");
+
+    assert_eq!(threat.action.as_deref(), Some("Quarantine"));
+}
+
+#[test]
+fn test_parse_app_blocked_event() {
+    let xml = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-AppLocker' Guid='{cbda4dbf-8d5d-4f69-9578-be14aa540d22}'/>
+        <EventID>8004</EventID>
+        <TimeCreated SystemTime='2025-07-22T19:20:41.0010000Z'/>
+        <EventRecordID>77</EventRecordID>
+        <Channel>Microsoft-Windows-AppLocker/EXE and DLL</Channel>
+        <Computer>Ether</Computer>
+    </System>
+    <UserData>
+        <RuleAndFileData xmlns='http://schemas.microsoft.com/schemas/event/Microsoft.Windows/1.0.0.0'>
+            <PolicyNameLength>3</PolicyNameLength>
+            <PolicyName>EXE</PolicyName>
+            <RuleId>{00000000-0000-0000-0000-000000000000}</RuleId>
+            <RuleNameLength>1</RuleNameLength>
+            <RuleName>-</RuleName>
+            <TargetUser>S-1-5-21-1004336348-1177238915-682003330-1001</TargetUser>
+            <TargetProcessId>6120</TargetProcessId>
+            <FilePath>%OSDRIVE%\USERS\XEVION\DOWNLOADS\PAYLOAD.EXE</FilePath>
+        </RuleAndFileData>
+    </UserData>
+</Event>
+        "#;
+
+    let (_, blocked) = parse_app_blocked_event(xml).expect("8004 should parse");
+
+    assert_eq!(
+        blocked.file_path,
+        r"%OSDRIVE%\USERS\XEVION\DOWNLOADS\PAYLOAD.EXE"
+    );
+    assert_eq!(blocked.policy.as_deref(), Some("EXE"));
+    assert_eq!(blocked.rule_name, None);
+    assert_eq!(
+        blocked.user_sid.as_deref(),
+        Some("S-1-5-21-1004336348-1177238915-682003330-1001")
+    );
+}
@@ -0,0 +1,87 @@
+use chrono::{Duration, TimeZone, Utc};
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::time_dedup::TimeDedup;
+
+fn failure(username: &str, second: i64) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(username, None),
+            "203.0.113.7",
+            LogonVariant::Network,
+            false,
+        )),
+        Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap() + Duration::seconds(second),
+    )
+}
+
+#[test]
+fn test_duplicates_suppressed_within_window_and_pass_after() {
+    let dedup = TimeDedup::new(Duration::seconds(30));
+
+    assert!(!dedup.is_duplicate(&failure("alice", 0)));
+    assert!(dedup.is_duplicate(&failure("alice", 10)));
+    assert!(dedup.is_duplicate(&failure("alice", 29)));
+    assert!(
+        !dedup.is_duplicate(&failure("alice", 30)),
+        "the window runs from the last forwarded event"
+    );
+    assert!(dedup.is_duplicate(&failure("alice", 45)));
+}
+
+#[test]
+fn test_different_keys_are_independent() {
+    let dedup = TimeDedup::new(Duration::seconds(30));
+
+    assert!(!dedup.is_duplicate(&failure("alice", 0)));
+    assert!(!dedup.is_duplicate(&failure("bob", 1)));
+
+    let mut success = failure("alice", 2);
+    if let EventDetails::Login(login) = &mut success.details {
+        login.success = true;
+    }
+    assert!(!dedup.is_duplicate(&success));
+}
+
+#[test]
+fn test_custom_fields_widen_the_key() {
+    let dedup = TimeDedup::with_fields(Duration::seconds(30), vec!["source_ip".to_string()]);
+
+    assert!(!dedup.is_duplicate(&failure("alice", 0)));
+    assert!(
+        dedup.is_duplicate(&failure("bob", 5)),
+        "only the source address is compared"
+    );
+}
+
+#[test]
+fn test_operational_events_always_pass() {
+    let dedup = TimeDedup::new(Duration::seconds(30));
+    let at = Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap();
+
+    for seq in 0..3 {
+        let heartbeat = Event::new(EventDetails::Heartbeat { seq }, at + Duration::seconds(1));
+        assert!(!dedup.is_duplicate(&heartbeat), "heartbeat {}", seq);
+    }
+}
+
+#[test]
+fn test_collapsed_failure_with_more_attempts_passes() {
+    let dedup = TimeDedup::new(Duration::seconds(30));
+
+    let mut first = failure("alice", 0);
+    let mut burst = failure("alice", 10);
+    if let EventDetails::Login(login) = &mut first.details {
+        login.attempt_count = 5;
+    }
+    if let EventDetails::Login(login) = &mut burst.details {
+        login.attempt_count = 12;
+    }
+
+    assert!(!dedup.is_duplicate(&first));
+    assert!(!dedup.is_duplicate(&burst));
+    assert!(
+        dedup.is_duplicate(&failure("alice", 15)),
+        "a single attempt adds nothing to the burst already forwarded"
+    );
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "tui")]
+
+use chrono::Utc;
+use hosho::listener::logon::LogonVariant;
+use hosho::listener::{Account, Event, EventDetails, LogonEvent};
+use hosho::tui::{Filter, Monitor};
+
+fn logon(user: &str, variant: LogonVariant, success: bool) -> Event {
+    Event::new(
+        EventDetails::Login(LogonEvent::new(
+            Account::new(user, Some("CORP")),
+            "203.0.113.7",
+            variant,
+            success,
+        )),
+        Utc::now(),
+    )
+}
+
+fn usernames(monitor: &Monitor) -> Vec<String> {
+    monitor
+        .visible()
+        .map(|event| match &event.details {
+            EventDetails::Login(login) => login.target.user.clone(),
+            other => panic!("expected a login, got {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_monitor_keeps_newest_events_up_to_capacity() {
+    let mut monitor = Monitor::new(2);
+    for user in ["alice", "bob", "carol"] {
+        monitor.record(logon(user, LogonVariant::Network, true));
+    }
+    assert_eq!(usernames(&monitor), ["carol", "bob"]);
+}
+
+#[test]
+fn test_filters_apply_to_the_event_list() {
+    let mut monitor = Monitor::new(10);
+    monitor.record(logon("alice", LogonVariant::Network, false));
+    monitor.record(logon("bob", LogonVariant::Network, true));
+    monitor.record(logon("alice", LogonVariant::Interactive, true));
+
+    monitor.filter = Filter {
+        failures_only: true,
+        username: None,
+    };
+    assert_eq!(usernames(&monitor), ["alice"]);
+
+    monitor.filter = Filter {
+        failures_only: false,
+        username: Some("ALICE".to_string()),
+    };
+    assert_eq!(usernames(&monitor), ["alice", "alice"]);
+}
+
+#[test]
+fn test_variant_counts_cover_every_event() {
+    let mut monitor = Monitor::new(1);
+    monitor.record(logon("alice", LogonVariant::Network, false));
+    monitor.record(logon("bob", LogonVariant::Network, true));
+    monitor.record(logon("carol", LogonVariant::Interactive, true));
+
+    assert_eq!(monitor.variant_counts().get("Network"), Some(&2));
+    assert_eq!(monitor.variant_counts().get("Interactive"), Some(&1));
+}
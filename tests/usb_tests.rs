@@ -0,0 +1,73 @@
+use hosho::listener::usb::{UsbAction, parse_usb_event};
+
+#[test]
+fn test_parse_usb_arrival_event() {
+    let xml = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <Provider Name='Microsoft-Windows-DriverFrameworks-UserMode' Guid='{2e35aaeb-857f-4beb-a418-2e6c0e54d988}'/>
+        <EventID>2003</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:25:08.8954670Z'/>
+        <EventRecordID>1024</EventRecordID>
+        <Channel>Microsoft-Windows-DriverFrameworks-UserMode/Operational</Channel>
+        <Computer>Ether</Computer>
+    </System>
+    <UserData>
+        <UMDFHostDeviceArrivalBegin lifetime='{4a8e3d12-5b7c-4f0e-9d1a-6c2b8e7f3a90}' instance='USB\VID_0781&amp;PID_5581\4C530001231120115142'>
+            <FriendlyName>SanDisk Ultra USB Device</FriendlyName>
+        </UMDFHostDeviceArrivalBegin>
+    </UserData>
+</Event>
+        "#;
+
+    let (_, usb_event) = parse_usb_event(xml).expect("parse_usb_event should succeed");
+
+    assert_eq!(
+        usb_event.device_id,
+        r"USB\VID_0781&PID_5581\4C530001231120115142"
+    );
+    assert_eq!(
+        usb_event.friendly_name.as_deref(),
+        Some("SanDisk Ultra USB Device")
+    );
+    assert_eq!(usb_event.action, UsbAction::Connected);
+}
+
+#[test]
+fn test_parse_usb_event_without_friendly_name() {
+    let xml = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>2100</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:30:00.0000000Z'/>
+        <EventRecordID>1025</EventRecordID>
+    </System>
+    <UserData>
+        <UMDFHostDeviceRequest lifetime='{4a8e3d12-5b7c-4f0e-9d1a-6c2b8e7f3a90}' instance='WPDBUSENUMROOT\UMB\2&amp;37C186B&amp;0&amp;STORAGE#VOLUME'>
+            <Request major='27' minor='23'/>
+            <Status>0</Status>
+        </UMDFHostDeviceRequest>
+    </UserData>
+</Event>
+        "#;
+
+    let (_, usb_event) = parse_usb_event(xml).expect("parse_usb_event should succeed");
+
+    assert_eq!(usb_event.friendly_name, None);
+    assert_eq!(usb_event.action, UsbAction::Removed);
+}
+
+#[test]
+fn test_parse_usb_event_missing_device_record() {
+    let xml = r#"
+<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'>
+    <System>
+        <EventID>2003</EventID>
+        <TimeCreated SystemTime='2025-07-22T16:30:00.0000000Z'/>
+    </System>
+    <UserData/>
+</Event>
+        "#;
+
+    assert!(parse_usb_event(xml).is_err());
+}
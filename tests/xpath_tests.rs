@@ -0,0 +1,97 @@
+use hosho::errors::SentinelError;
+use hosho::listener::xpath::XPathQuery;
+use hosho::listener::{EventListener, LogonListener};
+use tokio::sync::mpsc;
+
+const RDP_FAILURES: &str =
+    "*[System[(EventID=4625)]] and *[EventData[Data[@Name='LogonType']='10']]";
+
+#[test]
+fn test_blank_queries_are_rejected() {
+    assert!(matches!(
+        XPathQuery::new("Security", "  "),
+        Err(SentinelError::InvalidXPath(channel, _)) if channel == "Security"
+    ));
+    assert!(matches!(
+        XPathQuery::new("", RDP_FAILURES),
+        Err(SentinelError::ConfigError(_))
+    ));
+}
+
+#[test]
+fn test_query_list_escapes_xpath() {
+    let query = XPathQuery::new("Security", "*[System[(EventID>4624)]]").unwrap();
+
+    assert_eq!(
+        query.to_string(),
+        "<QueryList>\n  <Query Id=\"0\" Path=\"Security\">\n    <Select Path=\"Security\">*[System[(EventID&gt;4624)]]</Select>\n  </Query>\n</QueryList>"
+    );
+}
+
+#[test]
+fn test_listener_uses_xpath_instead_of_builder() {
+    let (tx, _rx) = mpsc::channel(1);
+    let listener = LogonListener::new(tx)
+        .with_xpath("Security", RDP_FAILURES)
+        .unwrap();
+
+    assert_eq!(
+        listener.xpath().as_deref().map(XPathQuery::xpath),
+        Some(RDP_FAILURES)
+    );
+    assert!(listener.query().is_none());
+
+    let (tx, _rx) = mpsc::channel(1);
+    assert!(LogonListener::new(tx).with_xpath("Security", "").is_err());
+}
+
+#[test]
+fn test_logon_types_filter_on_event_data() {
+    let (tx, _rx) = mpsc::channel(1);
+    let listener = LogonListener::new(tx)
+        .with_logon_types(vec![2, 10, 11])
+        .with_event_ids(vec![4624, 4625]);
+
+    let query = listener.xpath().expect("logon types need an XPath query");
+    assert_eq!(query.channel(), "Security");
+    assert_eq!(
+        query.xpath(),
+        "(*[System[(EventID=4624 or EventID=4625)]]) and *[EventData[(Data[@Name='LogonType']='2' or Data[@Name='LogonType']='10' or Data[@Name='LogonType']='11')]]"
+    );
+    assert!(listener.query().is_none());
+}
+
+#[test]
+fn test_no_logon_types_keeps_builder_query() {
+    let (tx, _rx) = mpsc::channel(1);
+    let listener = LogonListener::new(tx).with_logon_types(Vec::new());
+
+    assert!(listener.xpath().is_none());
+    assert!(listener.query().is_some());
+}
+
+#[test]
+fn test_max_age_filters_on_time_created() {
+    let query = XPathQuery::events("Security", &[4625]).with_max_age(chrono::Duration::hours(1));
+
+    assert_eq!(
+        query.xpath(),
+        "(*[System[(EventID=4625)]]) and *[System[TimeCreated[timediff(@SystemTime) <= 3600000]]]"
+    );
+}
+
+#[test]
+fn test_narrowing_applies_to_every_branch_of_a_raw_xpath() {
+    let query = XPathQuery::new(
+        "Security",
+        "*[System[(EventID=4624)]] or *[System[(EventID=4625)]]",
+    )
+    .unwrap()
+    .with_event_data("LogonType", &[10])
+    .with_max_age(chrono::Duration::minutes(5));
+
+    assert_eq!(
+        query.xpath(),
+        "((*[System[(EventID=4624)]] or *[System[(EventID=4625)]]) and *[EventData[(Data[@Name='LogonType']='10')]]) and *[System[TimeCreated[timediff(@SystemTime) <= 300000]]]"
+    );
+}